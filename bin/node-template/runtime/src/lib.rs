@@ -248,6 +248,7 @@ impl pallet_balances::Config for Runtime {
 	type AccountStore = System;
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 	type RuntimeHoldReason = ();
 	type MaxHolds = ();
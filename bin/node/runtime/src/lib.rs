@@ -37,8 +37,8 @@ use frame_support::{
 	traits::{
 		fungible::{Balanced, Credit, ItemOf},
 		tokens::{nonfungibles_v2::Inspect, GetSalary, PayFromAccount},
-		AsEnsureOriginWithArg, ConstBool, ConstU128, ConstU16, ConstU32, Contains, Currency,
-		EitherOfDiverse, EqualPrivilegeOnly, Imbalance, InsideBoth, InstanceFilter,
+		AsEnsureOriginWithArg, ConstBool, ConstU128, ConstU16, ConstU32, ConstU8, Contains,
+		Currency, EitherOfDiverse, EqualPrivilegeOnly, Imbalance, InsideBoth, InstanceFilter,
 		KeyOwnerProofSystem, LockIdentifier, Nothing, OnUnbalanced, WithdrawReasons,
 	},
 	weights::{
@@ -55,7 +55,10 @@ use frame_system::{
 };
 pub use node_primitives::{AccountId, Signature};
 use node_primitives::{AccountIndex, Balance, BlockNumber, Hash, Moment, Nonce};
-use pallet_asset_conversion::{NativeOrAssetId, NativeOrAssetIdConverter};
+use pallet_asset_conversion::{
+	DexHealth, EventLevel, KeepAlivePolicy, LpPnl, NativeOrAssetId, NativeOrAssetIdConverter,
+	PoolInfoSummary,
+};
 use pallet_broker::{CoreAssignment, CoreIndex, CoretimeInterface, PartsOf57600};
 use pallet_election_provider_multi_phase::SolutionAccuracyOf;
 use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
@@ -516,6 +519,7 @@ impl pallet_balances::Config for Runtime {
 	type AccountStore = frame_system::Pallet<Runtime>;
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 	type RuntimeHoldReason = RuntimeHoldReason;
 	type MaxHolds = ConstU32<2>;
@@ -1602,7 +1606,7 @@ impl pallet_assets::Config<Instance2> for Runtime {
 	type MetadataDepositPerByte = MetadataDepositPerByte;
 	type ApprovalDeposit = ApprovalDeposit;
 	type StringLimit = StringLimit;
-	type Freezer = ();
+	type Freezer = pallet_assets::FrozenBalanceOf<AssetConversion>;
 	type Extra = ();
 	type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
 	type RemoveItemsLimit = ConstU32<1000>;
@@ -1617,6 +1621,18 @@ parameter_types! {
 	pub const PoolSetupFee: Balance = 1 * DOLLARS; // should be more or equal to the existential deposit
 	pub const MintMinLiquidity: Balance = 100;  // 100 is good enough when the main currency has 10-12 decimals.
 	pub const LiquidityWithdrawalFee: Permill = Permill::from_percent(0);  // should be non-zero if AllowMultiAssetPools is true, otherwise can be zero.
+	pub const SwapKeepAlivePolicy: KeepAlivePolicy = KeepAlivePolicy::NativeOnly;
+	pub const EventVerbosity: EventLevel = EventLevel::Full;
+	pub const FlashFee: Permill = Permill::from_parts(3_000); // means 0.3%
+	pub FeeTiers: BoundedVec<Permill, ConstU32<4>> = vec![
+		Permill::from_parts(500),
+		Permill::from_parts(3_000),
+		Permill::from_percent(1),
+	].try_into().unwrap();
+	pub const ImpactAlertThreshold: Permill = Permill::from_percent(10);
+	pub const AutoCompoundPeriod: BlockNumber = HOURS;
+	pub const AutoCompoundMaxSlippage: Permill = Permill::from_percent(1);
+	pub const MaxLiquidityLockDuration: BlockNumber = 365 * DAYS;
 }
 
 impl pallet_asset_conversion::Config for Runtime {
@@ -1631,15 +1647,46 @@ impl pallet_asset_conversion::Config for Runtime {
 	type MultiAssetId = NativeOrAssetId<u32>;
 	type PoolAssetId = <Self as pallet_assets::Config<Instance2>>::AssetId;
 	type PalletId = AssetConversionPalletId;
+	type FeeTiers = FeeTiers;
+	type MaxFeeTiers = ConstU32<4>;
 	type LPFee = ConstU32<3>; // means 0.3%
 	type PoolSetupFee = PoolSetupFee;
 	type PoolSetupFeeReceiver = AssetConversionOrigin;
+	type SwapNetworkFee = ConstU128<0>;
+	type NetworkFeeReceiver = ();
 	type LiquidityWithdrawalFee = LiquidityWithdrawalFee;
+	type AllowSingleSidedDust = ConstBool<false>;
+	type SwapKeepAlivePolicy = SwapKeepAlivePolicy;
+	type EventVerbosity = EventVerbosity;
+	type OffchainSignature = Signature;
+	type OffchainPublic = <Signature as traits::Verify>::Signer;
 	type WeightInfo = pallet_asset_conversion::weights::SubstrateWeight<Runtime>;
 	type AllowMultiAssetPools = AllowMultiAssetPools;
 	type MaxSwapPathLength = ConstU32<4>;
 	type MintMinLiquidity = MintMinLiquidity;
 	type MultiAssetIdConverter = NativeOrAssetIdConverter<u32>;
+	type LpTransferPolicy = frame_support::traits::Everything;
+	type AssetAllowList = frame_support::traits::Everything;
+	type MaxReserve = ConstU128<{ u128::MAX }>;
+	type AccountDerivation = pallet_asset_conversion::HashedPoolAccount;
+	type GovernanceOrigin = EnsureRoot<AccountId>;
+	type ProtocolTreasury = TreasuryAccount;
+	type LpTokenDecimals = ConstU8<12>;
+	type OnLpBalanceChange = ();
+	type OnReserveChange = ();
+	type FlashBorrower = ();
+	type FlashFee = FlashFee;
+	type MaxPriceMovePerSwap = ();
+	type ImpactAlertThreshold = ImpactAlertThreshold;
+	type QuoteCacheSize = ConstU32<16>;
+	type MaxLockDuration = MaxLiquidityLockDuration;
+	type SwapCooldown = ();
+	type AutoCompoundTreasury = ();
+	type AutoCompoundPeriod = AutoCompoundPeriod;
+	type AutoCompoundMaxSlippage = AutoCompoundMaxSlippage;
+	type MaxLpMintPerTx = ();
+	type MaxLpRatioImbalance = ();
+	type MaxPoolsPerBatch = ConstU32<20>;
 	#[cfg(feature = "runtime-benchmarks")]
 	type BenchmarkHelper = ();
 }
@@ -2137,6 +2184,14 @@ type Migrations = (
 	pallet_nomination_pools::migration::v2::MigrateToV2<Runtime>,
 	pallet_alliance::migration::Migration<Runtime>,
 	pallet_contracts::Migration<Runtime>,
+	pallet_asset_conversion::migration::v1::MigrateToV1<Runtime>,
+	pallet_asset_conversion::migration::v2::MigrateToV2<Runtime>,
+	pallet_asset_conversion::migration::v3::MigrateToV3<Runtime>,
+	pallet_asset_conversion::migration::v4::MigrateToV4<
+		Runtime,
+		pallet_asset_conversion::HashedPoolAccount,
+	>,
+	pallet_staking::migrations::v14::MigrateToV14<Runtime>,
 );
 
 type EventRecord = frame_system::EventRecord<
@@ -2513,6 +2568,7 @@ impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(2)]
 	impl pallet_asset_conversion::AssetConversionApi<
 		Block,
 		Balance,
@@ -2528,8 +2584,55 @@ impl_runtime_apis! {
 			AssetConversion::quote_price_tokens_for_exact_tokens(asset1, asset2, amount, include_fee)
 		}
 
-		fn get_reserves(asset1: NativeOrAssetId<u32>, asset2: NativeOrAssetId<u32>) -> Option<(Balance, Balance)> {
-			AssetConversion::get_reserves(&asset1, &asset2).ok()
+		fn get_reserves(asset1: NativeOrAssetId<u32>, asset2: NativeOrAssetId<u32>, fee_tier: Permill) -> Option<(Balance, Balance)> {
+			AssetConversion::get_reserves(&asset1, &asset2, fee_tier).ok()
+		}
+
+		fn reserves_at(pool_id: (NativeOrAssetId<u32>, NativeOrAssetId<u32>, Permill)) -> Option<(Balance, Balance)> {
+			AssetConversion::pool_reserves(&pool_id)
+		}
+
+		fn spot_price(asset1: NativeOrAssetId<u32>, asset2: NativeOrAssetId<u32>, fee_tier: Permill) -> Option<(u128, u128)> {
+			AssetConversion::spot_price(asset1, asset2, fee_tier)
+		}
+
+		fn quote_with_slippage(
+			asset1: NativeOrAssetId<u32>,
+			asset2: NativeOrAssetId<u32>,
+			amount_in: u128,
+			slippage: Permill,
+		) -> Option<(u128, u128)> {
+			AssetConversion::quote_with_slippage(asset1, asset2, amount_in, slippage)
+		}
+	}
+
+	impl pallet_asset_conversion::AssetConversionPoolsApi<
+		Block,
+		u128,
+		NativeOrAssetId<u32>,
+		AccountId,
+		u32
+	> for Runtime
+	{
+		fn list_pools(
+			start_key: Option<Vec<u8>>,
+			limit: u32,
+		) -> Vec<((NativeOrAssetId<u32>, NativeOrAssetId<u32>, Permill), PoolInfoSummary<AccountId, u32, u128>)> {
+			AssetConversion::list_pools(start_key, limit)
+		}
+
+		fn dex_health(
+			start_key: Option<Vec<u8>>,
+			limit: u32,
+		) -> DexHealth<(NativeOrAssetId<u32>, NativeOrAssetId<u32>, Permill), u128> {
+			AssetConversion::dex_health(start_key, limit)
+		}
+
+		fn lp_pnl(
+			who: AccountId,
+			pool_id: (NativeOrAssetId<u32>, NativeOrAssetId<u32>, Permill),
+		) -> Option<LpPnl<u128>> {
+			AssetConversion::lp_pnl(&who, &pool_id)
 		}
 	}
 
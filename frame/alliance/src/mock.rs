@@ -86,6 +86,7 @@ impl pallet_balances::Config for Test {
 	type MaxReserves = ();
 	type ReserveIdentifier = [u8; 8];
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 	type RuntimeHoldReason = ();
 	type MaxHolds = ();
@@ -18,18 +18,22 @@
 //! Asset Conversion pallet benchmarking.
 
 use super::*;
-use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_benchmarking::{benchmarks, whitelisted_caller, BenchmarkError};
 use frame_support::{
 	assert_ok,
 	storage::bounded_vec::BoundedVec,
 	traits::{
 		fungible::{Inspect as InspectFungible, Mutate as MutateFungible, Unbalanced},
 		fungibles::{Create, Inspect, Mutate},
+		EnsureOrigin,
 	},
 };
 use frame_system::RawOrigin as SystemOrigin;
 use sp_core::Get;
-use sp_runtime::traits::{Bounded, StaticLookup};
+use sp_runtime::{
+	traits::{Bounded, StaticLookup},
+	Permill,
+};
 use sp_std::{ops::Div, prelude::*};
 
 use crate::Pallet as AssetConversion;
@@ -47,6 +51,11 @@ where
 	(next_id - 1).into()
 }
 
+/// The fee tier of the pools of the benchmarks.
+fn fee_tier<T: Config>() -> Permill {
+	T::FeeTiers::get()[0]
+}
+
 fn create_asset<T: Config>(asset: &T::MultiAssetId) -> (T::AccountId, AccountIdLookupOf<T>)
 where
 	T::AssetBalance: From<u128>,
@@ -81,7 +90,8 @@ where
 	assert_ok!(AssetConversion::<T>::create_pool(
 		SystemOrigin::Signed(caller.clone()).into(),
 		asset1.clone(),
-		asset2.clone()
+		asset2.clone(),
+		fee_tier::<T>()
 	));
 	let lp_token = get_lp_token_id::<T>();
 
@@ -110,10 +120,10 @@ benchmarks! {
 		let asset1 = T::MultiAssetIdConverter::get_native();
 		let asset2 = T::BenchmarkHelper::multiasset_id(0);
 		let (caller, _) = create_asset::<T>(&asset2);
-	}: _(SystemOrigin::Signed(caller.clone()), asset1.clone(), asset2.clone())
+	}: _(SystemOrigin::Signed(caller.clone()), asset1.clone(), asset2.clone(), fee_tier::<T>())
 	verify {
 		let lp_token = get_lp_token_id::<T>();
-		let pool_id = (asset1.clone(), asset2.clone());
+		let pool_id = (asset1.clone(), asset2.clone(), fee_tier::<T>());
 		assert_last_event::<T>(Event::PoolCreated {
 			creator: caller.clone(),
 			pool_account: AssetConversion::<T>::get_pool_account(&pool_id),
@@ -128,9 +138,9 @@ benchmarks! {
 		let (lp_token, caller, _) = create_asset_and_pool::<T>(&asset1, &asset2);
 		let ed: u128 = T::Currency::minimum_balance().into();
 		let add_amount = 1000 + ed;
-	}: _(SystemOrigin::Signed(caller.clone()), asset1.clone(), asset2.clone(), add_amount.into(), 1000.into(), 0.into(), 0.into(), caller.clone())
+	}: _(SystemOrigin::Signed(caller.clone()), asset1.clone(), asset2.clone(), fee_tier::<T>(), add_amount.into(), 1000.into(), 0.into(), 0.into(), caller.clone())
 	verify {
-		let pool_id = (asset1.clone(), asset2.clone());
+		let pool_id = (asset1.clone(), asset2.clone(), fee_tier::<T>());
 		let lp_minted = AssetConversion::<T>::calc_lp_amount_for_zero_supply(&add_amount.into(), &1000.into()).unwrap().into();
 		assert_eq!(
 			T::PoolAssets::balance(lp_token, &caller),
@@ -159,6 +169,7 @@ benchmarks! {
 			SystemOrigin::Signed(caller.clone()).into(),
 			asset1.clone(),
 			asset2.clone(),
+			fee_tier::<T>(),
 			add_amount.into(),
 			1000.into(),
 			0.into(),
@@ -166,7 +177,7 @@ benchmarks! {
 			caller.clone(),
 		)?;
 		let total_supply = <T::PoolAssets as Inspect<T::AccountId>>::total_issuance(lp_token.clone());
-	}: _(SystemOrigin::Signed(caller.clone()), asset1, asset2, remove_lp_amount.into(), 0.into(), 0.into(), caller.clone())
+	}: _(SystemOrigin::Signed(caller.clone()), asset1, asset2, fee_tier::<T>(), remove_lp_amount.into(), 0.into(), 0.into(), caller.clone())
 	verify {
 		let new_total_supply = <T::PoolAssets as Inspect<T::AccountId>>::total_issuance(lp_token.clone());
 		assert_eq!(
@@ -187,6 +198,7 @@ benchmarks! {
 			SystemOrigin::Signed(caller.clone()).into(),
 			native.clone(),
 			asset1.clone(),
+			fee_tier::<T>(),
 			(100 * ed).into(),
 			200.into(),
 			0.into(),
@@ -199,11 +211,12 @@ benchmarks! {
 		// if we only allow the native-asset pools, then the worst case scenario would be to swap
 		// asset1-native-asset2
 		if !T::AllowMultiAssetPools::get() {
-			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), native.clone(), asset2.clone())?;
+			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), native.clone(), asset2.clone(), fee_tier::<T>())?;
 			AssetConversion::<T>::add_liquidity(
 				SystemOrigin::Signed(caller.clone()).into(),
 				native.clone(),
 				asset2.clone(),
+				fee_tier::<T>(),
 				(500 * ed).into(),
 				1000.into(),
 				0.into(),
@@ -214,14 +227,15 @@ benchmarks! {
 			swap_amount = 100.into();
 		} else {
 			let asset3 = T::BenchmarkHelper::multiasset_id(3);
-			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), asset1.clone(), asset2.clone())?;
+			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), asset1.clone(), asset2.clone(), fee_tier::<T>())?;
 			let (_, _) = create_asset::<T>(&asset3);
-			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), asset2.clone(), asset3.clone())?;
+			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), asset2.clone(), asset3.clone(), fee_tier::<T>())?;
 
 			AssetConversion::<T>::add_liquidity(
 				SystemOrigin::Signed(caller.clone()).into(),
 				asset1.clone(),
 				asset2.clone(),
+				fee_tier::<T>(),
 				200.into(),
 				2000.into(),
 				0.into(),
@@ -232,6 +246,7 @@ benchmarks! {
 				SystemOrigin::Signed(caller.clone()).into(),
 				asset2.clone(),
 				asset3.clone(),
+				fee_tier::<T>(),
 				2000.into(),
 				2000.into(),
 				0.into(),
@@ -268,6 +283,7 @@ benchmarks! {
 			SystemOrigin::Signed(caller.clone()).into(),
 			native.clone(),
 			asset1.clone(),
+			fee_tier::<T>(),
 			(1000 * ed).into(),
 			500.into(),
 			0.into(),
@@ -279,11 +295,12 @@ benchmarks! {
 		// if we only allow the native-asset pools, then the worst case scenario would be to swap
 		// asset1-native-asset2
 		if !T::AllowMultiAssetPools::get() {
-			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), native.clone(), asset2.clone())?;
+			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), native.clone(), asset2.clone(), fee_tier::<T>())?;
 			AssetConversion::<T>::add_liquidity(
 				SystemOrigin::Signed(caller.clone()).into(),
 				native.clone(),
 				asset2.clone(),
+				fee_tier::<T>(),
 				(500 * ed).into(),
 				1000.into(),
 				0.into(),
@@ -292,15 +309,16 @@ benchmarks! {
 			)?;
 			path = vec![asset1.clone(), native.clone(), asset2.clone()];
 		} else {
-			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), asset1.clone(), asset2.clone())?;
+			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), asset1.clone(), asset2.clone(), fee_tier::<T>())?;
 			let asset3 = T::BenchmarkHelper::multiasset_id(3);
 			let (_, _) = create_asset::<T>(&asset3);
-			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), asset2.clone(), asset3.clone())?;
+			AssetConversion::<T>::create_pool(SystemOrigin::Signed(caller.clone()).into(), asset2.clone(), asset3.clone(), fee_tier::<T>())?;
 
 			AssetConversion::<T>::add_liquidity(
 				SystemOrigin::Signed(caller.clone()).into(),
 				asset1.clone(),
 				asset2.clone(),
+				fee_tier::<T>(),
 				2000.into(),
 				2000.into(),
 				0.into(),
@@ -311,6 +329,7 @@ benchmarks! {
 				SystemOrigin::Signed(caller.clone()).into(),
 				asset2.clone(),
 				asset3.clone(),
+				fee_tier::<T>(),
 				2000.into(),
 				2000.into(),
 				0.into(),
@@ -334,5 +353,150 @@ benchmarks! {
 		}
 	}
 
+	remove_liquidity_with_signature {
+		let asset1 = T::MultiAssetIdConverter::get_native();
+		let asset2 = T::BenchmarkHelper::multiasset_id(0);
+		let (lp_token, caller, _) = create_asset_and_pool::<T>(&asset1, &asset2);
+		let (signer, owner) = T::BenchmarkHelper::signer();
+		let ed: u128 = T::Currency::minimum_balance().into();
+		let add_amount = 100 * ed;
+		let lp_minted = AssetConversion::<T>::calc_lp_amount_for_zero_supply(&add_amount.into(), &1000.into()).unwrap().into();
+		let remove_lp_amount = lp_minted.checked_div(10).unwrap();
+		// the lp tokens are minted to the signer, so their account has to exist.
+		T::Currency::set_balance(&owner, ed.into());
+
+		AssetConversion::<T>::add_liquidity(
+			SystemOrigin::Signed(caller.clone()).into(),
+			asset1.clone(),
+			asset2.clone(),
+			fee_tier::<T>(),
+			add_amount.into(),
+			1000.into(),
+			0.into(),
+			0.into(),
+			owner.clone(),
+		)?;
+		let permit = RemoveLiquidityPermit {
+			asset1,
+			asset2,
+			fee_tier: fee_tier::<T>(),
+			lp_token_burn: remove_lp_amount.into(),
+			amount1_min_receive: 0.into(),
+			amount2_min_receive: 0.into(),
+			withdraw_to: owner.clone(),
+			deadline: frame_system::Pallet::<T>::block_number(),
+			nonce: 0,
+		};
+		// the wrapped payload is only verified after the raw one failed to.
+		let mut message = b"<Bytes>".to_vec();
+		message.extend(AssetConversion::<T>::permit_payload(&permit));
+		message.extend(b"</Bytes>");
+		let signature = T::BenchmarkHelper::sign(&signer, &message);
+		let total_supply = <T::PoolAssets as Inspect<T::AccountId>>::total_issuance(lp_token.clone());
+	}: _(SystemOrigin::Signed(caller.clone()), owner.clone(), permit, signature)
+	verify {
+		assert_eq!(DexNonces::<T>::get(&owner), 1);
+		let new_total_supply = <T::PoolAssets as Inspect<T::AccountId>>::total_issuance(lp_token.clone());
+		assert_eq!(
+			new_total_supply,
+			total_supply - remove_lp_amount.into()
+		);
+	}
+
+	lock_liquidity {
+		let asset1 = T::MultiAssetIdConverter::get_native();
+		let asset2 = T::BenchmarkHelper::multiasset_id(0);
+		let (lp_token, caller, _) = create_asset_and_pool::<T>(&asset1, &asset2);
+		let ed: u128 = T::Currency::minimum_balance().into();
+
+		AssetConversion::<T>::add_liquidity(
+			SystemOrigin::Signed(caller.clone()).into(),
+			asset1.clone(),
+			asset2.clone(),
+			fee_tier::<T>(),
+			(100 * ed).into(),
+			1000.into(),
+			0.into(),
+			0.into(),
+			caller.clone(),
+		)?;
+		let lp_amount = <T::PoolAssets as Inspect<T::AccountId>>::balance(lp_token, &caller);
+		let lock_until = frame_system::Pallet::<T>::block_number() + T::MaxLockDuration::get();
+	}: _(SystemOrigin::Signed(caller.clone()), asset1.clone(), asset2.clone(), fee_tier::<T>(), lp_amount, lock_until)
+	verify {
+		let pool_id = (asset1, asset2, fee_tier::<T>());
+		assert_eq!(
+			LockedPositions::<T>::get(&caller, &pool_id).map(|position| position.amount),
+			Some(lp_amount)
+		);
+	}
+
+	unlock_liquidity {
+		let asset1 = T::MultiAssetIdConverter::get_native();
+		let asset2 = T::BenchmarkHelper::multiasset_id(0);
+		let (lp_token, caller, _) = create_asset_and_pool::<T>(&asset1, &asset2);
+		let ed: u128 = T::Currency::minimum_balance().into();
+
+		AssetConversion::<T>::add_liquidity(
+			SystemOrigin::Signed(caller.clone()).into(),
+			asset1.clone(),
+			asset2.clone(),
+			fee_tier::<T>(),
+			(100 * ed).into(),
+			1000.into(),
+			0.into(),
+			0.into(),
+			caller.clone(),
+		)?;
+		let lp_amount = <T::PoolAssets as Inspect<T::AccountId>>::balance(lp_token, &caller);
+		let lock_until = frame_system::Pallet::<T>::block_number() + T::MaxLockDuration::get();
+		AssetConversion::<T>::lock_liquidity(
+			SystemOrigin::Signed(caller.clone()).into(),
+			asset1.clone(),
+			asset2.clone(),
+			fee_tier::<T>(),
+			lp_amount,
+			lock_until,
+		)?;
+		// the lp token of a closed pool is only found after missing its pool.
+		let origin = T::GovernanceOrigin::try_successful_origin()
+			.map_err(|_| BenchmarkError::Weightless)?;
+		AssetConversion::<T>::force_close_asset_pools(origin, asset2.clone(), 1, 1)?;
+		frame_system::Pallet::<T>::set_block_number(lock_until);
+	}: _(SystemOrigin::Signed(caller.clone()), asset1.clone(), asset2.clone(), fee_tier::<T>())
+	verify {
+		let pool_id = (asset1, asset2, fee_tier::<T>());
+		assert_eq!(LockedPositions::<T>::get(&caller, &pool_id), None);
+	}
+
+	redeem_closed_pool {
+		let asset1 = T::MultiAssetIdConverter::get_native();
+		let asset2 = T::BenchmarkHelper::multiasset_id(0);
+		let (lp_token, caller, _) = create_asset_and_pool::<T>(&asset1, &asset2);
+		let ed: u128 = T::Currency::minimum_balance().into();
+
+		AssetConversion::<T>::add_liquidity(
+			SystemOrigin::Signed(caller.clone()).into(),
+			asset1.clone(),
+			asset2.clone(),
+			fee_tier::<T>(),
+			(100 * ed).into(),
+			1000.into(),
+			0.into(),
+			0.into(),
+			caller.clone(),
+		)?;
+		let origin = T::GovernanceOrigin::try_successful_origin()
+			.map_err(|_| BenchmarkError::Weightless)?;
+		AssetConversion::<T>::force_close_asset_pools(origin, asset2.clone(), 1, 1)?;
+		// redeeming the last lp tokens also removes the closed pool.
+		let lp_amount = <T::PoolAssets as Inspect<T::AccountId>>::balance(lp_token.clone(), &caller);
+	}: _(SystemOrigin::Signed(caller.clone()), asset1.clone(), asset2.clone(), fee_tier::<T>(), lp_amount, caller.clone())
+	verify {
+		let pool_id = (asset1, asset2, fee_tier::<T>());
+		assert_eq!(<T::PoolAssets as Inspect<T::AccountId>>::total_issuance(lp_token), 0.into());
+		assert_eq!(ClosedPools::<T>::get(&pool_id), None);
+	}
+
 	impl_benchmark_test_suite!(AssetConversion, crate::mock::new_test_ext(), crate::mock::Test);
 }
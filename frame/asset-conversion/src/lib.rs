@@ -34,6 +34,8 @@
 //!    a runtime call endpoint
 //!  - [query the size of a liquidity pool](`AssetConversionApi::get_reserves`) via a runtime api
 //!    endpoint.
+//!  - [list all the liquidity pools](`AssetConversionApi::list_pools`) page by page via a runtime
+//!    api endpoint.
 //!
 //! The `quote_price_exact_tokens_for_tokens` and `quote_price_tokens_for_exact_tokens` functions
 //! both take a path parameter of the route to take. If you want to swap from native asset to
@@ -58,6 +60,7 @@ use frame_support::traits::{DefensiveOption, Incrementable};
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
 
+pub mod migration;
 mod types;
 pub mod weights;
 
@@ -69,8 +72,15 @@ mod mock;
 
 use codec::Codec;
 use frame_support::{
+	dispatch::DispatchResult,
 	ensure,
-	traits::tokens::{AssetId, Balance},
+	traits::{
+		fungibles,
+		tokens::{
+			AssetId, Balance, DepositConsequence, Fortitude, Preservation, Provenance,
+			WithdrawConsequence,
+		},
+	},
 };
 use frame_system::{
 	ensure_signed,
@@ -80,11 +90,12 @@ pub use pallet::*;
 use sp_arithmetic::traits::Unsigned;
 use sp_runtime::{
 	traits::{
-		CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Ensure, MaybeDisplay, TrailingZeroInput,
+		AccountIdConversion, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Ensure,
+		IdentifyAccount, MaybeDisplay, TrailingZeroInput, Verify, Zero,
 	},
 	DispatchError,
 };
-use sp_std::prelude::*;
+use sp_std::{mem, prelude::*};
 pub use types::*;
 pub use weights::WeightInfo;
 
@@ -92,26 +103,37 @@ pub use weights::WeightInfo;
 pub mod pallet {
 	use super::*;
 	use frame_support::{
+		dispatch_context::with_context,
 		pallet_prelude::*,
 		traits::{
 			fungible::{Inspect as InspectFungible, Mutate as MutateFungible},
-			fungibles::{Create, Inspect, Mutate},
+			fungibles::{
+				metadata::{MetadataDeposit, Mutate as MutateMetadata},
+				Create, Inspect, Mutate, MutateFreeze,
+			},
 			tokens::{
 				Fortitude::Polite,
 				Precision::Exact,
-				Preservation::{Expendable, Preserve},
+				Preservation::{self, Expendable, Preserve},
 			},
-			AccountTouch, ContainsPair,
+			AccountTouch, Contains, ContainsPair,
 		},
 		BoundedBTreeSet, PalletId,
 	};
-	use sp_arithmetic::Permill;
+	use sp_arithmetic::{
+		helpers_128bit::multiply_by_rational_with_rounding, FixedPointNumber, FixedU128, PerThing,
+		Permill, Rounding,
+	};
 	use sp_runtime::{
-		traits::{IntegerSquareRoot, One, Zero},
+		traits::{IntegerSquareRoot, One, SaturatedConversion, Zero},
 		Saturating,
 	};
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(4);
+
 	#[pallet::pallet]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
@@ -152,6 +174,12 @@ pub mod pallet {
 		/// necessitate a migration.
 		type MultiAssetId: AssetId + Ord + From<Self::AssetId>;
 
+		/// Derives the account holding the reserves of a pool from the pool id.
+		///
+		/// Any change of the derivation scheme for a live chain necessitates a migration of the
+		/// existing pools.
+		type AccountDerivation: PoolAccountDerivation<Self::AccountId, PoolIdOf<Self>>;
+
 		/// Type to convert an `AssetId` into `MultiAssetId`.
 		type MultiAssetIdConverter: MultiAssetIdConverter<Self::MultiAssetId, Self::AssetId>;
 
@@ -166,15 +194,58 @@ pub mod pallet {
 
 		/// Registry for the lp tokens. Ideally only this pallet should have create permissions on
 		/// the assets.
+		///
+		/// The metadata deposit of an lp token is paid in `Currency` by the pool creator, so unless
+		/// it is zero it must be at least the existential deposit of `Currency`.
+		///
+		/// The freezes of [`Pallet::lock_liquidity`] are kept by this pallet, and only enforced if
+		/// the registry asks it for them, e.g. a `pallet_assets` instance with
+		/// `Freezer = pallet_assets::FrozenBalanceOf<Pallet>`. Otherwise locking fails with
+		/// [`Error::LpFreezeNotEnforced`].
 		type PoolAssets: Inspect<Self::AccountId, AssetId = Self::PoolAssetId, Balance = Self::AssetBalance>
 			+ Create<Self::AccountId>
 			+ Mutate<Self::AccountId>
+			+ MutateMetadata<Self::AccountId>
+			+ MetadataDeposit<Self::Balance>
 			+ AccountTouch<Self::PoolAssetId, Self::AccountId>;
 
-		/// A % the liquidity providers will take of every swap. Represents 10ths of a percent.
+		/// The decimals of the lp tokens, as set in their metadata.
+		#[pallet::constant]
+		type LpTokenDecimals: Get<u8>;
+
+		/// The fee tiers pools can be created with, each the share of the input of every swap
+		/// through a pool that its liquidity providers take.
+		///
+		/// A pair of assets can have a pool in each tier, and swaps go through the one giving the
+		/// best price. Pools of a tier that is removed from the list are no longer swapped through
+		/// by path, see [`Pallet::swap_exact_tokens_for_tokens_in_pool`].
+		#[pallet::constant]
+		type FeeTiers: Get<BoundedVec<Permill, Self::MaxFeeTiers>>;
+
+		/// The max number of `FeeTiers`.
+		#[pallet::constant]
+		type MaxFeeTiers: Get<u32>;
+
+		/// The fee tier of the pools created before storage version 4, see [`migration::v4`].
+		/// Represents 10ths of a percent, and should be one of the `FeeTiers`.
 		#[pallet::constant]
 		type LPFee: Get<u32>;
 
+		/// The number of pools the price quotes of one dispatch remember, so that quoting the same
+		/// pool again within that dispatch does not read its `PoolInfo`. `0` to turn this off.
+		///
+		/// The pools are kept in memory, in the dispatch context, so the cache costs no storage
+		/// access. It is scoped to a single dispatch, not to the block, and only helps consumers
+		/// that quote repeatedly from within one call. Quotes made outside of a dispatch, like the
+		/// ones of the runtime api that frontends poll, get no benefit from it: they read the pool
+		/// every time.
+		///
+		/// Only the pool's existence and fee are remembered. Its reserves are the balances of the
+		/// pool account, which transfers outside of this pallet can change, so they are read by
+		/// every quote.
+		#[pallet::constant]
+		type QuoteCacheSize: Get<u32>;
+
 		/// A one-time fee to setup the pool.
 		#[pallet::constant]
 		type PoolSetupFee: Get<Self::Balance>;
@@ -182,14 +253,61 @@ pub mod pallet {
 		/// An account that receives the pool setup fee.
 		type PoolSetupFeeReceiver: Get<Self::AccountId>;
 
+		/// A flat fee in the native asset taken from the sender of every swap made through the
+		/// calls of this pallet, on top of the fee of the pools.
+		///
+		/// Swaps made through the [`Swap`] trait, e.g. to pay transaction fees in an asset, are not
+		/// charged, as their sender may hold no native asset at all.
+		#[pallet::constant]
+		type SwapNetworkFee: Get<Self::Balance>;
+
+		/// An account that receives the swap network fee. `None` to not charge it.
+		type NetworkFeeReceiver: Get<Option<Self::AccountId>>;
+
 		/// A fee to withdraw the liquidity.
 		#[pallet::constant]
 		type LiquidityWithdrawalFee: Get<Permill>;
 
+		/// Which assets the `keep_alive` flag applies to. Liquidity is always added with
+		/// `keep_alive` set, swaps take it from the caller.
+		///
+		/// With [`KeepAlivePolicy::NativeOnly`], a sender kept alive by a sufficient asset alone
+		/// can still be reaped by giving all of it away.
+		#[pallet::constant]
+		type SwapKeepAlivePolicy: Get<KeepAlivePolicy>;
+
+		/// Whether [`Pallet::remove_liquidity`] may pay out nothing of one of the assets, as long
+		/// as it pays out some of the other one.
+		///
+		/// Burning a small amount of lp tokens of a lopsided pool can round the share of the
+		/// scarce asset down to zero, which would otherwise leave the provider unable to exit.
+		/// Allowing it means that share is forfeited to the remaining providers.
+		#[pallet::constant]
+		type AllowSingleSidedDust: Get<bool>;
+
 		/// The minimum LP token amount that could be minted. Ameliorates rounding errors.
 		#[pallet::constant]
 		type MintMinLiquidity: Get<Self::AssetBalance>;
 
+		/// The most lp tokens a single [`Pallet::add_liquidity`] can mint. `None` for no limit.
+		///
+		/// Larger deposits have to be split over several calls.
+		#[pallet::constant]
+		type MaxLpMintPerTx: Get<Option<Self::AssetBalance>>;
+
+		/// How much the lp tokens a deposit is worth in one asset of the pool may exceed what it is
+		/// worth in the other, as a share of the larger. `None` for no limit.
+		///
+		/// Only the smaller is minted, so the difference is lost to the pool. Deposits are matched
+		/// to the pool's ratio, but for small amounts the rounding of that match alone can make up
+		/// a large share of the deposit.
+		#[pallet::constant]
+		type MaxLpRatioImbalance: Get<Option<Permill>>;
+
+		/// The max number of pools [`Pallet::create_pools`] can create at once.
+		#[pallet::constant]
+		type MaxPoolsPerBatch: Get<u32>;
+
 		/// The max number of hops in a swap.
 		#[pallet::constant]
 		type MaxSwapPathLength: Get<u32>;
@@ -202,19 +320,224 @@ pub mod pallet {
 		#[pallet::constant]
 		type AllowMultiAssetPools: Get<bool>;
 
+		/// The maximum amount of an asset a pool may hold. Deposits that would push a reserve
+		/// beyond it are rejected, keeping the pool math well clear of overflow.
+		#[pallet::constant]
+		type MaxReserve: Get<Self::AssetBalance>;
+
+		/// Filter of the accounts that are allowed to receive lp tokens when liquidity is added.
+		type LpTransferPolicy: Contains<Self::AccountId>;
+
+		/// Filter of the assets pools can be created with. The native currency is always allowed.
+		type AssetAllowList: Contains<Self::AssetId>;
+
+		/// The origin allowed to burn the lp tokens held by the `ProtocolTreasury`.
+		type GovernanceOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The account holding the protocol-owned lp tokens.
+		type ProtocolTreasury: Get<Self::AccountId>;
+
+		/// Transfers the assets the `MultiAssetIdConverter` does not support. `()` to reject them.
+		#[cfg(feature = "nft-pools")]
+		type NftHandler: NftHandler<Self::AccountId, Self::MultiAssetId, Self::AssetBalance>;
+
+		/// Notified whenever lp tokens are minted to or burned from an account, except for the
+		/// `MintMinLiquidity` held by the pool account.
+		type OnLpBalanceChange: OnLpBalanceChange<
+			Self::AccountId,
+			PoolIdOf<Self>,
+			Self::AssetBalance,
+		>;
+
+		/// Notified of the new reserves of a pool whenever a call moves funds in or out of it.
+		type OnReserveChange: OnReserveChange<PoolIdOf<Self>, Self::AssetBalance>;
+
+		/// The receiver of flash loans, called with the borrowed funds by [`Pallet::flash_loan`].
+		type FlashBorrower: FlashBorrower<Self::AccountId, Self::MultiAssetId, Self::AssetBalance>;
+
+		/// The fee charged on top of the principal of a flash loan, paid to the pool.
+		#[pallet::constant]
+		type FlashFee: Get<Permill>;
+
+		/// The most a single swap may move the price of a pool it goes through, regardless of
+		/// the limits set by the trader. `None` for no limit.
+		#[pallet::constant]
+		type MaxPriceMovePerSwap: Get<Option<Permill>>;
+
+		/// A swap moving the price of a pool by more than this emits
+		/// [`Event::LargePriceImpact`], without being rejected.
+		#[pallet::constant]
+		type ImpactAlertThreshold: Get<Permill>;
+
+		/// How much detail the swap and withdrawal events carry, trading the richness of what
+		/// indexers see for block space on chains with many swaps.
+		#[pallet::constant]
+		type EventVerbosity: Get<EventLevel>;
+
+		/// The number of blocks an account has to wait between two swaps. `None` for no limit.
+		///
+		/// This applies to the swaps made through the calls of this pallet. Swaps made through the
+		/// [`Swap`] trait, e.g. to pay transaction fees in an asset, neither wait for nor start a
+		/// cooldown.
+		#[pallet::constant]
+		type SwapCooldown: Get<Option<BlockNumberFor<Self>>>;
+
+		/// The account whose lp tokens are periodically turned into the native asset by
+		/// [`Pallet::auto_compound`], in idle blocks. `None` to turn this off.
+		type AutoCompoundTreasury: Get<Option<Self::AccountId>>;
+
+		/// The number of blocks between two compoundings of a pool by [`Pallet::auto_compound`].
+		/// A pool whose compounding fails waits twice as long after each failure in a row.
+		#[pallet::constant]
+		type AutoCompoundPeriod: Get<BlockNumberFor<Self>>;
+
+		/// How far below the spot price of a pool [`Pallet::auto_compound`] may swap the
+		/// non-native asset into native, the fee of the pool included. Pools swapped through in the
+		/// current block are left for later, so the spot price is the one from before the block.
+		#[pallet::constant]
+		type AutoCompoundMaxSlippage: Get<Permill>;
+
+		/// The longest lp tokens can be locked for by [`Pallet::lock_liquidity`]. A lock of this
+		/// duration doubles the boost multiplier of the position.
+		///
+		/// Locks are only available if `PoolAssets` enforces the freezes of this pallet, see
+		/// [`Config::PoolAssets`].
+		#[pallet::constant]
+		type MaxLockDuration: Get<BlockNumberFor<Self>>;
+
+		/// Off-chain signature type, verifying the permits of
+		/// [`Pallet::remove_liquidity_with_signature`].
+		type OffchainSignature: Verify<Signer = Self::OffchainPublic> + Parameter;
+
+		/// Off-chain public key. Must identify as an on-chain `Self::AccountId`.
+		type OffchainPublic: IdentifyAccount<AccountId = Self::AccountId>;
+
 		/// Weight information for extrinsics in this pallet.
 		type WeightInfo: WeightInfo;
 
-		/// The benchmarks need a way to create asset ids from u32s.
+		/// The benchmarks need a way to create asset ids from u32s, and to sign permits.
 		#[cfg(feature = "runtime-benchmarks")]
-		type BenchmarkHelper: BenchmarkHelper<Self::AssetId, Self::MultiAssetId>;
+		type BenchmarkHelper: BenchmarkHelper<
+			Self::AssetId,
+			Self::MultiAssetId,
+			Self::OffchainPublic,
+			Self::AccountId,
+			Self::OffchainSignature,
+		>;
 	}
 
 	/// Map from `PoolAssetId` to `PoolInfo`. This establishes whether a pool has been officially
 	/// created rather than people sending tokens directly to a pool's public account.
 	#[pallet::storage]
-	pub type Pools<T: Config> =
-		StorageMap<_, Blake2_128Concat, PoolIdOf<T>, PoolInfo<T::PoolAssetId>, OptionQuery>;
+	pub type Pools<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		PoolIdOf<T>,
+		PoolInfo<T::PoolAssetId, BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// Map from the lp token of a pool back to its pool id. The reverse of
+	/// [`PoolInfo::lp_token`].
+	#[pallet::storage]
+	pub type LpTokenToPool<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::PoolAssetId, PoolIdOf<T>, OptionQuery>;
+
+	/// The lp token of each pool closed by [`Pallet::force_close_asset_pools`] whose reserves are
+	/// still held for its lp token holders, see [`Pallet::redeem_closed_pool`].
+	#[pallet::storage]
+	pub type ClosedPools<T: Config> =
+		StorageMap<_, Blake2_128Concat, PoolIdOf<T>, T::PoolAssetId, OptionQuery>;
+
+	/// The asset being wound down by [`Pallet::force_close_asset_pools`] and the last pool it
+	/// visited, which the next call for the same asset continues after.
+	#[pallet::storage]
+	pub type ForceCloseCursor<T: Config> =
+		StorageValue<_, (T::MultiAssetId, PoolIdOf<T>), OptionQuery>;
+
+	/// Lifetime statistics of each pool.
+	#[pallet::storage]
+	pub type PoolStats<T: Config> =
+		StorageMap<_, Blake2_128Concat, PoolIdOf<T>, PoolStatistics<T::AssetBalance>, ValueQuery>;
+
+	/// The block of the last swap of each account, tracked while a `SwapCooldown` is set.
+	#[pallet::storage]
+	pub type LastSwapBlock<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, BlockNumberFor<T>, OptionQuery>;
+
+	/// The last pool visited by [`Pallet::auto_compound`], which continues after it.
+	#[pallet::storage]
+	pub type AutoCompoundCursor<T: Config> = StorageValue<_, PoolIdOf<T>, OptionQuery>;
+
+	/// Where the compounding of each pool by [`Pallet::auto_compound`] left off.
+	#[pallet::storage]
+	pub type CompoundStates<T: Config> =
+		StorageMap<_, Blake2_128Concat, PoolIdOf<T>, CompoundState<BlockNumberFor<T>>, OptionQuery>;
+
+	/// What each account deposited for the lp tokens it was minted in each pool, for
+	/// [`Pallet::lp_pnl`].
+	///
+	/// Burning lp tokens takes their share of the deposit off the position. Lp tokens received by
+	/// transfer are not tracked.
+	#[pallet::storage]
+	pub type LpPositions<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		PoolIdOf<T>,
+		LpPosition<T::AssetBalance>,
+		OptionQuery,
+	>;
+
+	/// The lp tokens each account has locked in each pool, see [`Pallet::lock_liquidity`].
+	#[pallet::storage]
+	pub type LockedPositions<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Blake2_128Concat,
+		PoolIdOf<T>,
+		LockedPosition<T::AssetBalance, BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// The freezes placed on lp tokens, by lp token, account and reason.
+	#[pallet::storage]
+	pub type LpFreezes<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Blake2_128Concat, T::PoolAssetId>,
+			NMapKey<Blake2_128Concat, T::AccountId>,
+			NMapKey<Blake2_128Concat, FreezeReason>,
+		),
+		T::AssetBalance,
+		ValueQuery,
+	>;
+
+	/// The pool whose reserves are currently lent out by a flash loan. The pool can't be traded
+	/// against or have its liquidity changed until the loan is repaid.
+	#[pallet::storage]
+	pub type FlashLoanLock<T: Config> = StorageValue<_, PoolIdOf<T>, OptionQuery>;
+
+	/// The fee charged by every pool instead of its own while set, see
+	/// [`Pallet::set_global_fee`].
+	#[pallet::storage]
+	pub type GlobalFee<T: Config> = StorageValue<_, Permill, OptionQuery>;
+
+	/// The existing pools quoted in the current dispatch and their fees, see
+	/// `Config::QuoteCacheSize`. Cleared whenever a pool is created or removed, or a fee changes.
+	pub(super) struct QuoteCache<PoolId>(Vec<(PoolId, Permill)>);
+
+	impl<PoolId> Default for QuoteCache<PoolId> {
+		fn default() -> Self {
+			Self(Vec::new())
+		}
+	}
+
+	/// The nonce the next [`RemoveLiquidityPermit`] of each account has to be signed with.
+	#[pallet::storage]
+	pub type DexNonces<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, u64, ValueQuery>;
 
 	/// Stores the `PoolAssetId` that is going to be used for the next lp token.
 	/// This gets incremented whenever a new lp pool is created.
@@ -232,7 +555,8 @@ pub mod pallet {
 			/// The pool id associated with the pool. Note that the order of the assets may not be
 			/// the same as the order specified in the create pool extrinsic.
 			pool_id: PoolIdOf<T>,
-			/// The account ID of the pool.
+			/// The account holding the reserves of the pool, derived from the pool id by
+			/// [`Pallet::get_pool_account`]. Deposits to the pool can be watched on it.
 			pool_account: T::AccountId,
 			/// The id of the liquidity tokens that will be minted when assets are added to this
 			/// pool.
@@ -275,6 +599,10 @@ pub mod pallet {
 			lp_token_burned: T::AssetBalance,
 			/// Liquidity withdrawal fee (%).
 			withdrawal_fee: Permill,
+			/// The minimum amount of the first asset the caller accepted to receive.
+			amount1_min_receive: T::AssetBalance,
+			/// The minimum amount of the second asset the caller accepted to receive.
+			amount2_min_receive: T::AssetBalance,
 		},
 		/// Assets have been converted from one to another. Both `SwapExactTokenForToken`
 		/// and `SwapTokenForExactToken` will generate this event.
@@ -290,6 +618,199 @@ pub mod pallet {
 			amount_in: T::AssetBalance,
 			/// The amount of the second asset that was received.
 			amount_out: T::AssetBalance,
+			/// The change of the reserves of the first asset of the pool id formed by the first
+			/// and the last asset of the `path`, i.e. in canonical rather than in the caller's
+			/// orientation. For multi-hop swaps this is the net change along the route.
+			pool_asset1_delta: BalanceDelta<T::AssetBalance>,
+			/// The change of the reserves of the second asset of the pool id formed by the first
+			/// and the last asset of the `path`. See `pool_asset1_delta`.
+			pool_asset2_delta: BalanceDelta<T::AssetBalance>,
+			/// The minimum amount out the caller accepted, for swaps of an exact amount in.
+			amount_out_min: Option<T::AssetBalance>,
+			/// The maximum amount in the caller accepted, for swaps to an exact amount out.
+			amount_in_max: Option<T::AssetBalance>,
+		},
+		/// Like [`Event::SwapExecuted`] without the reserve changes and limits, emitted instead of
+		/// it when the `EventVerbosity` is [`EventLevel::Minimal`].
+		SwapExecutedCompact {
+			/// Which account was the instigator of the swap.
+			who: T::AccountId,
+			/// The account that the assets were transferred to.
+			send_to: T::AccountId,
+			/// The route of asset ids that the swap went through.
+			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
+			/// The amount of the first asset that was swapped.
+			amount_in: T::AssetBalance,
+			/// The amount of the second asset that was received.
+			amount_out: T::AssetBalance,
+		},
+		/// Like [`Event::LiquidityRemoved`] without the withdrawal fee and limits, emitted
+		/// instead of it when the `EventVerbosity` is [`EventLevel::Minimal`].
+		LiquidityRemovedCompact {
+			/// The account that the liquidity tokens were burned from.
+			who: T::AccountId,
+			/// The account that the assets were transferred to.
+			withdraw_to: T::AccountId,
+			/// The pool id that the liquidity was removed from.
+			pool_id: PoolIdOf<T>,
+			/// The amount of the first asset that was removed from the pool.
+			amount1: T::AssetBalance,
+			/// The amount of the second asset that was removed from the pool.
+			amount2: T::AssetBalance,
+			/// The amount of lp tokens that were burned.
+			lp_token_burned: T::AssetBalance,
+		},
+		/// A swap of [`Pallet::try_swap_exact_tokens_for_tokens`] was skipped because it would
+		/// have paid out less than `amount_out_min`.
+		SwapSkipped {
+			/// Which account asked for the swap.
+			who: T::AccountId,
+			/// The route of asset ids the swap would have gone through.
+			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
+			/// The amount of the first asset that would have been swapped.
+			amount_in: T::AssetBalance,
+			/// The minimum amount out the caller accepted.
+			amount_out_min: T::AssetBalance,
+		},
+		/// Protocol-owned lp tokens have been burned without withdrawing the reserves, donating
+		/// that liquidity to the remaining liquidity providers.
+		ProtocolLpBurned {
+			/// The pool id of the pool that the lp tokens belong to.
+			pool_id: PoolIdOf<T>,
+			/// The id of the lp token that was burned.
+			lp_token: T::PoolAssetId,
+			/// The amount of lp tokens that were burned.
+			amount: T::AssetBalance,
+		},
+		/// The reserves left behind the locked minimum liquidity of a pool have been sent to the
+		/// `ProtocolTreasury` and the pool has been removed.
+		MinimumLiquidityReclaimed {
+			/// The pool id of the removed pool.
+			pool_id: PoolIdOf<T>,
+			/// The id of the lp token of the removed pool.
+			lp_token: T::PoolAssetId,
+			/// The amount of the first asset that was reclaimed.
+			amount1: T::AssetBalance,
+			/// The amount of the second asset that was reclaimed.
+			amount2: T::AssetBalance,
+		},
+		/// A pool has been wound down by [`Pallet::force_close_asset_pools`]: it has been removed
+		/// and its reserves are left for its lp token holders to redeem.
+		PoolForceClosed {
+			/// The pool id of the removed pool.
+			pool_id: PoolIdOf<T>,
+			/// The id of the lp token of the removed pool.
+			lp_token: T::PoolAssetId,
+			/// The reserve of the first asset left to redeem.
+			amount1: T::AssetBalance,
+			/// The reserve of the second asset left to redeem.
+			amount2: T::AssetBalance,
+		},
+		/// Lp tokens of a pool closed by [`Pallet::force_close_asset_pools`] have been redeemed
+		/// for their share of its reserves.
+		ClosedPoolRedeemed {
+			/// The account that burned the lp tokens.
+			who: T::AccountId,
+			/// The account that received the reserves.
+			withdraw_to: T::AccountId,
+			/// The pool id of the closed pool.
+			pool_id: PoolIdOf<T>,
+			/// The amount of lp tokens burned.
+			lp_token_burned: T::AssetBalance,
+			/// The amount of the first asset received.
+			amount1: T::AssetBalance,
+			/// The amount of the second asset received.
+			amount2: T::AssetBalance,
+		},
+		/// A share of the reserves of a closed pool could not be paid out by
+		/// [`Pallet::redeem_closed_pool`]. It stays in the pool for the remaining lp token
+		/// holders.
+		ClosedPoolPayoutFailed {
+			/// The pool id of the closed pool.
+			pool_id: PoolIdOf<T>,
+			/// The account that was to receive the share.
+			withdraw_to: T::AccountId,
+			/// The asset that could not be paid out.
+			asset: T::MultiAssetId,
+			/// The amount that could not be paid out.
+			amount: T::AssetBalance,
+			/// The reason the transfer failed.
+			error: DispatchError,
+		},
+		/// A flash loan has been taken out and repaid within the same call.
+		FlashLoan {
+			/// The account that borrowed the funds.
+			borrower: T::AccountId,
+			/// The pool id of the pool that lent the funds.
+			pool_id: PoolIdOf<T>,
+			/// The asset that was borrowed.
+			asset: T::MultiAssetId,
+			/// The amount that was borrowed.
+			amount: T::AssetBalance,
+			/// The fee that was paid to the pool on top of `amount`.
+			fee: T::AssetBalance,
+		},
+		/// A swap has moved the price of a pool by more than the `ImpactAlertThreshold`.
+		LargePriceImpact {
+			/// The pool id of the pool whose price moved.
+			pool_id: PoolIdOf<T>,
+			/// By how much the price of the input asset of the swap went down.
+			impact: Permill,
+		},
+		/// The fees accrued by the liquidity of the `AutoCompoundTreasury` have been turned into
+		/// the native asset.
+		FeesCompounded {
+			/// The pool id of the pool the liquidity was withdrawn from.
+			pool_id: PoolIdOf<T>,
+			/// The amount of lp tokens that were burned.
+			lp_token_burned: T::AssetBalance,
+			/// The amount of the native asset the treasury received in total.
+			native_received: T::AssetBalance,
+		},
+		/// Liquidity of the `AutoCompoundTreasury` could not be compounded. The pool is retried
+		/// after a backoff, and only the first of the failures in a row is reported.
+		CompoundFailed {
+			/// The pool id of the pool that could not be compounded.
+			pool_id: PoolIdOf<T>,
+			/// The reason the compounding failed.
+			error: DispatchError,
+		},
+		/// Lp tokens have been locked, see [`Pallet::lock_liquidity`].
+		LiquidityLocked {
+			/// The account that locked the lp tokens.
+			who: T::AccountId,
+			/// The pool id of the pool of the lp tokens.
+			pool_id: PoolIdOf<T>,
+			/// The amount of lp tokens that were locked by this call.
+			lp_amount: T::AssetBalance,
+			/// The block until which the whole position is locked.
+			lock_until: BlockNumberFor<T>,
+		},
+		/// A locked position has been released, see [`Pallet::unlock_liquidity`].
+		LiquidityUnlocked {
+			/// The account whose lp tokens were unlocked.
+			who: T::AccountId,
+			/// The pool id of the pool of the lp tokens.
+			pool_id: PoolIdOf<T>,
+			/// The amount of lp tokens that were unlocked.
+			lp_amount: T::AssetBalance,
+		},
+		/// The fee of a pool has been changed by [`Pallet::set_pool_fee`].
+		PoolFeeChanged {
+			/// The pool id of the pool whose fee changed.
+			pool_id: PoolIdOf<T>,
+			/// The fee of the pool before the change.
+			old_fee: Permill,
+			/// The fee of the pool after the change.
+			new_fee: Permill,
+		},
+		/// The fee charged by every pool instead of its own has been changed by
+		/// [`Pallet::set_global_fee`]. `None` means each pool charges its own fee.
+		GlobalFeeChanged {
+			/// The global fee before the change.
+			old: Option<Permill>,
+			/// The global fee after the change.
+			new: Option<Permill>,
 		},
 		/// An amount has been transferred from one account to another.
 		Transfer {
@@ -359,21 +880,116 @@ pub mod pallet {
 		PathError,
 		/// The provided path must consists of unique assets.
 		NonUniquePath,
+		/// The provided path is longer than `MaxSwapPathLength`.
+		PathTooLong,
 		/// It was not possible to get or increment the Id of the pool.
 		IncorrectPoolAssetId,
 		/// Unable to find an element in an array/vec that should have one-to-one correspondence
 		/// with another. For example, an array of assets constituting a `path` should have a
 		/// corresponding array of `amounts` along the path.
 		CorrespondenceError,
+		/// There are no more `PoolAssetId`s available for new lp tokens.
+		PoolAssetIdExhausted,
+		/// The account is not allowed to receive lp tokens.
+		LpRecipientNotAllowed,
+		/// The asset is not allowed in pools, see `AssetAllowList`.
+		AssetNotAllowed,
+		/// The deposit would push the reserve of the pool beyond `MaxReserve`.
+		ReserveCapExceeded,
+		/// The requested asset is not part of the pool.
+		AssetNotInPool,
+		/// The protocol treasury doesn't hold enough lp tokens.
+		InsufficientProtocolLp,
+		/// The fee of the pool is, or would be set to, 100%.
+		InvalidFeeConfiguration,
+		/// The fee tier is not one of the `FeeTiers`.
+		InvalidFeeTier,
+		/// The flash loan, together with its fee, wasn't paid back to the pool.
+		FlashLoanNotRepaid,
+		/// The pool is locked by an ongoing flash loan.
+		FlashLoanInProgress,
+		/// The lp tokens minted to an account without any are less than the lp token's minimum
+		/// balance.
+		LpBelowMinBalance,
+		/// Adding the liquidity would mint more lp tokens than `MaxLpMintPerTx`.
+		LpMintTooLarge,
+		/// The two assets of the deposit are worth amounts of lp tokens further apart than the
+		/// `MaxLpRatioImbalance`.
+		RatioImbalance,
+		/// The sender can't pay the amount of the first asset of the pool.
+		InsufficientAssetOneBalance,
+		/// The sender can't pay the amount of the second asset of the pool.
+		InsufficientAssetTwoBalance,
+		/// The sender can't pay the input of the swap.
+		InsufficientBalance,
+		/// The swap would move the price of a pool by more than `MaxPriceMovePerSwap`.
+		PriceMoveTooLarge,
+		/// Lp tokens other than the locked minimum liquidity are still outstanding.
+		PoolStillHasLiquidity,
+		/// The pools of the native asset can't be closed all at once.
+		CannotCloseNativePools,
+		/// At least one pool must be closed.
+		ZeroLimit,
+		/// The pool has been closed and its reserves are still being redeemed.
+		PoolClosed,
+		/// The lock must end after the current block.
+		LockInThePast,
+		/// The lock would last longer than the `MaxLockDuration`.
+		LockTooLong,
+		/// The account does not hold the lp tokens it tries to lock.
+		InsufficientLpTokens,
+		/// The account has no lp tokens locked in the pool.
+		NoLockedLiquidity,
+		/// The lock of the position has not ended yet.
+		LiquidityStillLocked,
+		/// The account swapped less than `SwapCooldown` blocks ago.
+		SwapCooldownActive,
+		/// The pool exists but one of its reserves is empty, e.g. because no liquidity was added
+		/// to it yet.
+		EmptyPool,
+		/// The deadline of the permit has passed.
+		DeadlineExpired,
+		/// The pallet account can't receive lp tokens or the assets of a swap or withdrawal.
+		InvalidRecipient,
+		/// The nonce of the permit is not the owner's current `DexNonces`.
+		InvalidNonce,
+		/// The permit was not signed by its owner.
+		WrongSignature,
+		/// The `PoolAssets` do not enforce the freezes of the locked lp tokens, see
+		/// [`Config::PoolAssets`].
+		LpFreezeNotEnforced,
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_idle(_: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			Self::auto_compound(remaining_weight)
+		}
+
 		fn integrity_test() {
 			assert!(
 				T::MaxSwapPathLength::get() > 1,
 				"the `MaxSwapPathLength` should be greater than 1",
 			);
+			let fee_tiers = T::FeeTiers::get();
+			assert!(!fee_tiers.is_empty(), "the `FeeTiers` should not be empty");
+			assert!(
+				fee_tiers.iter().all(|fee_tier| *fee_tier < Permill::one()),
+				"the `FeeTiers` should be less than 100%",
+			);
+			assert!(T::LPFee::get() < 1000, "the `LPFee` should be less than 100%");
+			assert!(
+				!T::MintMinLiquidity::get().is_zero(),
+				"the `MintMinLiquidity` should be greater than 0",
+			);
+			// the pallet account is truncated to fit `T::AccountId`, so only a collapse into the
+			// all-zero account means it can't be told apart from other accounts.
+			let zero_account = T::AccountId::decode(&mut TrailingZeroInput::zeroes())
+				.expect("infinite length input; no invalid inputs for type; qed");
+			assert!(
+				Self::account_id() != zero_account,
+				"an account should be derivable from the `PalletId`",
+			);
 		}
 	}
 
@@ -383,6 +999,9 @@ pub mod pallet {
 		/// Creates an empty liquidity pool and an associated new `lp_token` asset
 		/// (the id of which is returned in the `Event::PoolCreated` event).
 		///
+		/// The liquidity providers of the pool take `fee_tier` of every swap through it, which
+		/// must be one of the `FeeTiers`. A pair of assets can have a pool in each tier.
+		///
 		/// Once a pool is created, someone may [`Pallet::add_liquidity`] to it.
 		#[pallet::call_index(0)]
 		#[pallet::weight(T::WeightInfo::create_pool())]
@@ -390,17 +1009,27 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			asset1: T::MultiAssetId,
 			asset2: T::MultiAssetId,
+			fee_tier: Permill,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
 			ensure!(asset1 != asset2, Error::<T>::EqualAssets);
+			ensure!(T::FeeTiers::get().contains(&fee_tier), Error::<T>::InvalidFeeTier);
 
 			// prepare pool_id
-			let pool_id = Self::get_pool_id(asset1, asset2);
+			let pool_id = Self::get_pool_id(asset1, asset2, fee_tier);
 			ensure!(!Pools::<T>::contains_key(&pool_id), Error::<T>::PoolExists);
-			let (asset1, asset2) = &pool_id;
+			ensure!(!ClosedPools::<T>::contains_key(&pool_id), Error::<T>::PoolClosed);
+			let (asset1, asset2, _) = &pool_id;
 			if !T::AllowMultiAssetPools::get() && !T::MultiAssetIdConverter::is_native(asset1) {
 				Err(Error::<T>::PoolMustContainNativeCurrency)?;
 			}
+			for asset in [asset1, asset2] {
+				if let MultiAssetIdConversionResult::Converted(asset) =
+					T::MultiAssetIdConverter::try_convert(asset)
+				{
+					ensure!(T::AssetAllowList::contains(&asset), Error::<T>::AssetNotAllowed);
+				}
+			}
 
 			let pool_account = Self::get_pool_account(&pool_id);
 			frame_system::Pallet::<T>::inc_providers(&pool_account);
@@ -434,14 +1063,41 @@ pub mod pallet {
 			let lp_token = NextPoolAssetId::<T>::get()
 				.or(T::PoolAssetId::initial_value())
 				.ok_or(Error::<T>::IncorrectPoolAssetId)?;
-			let next_lp_token_id = lp_token.increment().ok_or(Error::<T>::IncorrectPoolAssetId)?;
+			// `Incrementable` implementations may saturate instead of failing, so make sure we got
+			// a fresh id rather than reusing the current one.
+			let next_lp_token_id = lp_token
+				.increment()
+				.filter(|next| *next > lp_token)
+				.ok_or(Error::<T>::PoolAssetIdExhausted)?;
 			NextPoolAssetId::<T>::set(Some(next_lp_token_id));
 
 			T::PoolAssets::create(lp_token.clone(), pool_account.clone(), false, 1u32.into())?;
 			T::PoolAssets::touch(lp_token.clone(), pool_account.clone(), sender.clone())?;
+			// the metadata deposit is reserved from the owner of the lp token, which is the pool
+			// account, so the creator pays it in.
+			let (name, symbol) = (b"LP".to_vec(), b"LP".to_vec());
+			let metadata_deposit = T::PoolAssets::calc_metadata_deposit(&name, &symbol);
+			if !metadata_deposit.is_zero() {
+				T::Currency::transfer(&sender, &pool_account, metadata_deposit, Preserve)?;
+			}
+			T::PoolAssets::set(
+				lp_token.clone(),
+				&pool_account,
+				name,
+				symbol,
+				T::LpTokenDecimals::get(),
+			)?;
 
-			let pool_info = PoolInfo { lp_token: lp_token.clone() };
+			let now = frame_system::Pallet::<T>::block_number();
+			let pool_info = PoolInfo {
+				lp_token: lp_token.clone(),
+				last_swap_block: now,
+				created_at: now,
+				fee_tier,
+			};
 			Pools::<T>::insert(pool_id.clone(), pool_info);
+			Self::clear_quote_cache();
+			LpTokenToPool::<T>::insert(lp_token.clone(), pool_id.clone());
 
 			Self::deposit_event(Event::PoolCreated {
 				creator: sender,
@@ -453,21 +1109,28 @@ pub mod pallet {
 			Ok(())
 		}
 
-		/// Provide liquidity into the pool of `asset1` and `asset2`.
+		/// Provide liquidity into the pool of `asset1` and `asset2` in the `fee_tier`.
 		/// NOTE: an optimal amount of asset1 and asset2 will be calculated and
 		/// might be different than the provided `amount1_desired`/`amount2_desired`
 		/// thus you should provide the min amount you're happy to provide.
 		/// Params `amount1_min`/`amount2_min` represent that.
 		/// `mint_to` will be sent the liquidity tokens that represent this share of the pool.
 		///
+		/// The assets are always taken from the signer, and the minimums are checked against
+		/// what the signer pays, whoever `mint_to` is. A router can thus add liquidity from its
+		/// own balance on behalf of a user by passing the user as `mint_to`.
+		///
 		/// Once liquidity is added, someone may successfully call
 		/// [`Pallet::swap_exact_tokens_for_tokens`] successfully.
 		#[pallet::call_index(1)]
-		#[pallet::weight(T::WeightInfo::add_liquidity())]
+		#[pallet::weight(
+			T::WeightInfo::add_liquidity().saturating_add(T::OnReserveChange::weight())
+		)]
 		pub fn add_liquidity(
 			origin: OriginFor<T>,
 			asset1: T::MultiAssetId,
 			asset2: T::MultiAssetId,
+			fee_tier: Permill,
 			amount1_desired: T::AssetBalance,
 			amount2_desired: T::AssetBalance,
 			amount1_min: T::AssetBalance,
@@ -475,15 +1138,17 @@ pub mod pallet {
 			mint_to: T::AccountId,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			ensure!(T::LpTransferPolicy::contains(&mint_to), Error::<T>::LpRecipientNotAllowed);
+			Self::ensure_valid_recipient(&mint_to)?;
 
-			let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone());
+			let (pool_id, is_pool_order) =
+				Self::get_pool_id_with_flag(asset1.clone(), asset2.clone(), fee_tier);
 			// swap params if needed
-			let (amount1_desired, amount2_desired, amount1_min, amount2_min) =
-				if pool_id.0 == asset1 {
-					(amount1_desired, amount2_desired, amount1_min, amount2_min)
-				} else {
-					(amount2_desired, amount1_desired, amount2_min, amount1_min)
-				};
+			let (amount1_desired, amount2_desired, amount1_min, amount2_min) = if is_pool_order {
+				(amount1_desired, amount2_desired, amount1_min, amount2_min)
+			} else {
+				(amount2_desired, amount1_desired, amount2_min, amount1_min)
+			};
 			ensure!(
 				amount1_desired > Zero::zero() && amount2_desired > Zero::zero(),
 				Error::<T>::WrongDesiredAmount
@@ -491,72 +1156,85 @@ pub mod pallet {
 
 			let maybe_pool = Pools::<T>::get(&pool_id);
 			let pool = maybe_pool.as_ref().ok_or(Error::<T>::PoolNotFound)?;
+			Self::ensure_not_flash_locked(&pool_id)?;
 			let pool_account = Self::get_pool_account(&pool_id);
 
-			let (asset1, asset2) = &pool_id;
+			let (asset1, asset2, _) = &pool_id;
 			let reserve1 = Self::get_balance(&pool_account, asset1)?;
 			let reserve2 = Self::get_balance(&pool_account, asset2)?;
 
-			let amount1: T::AssetBalance;
-			let amount2: T::AssetBalance;
-			if reserve1.is_zero() || reserve2.is_zero() {
-				amount1 = amount1_desired;
-				amount2 = amount2_desired;
-			} else {
-				let amount2_optimal = Self::quote(&amount1_desired, &reserve1, &reserve2)?;
+			let (amount1, amount2) = Self::calc_add_liquidity_amounts(
+				&amount1_desired,
+				&amount2_desired,
+				&amount1_min,
+				&amount2_min,
+				&reserve1,
+				&reserve2,
+			)?;
 
-				if amount2_optimal <= amount2_desired {
-					ensure!(
-						amount2_optimal >= amount2_min,
-						Error::<T>::AssetTwoDepositDidNotMeetMinimum
-					);
-					amount1 = amount1_desired;
-					amount2 = amount2_optimal;
-				} else {
-					let amount1_optimal = Self::quote(&amount2_desired, &reserve2, &reserve1)?;
-					ensure!(
-						amount1_optimal <= amount1_desired,
-						Error::<T>::OptimalAmountLessThanDesired
-					);
-					ensure!(
-						amount1_optimal >= amount1_min,
-						Error::<T>::AssetOneDepositDidNotMeetMinimum
-					);
-					amount1 = amount1_optimal;
-					amount2 = amount2_desired;
-				}
-			}
+			Self::validate_reserve_cap(&reserve1, &amount1)?;
+			Self::validate_reserve_cap(&reserve2, &amount2)?;
 
 			Self::validate_minimal_amount(amount1.saturating_add(reserve1), asset1)
 				.map_err(|_| Error::<T>::AmountOneLessThanMinimal)?;
 			Self::validate_minimal_amount(amount2.saturating_add(reserve2), asset2)
 				.map_err(|_| Error::<T>::AmountTwoLessThanMinimal)?;
 
+			ensure!(
+				Self::can_pay(asset1, &sender, amount1)?,
+				Error::<T>::InsufficientAssetOneBalance
+			);
+			ensure!(
+				Self::can_pay(asset2, &sender, amount2)?,
+				Error::<T>::InsufficientAssetTwoBalance
+			);
 			Self::transfer(asset1, &sender, &pool_account, amount1, true)?;
 			Self::transfer(asset2, &sender, &pool_account, amount2, true)?;
 
 			let total_supply = T::PoolAssets::total_issuance(pool.lp_token.clone());
+			let lp_token_amount = Self::calc_lp_amount_to_mint(
+				&amount1,
+				&amount2,
+				&reserve1,
+				&reserve2,
+				&total_supply,
+			)?;
 
-			let lp_token_amount: T::AssetBalance;
 			if total_supply.is_zero() {
-				lp_token_amount = Self::calc_lp_amount_for_zero_supply(&amount1, &amount2)?;
 				T::PoolAssets::mint_into(
 					pool.lp_token.clone(),
 					&pool_account,
 					T::MintMinLiquidity::get(),
 				)?;
-			} else {
-				let side1 = Self::mul_div(&amount1, &total_supply, &reserve1)?;
-				let side2 = Self::mul_div(&amount2, &total_supply, &reserve2)?;
-				lp_token_amount = side1.min(side2);
 			}
 
-			ensure!(
-				lp_token_amount > T::MintMinLiquidity::get(),
-				Error::<T>::InsufficientLiquidityMinted
-			);
+			if let Some(max_mint) = T::MaxLpMintPerTx::get() {
+				ensure!(lp_token_amount <= max_mint, Error::<T>::LpMintTooLarge);
+			}
+
+			// minting to an account without lp tokens creates its balance, which has to meet the
+			// minimum balance of the lp token.
+			if T::PoolAssets::balance(pool.lp_token.clone(), &mint_to).is_zero() {
+				ensure!(
+					lp_token_amount >= T::PoolAssets::minimum_balance(pool.lp_token.clone()),
+					Error::<T>::LpBelowMinBalance
+				);
+			}
 
 			T::PoolAssets::mint_into(pool.lp_token.clone(), &mint_to, lp_token_amount)?;
+			T::OnLpBalanceChange::on_lp_balance_change(
+				&mint_to,
+				&pool_id,
+				BalanceDelta::Increase(lp_token_amount),
+			);
+			Self::increase_lp_position(&mint_to, &pool_id, lp_token_amount, amount1, amount2);
+			PoolStats::<T>::mutate(&pool_id, |stats| {
+				stats.lifetime_lp_minted = stats.lifetime_lp_minted.saturating_add(lp_token_amount)
+			});
+			Self::note_reserve_change(&pool_id)?;
+
+			#[cfg(debug_assertions)]
+			Self::ensure_pool_invariant(&pool_id);
 
 			Self::deposit_event(Event::LiquidityAdded {
 				who: sender,
@@ -574,50 +1252,63 @@ pub mod pallet {
 		/// Allows you to remove liquidity by providing the `lp_token_burn` tokens that will be
 		/// burned in the process. With the usage of `amount1_min_receive`/`amount2_min_receive`
 		/// it's possible to control the min amount of returned tokens you're happy with.
+		///
+		/// The lp tokens are always burned from the signer, and the minimums are checked against
+		/// what `withdraw_to` receives. A router holding a user's lp tokens can thus withdraw them
+		/// straight to the user by passing the user as `withdraw_to`.
+		///
+		/// Note that the `MintMinLiquidity` lp tokens minted to the pool account on the first
+		/// deposit can never be redeemed, so the reserves backing them stay in the pool after
+		/// every provider has withdrawn. See [`Pallet::reclaim_minimum_liquidity`].
 		#[pallet::call_index(2)]
-		#[pallet::weight(T::WeightInfo::remove_liquidity())]
+		#[pallet::weight(
+			T::WeightInfo::remove_liquidity().saturating_add(T::OnReserveChange::weight())
+		)]
 		pub fn remove_liquidity(
 			origin: OriginFor<T>,
 			asset1: T::MultiAssetId,
 			asset2: T::MultiAssetId,
+			fee_tier: Permill,
 			lp_token_burn: T::AssetBalance,
 			amount1_min_receive: T::AssetBalance,
 			amount2_min_receive: T::AssetBalance,
 			withdraw_to: T::AccountId,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			Self::ensure_valid_recipient(&withdraw_to)?;
 
-			let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone());
+			let (pool_id, is_pool_order) = Self::get_pool_id_with_flag(asset1, asset2, fee_tier);
 			// swap params if needed
-			let (amount1_min_receive, amount2_min_receive) = if pool_id.0 == asset1 {
+			let (amount1_min_receive, amount2_min_receive) = if is_pool_order {
 				(amount1_min_receive, amount2_min_receive)
 			} else {
 				(amount2_min_receive, amount1_min_receive)
 			};
-			let (asset1, asset2) = pool_id.clone();
+			let (asset1, asset2, _) = pool_id.clone();
 
 			ensure!(lp_token_burn > Zero::zero(), Error::<T>::ZeroLiquidity);
 
 			let maybe_pool = Pools::<T>::get(&pool_id);
 			let pool = maybe_pool.as_ref().ok_or(Error::<T>::PoolNotFound)?;
+			Self::ensure_not_flash_locked(&pool_id)?;
 
 			let pool_account = Self::get_pool_account(&pool_id);
 			let reserve1 = Self::get_balance(&pool_account, &asset1)?;
 			let reserve2 = Self::get_balance(&pool_account, &asset2)?;
 
 			let total_supply = T::PoolAssets::total_issuance(pool.lp_token.clone());
-			let withdrawal_fee_amount = T::LiquidityWithdrawalFee::get() * lp_token_burn;
-			let lp_redeem_amount = lp_token_burn.saturating_sub(withdrawal_fee_amount);
-
-			let amount1 = Self::mul_div(&lp_redeem_amount, &reserve1, &total_supply)?;
-			let amount2 = Self::mul_div(&lp_redeem_amount, &reserve2, &total_supply)?;
+			let (amount1, amount2) =
+				Self::calc_redemption_amounts(&lp_token_burn, &reserve1, &reserve2, &total_supply)?;
 
+			// with `AllowSingleSidedDust` a zero amount is fine as long as the other one isn't.
+			let dust_allowed =
+				T::AllowSingleSidedDust::get() && !(amount1.is_zero() && amount2.is_zero());
 			ensure!(
-				!amount1.is_zero() && amount1 >= amount1_min_receive,
+				(dust_allowed || !amount1.is_zero()) && amount1 >= amount1_min_receive,
 				Error::<T>::AssetOneWithdrawalDidNotMeetMinimum
 			);
 			ensure!(
-				!amount2.is_zero() && amount2 >= amount2_min_receive,
+				(dust_allowed || !amount2.is_zero()) && amount2 >= amount2_min_receive,
 				Error::<T>::AssetTwoWithdrawalDidNotMeetMinimum
 			);
 			let reserve1_left = reserve1.saturating_sub(amount1);
@@ -629,19 +1320,46 @@ pub mod pallet {
 
 			// burn the provided lp token amount that includes the fee
 			T::PoolAssets::burn_from(pool.lp_token.clone(), &sender, lp_token_burn, Exact, Polite)?;
+			T::OnLpBalanceChange::on_lp_balance_change(
+				&sender,
+				&pool_id,
+				BalanceDelta::Decrease(lp_token_burn),
+			);
+			Self::decrease_lp_position(&sender, &pool_id, lp_token_burn);
 
-			Self::transfer(&asset1, &pool_account, &withdraw_to, amount1, false)?;
-			Self::transfer(&asset2, &pool_account, &withdraw_to, amount2, false)?;
+			// the pool account should never be reaped.
+			if !amount1.is_zero() {
+				Self::transfer(&asset1, &pool_account, &withdraw_to, amount1, true)?;
+			}
+			if !amount2.is_zero() {
+				Self::transfer(&asset2, &pool_account, &withdraw_to, amount2, true)?;
+			}
+			Self::note_reserve_change(&pool_id)?;
 
-			Self::deposit_event(Event::LiquidityRemoved {
-				who: sender,
-				withdraw_to,
-				pool_id,
-				amount1,
-				amount2,
-				lp_token: pool.lp_token.clone(),
-				lp_token_burned: lp_token_burn,
-				withdrawal_fee: T::LiquidityWithdrawalFee::get(),
+			#[cfg(debug_assertions)]
+			Self::ensure_pool_invariant(&pool_id);
+
+			Self::deposit_event(match T::EventVerbosity::get() {
+				EventLevel::Full => Event::LiquidityRemoved {
+					who: sender,
+					withdraw_to,
+					pool_id,
+					amount1,
+					amount2,
+					lp_token: pool.lp_token.clone(),
+					lp_token_burned: lp_token_burn,
+					withdrawal_fee: T::LiquidityWithdrawalFee::get(),
+					amount1_min_receive,
+					amount2_min_receive,
+				},
+				EventLevel::Minimal => Event::LiquidityRemovedCompact {
+					who: sender,
+					withdraw_to,
+					pool_id,
+					amount1,
+					amount2,
+					lp_token_burned: lp_token_burn,
+				},
 			});
 
 			Ok(())
@@ -654,7 +1372,10 @@ pub mod pallet {
 		/// [`AssetConversionApi::quote_price_exact_tokens_for_tokens`] runtime call can be called
 		/// for a quote.
 		#[pallet::call_index(3)]
-		#[pallet::weight(T::WeightInfo::swap_exact_tokens_for_tokens())]
+		#[pallet::weight(
+			T::WeightInfo::swap_exact_tokens_for_tokens()
+				.saturating_add(Pallet::<T>::swap_weight_overhead(Pallet::<T>::hops(path)))
+		)]
 		pub fn swap_exact_tokens_for_tokens(
 			origin: OriginFor<T>,
 			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
@@ -664,48 +1385,933 @@ pub mod pallet {
 			keep_alive: bool,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			Self::ensure_swap_cooldown(&sender)?;
 			Self::do_swap_exact_tokens_for_tokens(
-				sender,
+				sender.clone(),
 				path,
-				amount_in,
-				Some(amount_out_min),
+				SwapExactInParams { amount_in, amount_out_min: Some(amount_out_min) },
 				send_to,
 				keep_alive,
 			)?;
-			Ok(())
+			Self::charge_swap_network_fee(&sender, keep_alive)
 		}
 
-		/// Swap any amount of `asset1` to get the exact amount of `asset2`.
-		/// `amount_in_max` param allows to specify the max amount of the `asset1`
-		/// you're happy to provide.
+		/// Like [`Pallet::swap_exact_tokens_for_tokens`], but if the swap would pay out less than
+		/// `amount_out_min`, nothing is swapped and [`Event::SwapSkipped`] is emitted instead of
+		/// failing. Any other error still fails the call.
 		///
-		/// [`AssetConversionApi::quote_price_tokens_for_exact_tokens`] runtime call can be called
-		/// for a quote.
-		#[pallet::call_index(4)]
-		#[pallet::weight(T::WeightInfo::swap_tokens_for_exact_tokens())]
-		pub fn swap_tokens_for_exact_tokens(
+		/// This lets optional legs of a batch be skipped without aborting the batch.
+		#[pallet::call_index(17)]
+		#[pallet::weight(
+			T::WeightInfo::swap_exact_tokens_for_tokens()
+				.saturating_add(Pallet::<T>::swap_weight_overhead(Pallet::<T>::hops(path)))
+		)]
+		pub fn try_swap_exact_tokens_for_tokens(
 			origin: OriginFor<T>,
 			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
-			amount_out: T::AssetBalance,
+			amount_in: T::AssetBalance,
+			amount_out_min: T::AssetBalance,
+			send_to: T::AccountId,
+			keep_alive: bool,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_swap_cooldown(&sender)?;
+			let swapped = Self::do_try_swap_exact_tokens_for_tokens(
+				sender.clone(),
+				path.clone(),
+				amount_in,
+				amount_out_min,
+				send_to,
+				keep_alive,
+			)?;
+			match swapped {
+				Some(_) => Self::charge_swap_network_fee(&sender, keep_alive)?,
+				None => Self::deposit_event(Event::SwapSkipped {
+					who: sender,
+					path,
+					amount_in,
+					amount_out_min,
+				}),
+			}
+			Ok(())
+		}
+
+		/// Swap any amount of `asset1` to get the exact amount of `asset2`.
+		/// `amount_in_max` param allows to specify the max amount of the `asset1`
+		/// you're happy to provide.
+		///
+		/// [`AssetConversionApi::quote_price_tokens_for_exact_tokens`] runtime call can be called
+		/// for a quote.
+		#[pallet::call_index(4)]
+		#[pallet::weight(
+			T::WeightInfo::swap_tokens_for_exact_tokens()
+				.saturating_add(Pallet::<T>::swap_weight_overhead(Pallet::<T>::hops(path)))
+		)]
+		pub fn swap_tokens_for_exact_tokens(
+			origin: OriginFor<T>,
+			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
+			amount_out: T::AssetBalance,
 			amount_in_max: T::AssetBalance,
 			send_to: T::AccountId,
 			keep_alive: bool,
 		) -> DispatchResult {
 			let sender = ensure_signed(origin)?;
+			Self::ensure_swap_cooldown(&sender)?;
 			Self::do_swap_tokens_for_exact_tokens(
-				sender,
+				sender.clone(),
 				path,
-				amount_out,
-				Some(amount_in_max),
+				SwapExactOutParams { amount_out, amount_in_max: Some(amount_in_max) },
 				send_to,
 				keep_alive,
 			)?;
+			Self::charge_swap_network_fee(&sender, keep_alive)
+		}
+
+		/// Remove liquidity by burning `lp_token_burn` and receive the whole withdrawal in
+		/// `asset_out` only. The share of the other asset is swapped back into the same pool,
+		/// after the removal, for more of `asset_out`.
+		///
+		/// `amount_out_min` applies to the final amount of `asset_out` received.
+		#[pallet::call_index(5)]
+		#[pallet::weight(
+			T::WeightInfo::remove_liquidity()
+				.saturating_add(T::WeightInfo::swap_exact_tokens_for_tokens())
+				.saturating_add(Pallet::<T>::swap_weight_overhead(1))
+		)]
+		pub fn remove_liquidity_single(
+			origin: OriginFor<T>,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+			lp_token_burn: T::AssetBalance,
+			asset_out: T::MultiAssetId,
+			amount_out_min: T::AssetBalance,
+			withdraw_to: T::AccountId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_valid_recipient(&withdraw_to)?;
+			Self::ensure_swap_cooldown(&sender)?;
+
+			let pool_id = Self::get_pool_id(asset1, asset2, fee_tier);
+			let (pool_asset1, pool_asset2, _) = pool_id.clone();
+			let asset_other = if asset_out == pool_asset1 {
+				pool_asset2.clone()
+			} else if asset_out == pool_asset2 {
+				pool_asset1.clone()
+			} else {
+				return Err(Error::<T>::AssetNotInPool.into())
+			};
+
+			ensure!(lp_token_burn > Zero::zero(), Error::<T>::ZeroLiquidity);
+
+			let maybe_pool = Pools::<T>::get(&pool_id);
+			let pool = maybe_pool.as_ref().ok_or(Error::<T>::PoolNotFound)?;
+			Self::ensure_not_flash_locked(&pool_id)?;
+			let fee = Self::pool_fee(pool);
+
+			let pool_account = Self::get_pool_account(&pool_id);
+			let reserve_out = Self::get_balance(&pool_account, &asset_out)?;
+			let reserve_other = Self::get_balance(&pool_account, &asset_other)?;
+
+			let total_supply = T::PoolAssets::total_issuance(pool.lp_token.clone());
+			let (amount_removed_out, amount_removed_other) = Self::calc_redemption_amounts(
+				&lp_token_burn,
+				&reserve_out,
+				&reserve_other,
+				&total_supply,
+			)?;
+			ensure!(
+				!amount_removed_out.is_zero() && !amount_removed_other.is_zero(),
+				Error::<T>::ZeroAmount
+			);
+
+			// the other side never leaves the pool: it is swapped straight back in against the
+			// reserves left after the removal, under the same guards as any other swap.
+			let reserve_other_left = reserve_other.saturating_sub(amount_removed_other);
+			let reserve_out_left = reserve_out.saturating_sub(amount_removed_out);
+			let amount_swapped = Self::get_amount_out(
+				&amount_removed_other,
+				&reserve_other_left,
+				&reserve_out_left,
+				fee,
+			)?;
+			let amount_out =
+				amount_removed_out.checked_add(&amount_swapped).ok_or(Error::<T>::Overflow)?;
+			ensure!(amount_out >= amount_out_min, Error::<T>::ProvidedMinimumNotSufficientForSwap);
+
+			Self::validate_minimal_amount(reserve_out.saturating_sub(amount_out), &asset_out)
+				.map_err(|_| Error::<T>::ReserveLeftLessThanMinimal)?;
+			Self::check_price_move(
+				pool_id.clone(),
+				&amount_removed_other,
+				&amount_swapped,
+				&reserve_other_left,
+				&reserve_out_left,
+			)?;
+			Self::charge_swap_network_fee(&sender, true)?;
+
+			// burn the provided lp token amount that includes the fee
+			T::PoolAssets::burn_from(pool.lp_token.clone(), &sender, lp_token_burn, Exact, Polite)?;
+			T::OnLpBalanceChange::on_lp_balance_change(
+				&sender,
+				&pool_id,
+				BalanceDelta::Decrease(lp_token_burn),
+			);
+			Self::decrease_lp_position(&sender, &pool_id, lp_token_burn);
+
+			// the pool account should never be reaped.
+			Self::transfer(&asset_out, &pool_account, &withdraw_to, amount_out, true)?;
+			Self::note_swap(&pool_id);
+			Self::note_reserve_change(&pool_id)?;
+
+			let ((amount1, amount1_min_receive), (amount2, amount2_min_receive)) =
+				if asset_out == pool_asset1 {
+					((amount_removed_out, amount_out_min), (amount_removed_other, Zero::zero()))
+				} else {
+					((amount_removed_other, Zero::zero()), (amount_removed_out, amount_out_min))
+				};
+			let verbosity = T::EventVerbosity::get();
+			Self::deposit_event(match verbosity {
+				EventLevel::Full => Event::LiquidityRemoved {
+					who: sender.clone(),
+					withdraw_to: withdraw_to.clone(),
+					pool_id: pool_id.clone(),
+					amount1,
+					amount2,
+					lp_token: pool.lp_token.clone(),
+					lp_token_burned: lp_token_burn,
+					withdrawal_fee: T::LiquidityWithdrawalFee::get(),
+					// the minimum applies to all of `asset_out` received, swapped share included.
+					amount1_min_receive,
+					amount2_min_receive,
+				},
+				EventLevel::Minimal => Event::LiquidityRemovedCompact {
+					who: sender.clone(),
+					withdraw_to: withdraw_to.clone(),
+					pool_id: pool_id.clone(),
+					amount1,
+					amount2,
+					lp_token_burned: lp_token_burn,
+				},
+			});
+
+			let (pool_asset1_delta, pool_asset2_delta) = if asset_out == pool_asset1 {
+				(
+					BalanceDelta::Decrease(amount_swapped),
+					BalanceDelta::Increase(amount_removed_other),
+				)
+			} else {
+				(
+					BalanceDelta::Increase(amount_removed_other),
+					BalanceDelta::Decrease(amount_swapped),
+				)
+			};
+			#[cfg(debug_assertions)]
+			Self::ensure_pool_invariant(&pool_id);
+
+			let path: BoundedVec<_, T::MaxSwapPathLength> =
+				vec![asset_other, asset_out].try_into().map_err(|_| Error::<T>::PathError)?;
+			Self::deposit_event(match verbosity {
+				EventLevel::Full => Event::SwapExecuted {
+					who: sender,
+					send_to: withdraw_to,
+					path,
+					amount_in: amount_removed_other,
+					amount_out: amount_swapped,
+					pool_asset1_delta,
+					pool_asset2_delta,
+					amount_out_min: None,
+					amount_in_max: None,
+				},
+				EventLevel::Minimal => Event::SwapExecutedCompact {
+					who: sender,
+					send_to: withdraw_to,
+					path,
+					amount_in: amount_removed_other,
+					amount_out: amount_swapped,
+				},
+			});
+
+			Ok(())
+		}
+
+		/// Pay exactly `native_out` of the native currency to `pay_to`, funded by swapping at
+		/// most `max_asset_in` of `asset_in` through the `asset_in`/native pool.
+		///
+		/// This is [`Pallet::swap_tokens_for_exact_tokens`] with a native output, intended for
+		/// payment flows where the recipient must receive an exact amount.
+		#[pallet::call_index(6)]
+		#[pallet::weight(
+			T::WeightInfo::swap_tokens_for_exact_tokens()
+				.saturating_add(Pallet::<T>::swap_weight_overhead(1))
+		)]
+		pub fn pay_exact_native(
+			origin: OriginFor<T>,
+			asset_in: T::MultiAssetId,
+			native_out: T::AssetBalance,
+			max_asset_in: T::AssetBalance,
+			pay_to: T::AccountId,
+			keep_alive: bool,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_swap_cooldown(&sender)?;
+			let native = T::MultiAssetIdConverter::get_native();
+			ensure!(asset_in != native, Error::<T>::EqualAssets);
+			let path = vec![asset_in, native].try_into().map_err(|_| Error::<T>::PathError)?;
+			Self::do_swap_tokens_for_exact_tokens(
+				sender.clone(),
+				path,
+				SwapExactOutParams { amount_out: native_out, amount_in_max: Some(max_asset_in) },
+				pay_to,
+				keep_alive,
+			)?;
+			Self::charge_swap_network_fee(&sender, keep_alive)
+		}
+
+		/// Burn `amount` of the lp tokens of the pool of `asset1` and `asset2` in the `fee_tier`
+		/// held by the `ProtocolTreasury`, without withdrawing the corresponding reserves.
+		///
+		/// The reserves stay in the pool, so every remaining lp token is redeemable for more.
+		#[pallet::call_index(7)]
+		#[pallet::weight(T::WeightInfo::remove_liquidity())]
+		pub fn burn_protocol_lp(
+			origin: OriginFor<T>,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+			amount: T::AssetBalance,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(amount > Zero::zero(), Error::<T>::ZeroLiquidity);
+
+			let pool_id = Self::get_pool_id(asset1, asset2, fee_tier);
+			let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+			Self::ensure_not_flash_locked(&pool_id)?;
+
+			let treasury = T::ProtocolTreasury::get();
+			ensure!(
+				T::PoolAssets::balance(pool.lp_token.clone(), &treasury) >= amount,
+				Error::<T>::InsufficientProtocolLp
+			);
+			let total_supply = T::PoolAssets::total_issuance(pool.lp_token.clone());
+			ensure!(
+				total_supply.saturating_sub(amount) >= T::MintMinLiquidity::get(),
+				Error::<T>::InsufficientLiquidity
+			);
+
+			T::PoolAssets::burn_from(pool.lp_token.clone(), &treasury, amount, Exact, Polite)?;
+			T::OnLpBalanceChange::on_lp_balance_change(
+				&treasury,
+				&pool_id,
+				BalanceDelta::Decrease(amount),
+			);
+			Self::decrease_lp_position(&treasury, &pool_id, amount);
+
+			Self::deposit_event(Event::ProtocolLpBurned {
+				pool_id,
+				lp_token: pool.lp_token,
+				amount,
+			});
+
+			Ok(())
+		}
+
+		/// Swap the exact amount of `asset_in` for the other asset of the pool with the lp token
+		/// `lp_token`.
+		///
+		/// Behaves like [`Pallet::swap_exact_tokens_for_tokens`] with a single hop, for callers
+		/// that already know the pool by its lp token. The swap goes through that pool, even if
+		/// a pool of the same pair in another fee tier gives a better price.
+		#[pallet::call_index(9)]
+		#[pallet::weight(
+			T::WeightInfo::swap_exact_tokens_for_tokens()
+				.saturating_add(Pallet::<T>::swap_weight_overhead(1))
+		)]
+		pub fn swap_exact_tokens_for_tokens_in_pool(
+			origin: OriginFor<T>,
+			lp_token: T::PoolAssetId,
+			asset_in: T::MultiAssetId,
+			amount_in: T::AssetBalance,
+			amount_out_min: T::AssetBalance,
+			send_to: T::AccountId,
+			keep_alive: bool,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			Self::ensure_swap_cooldown(&sender)?;
+			let pool_id = LpTokenToPool::<T>::get(&lp_token).ok_or(Error::<T>::PoolNotFound)?;
+			Self::do_swap_exact_tokens_for_tokens_in_pool(
+				sender.clone(),
+				pool_id,
+				asset_in,
+				SwapExactInParams { amount_in, amount_out_min: Some(amount_out_min) },
+				send_to,
+				keep_alive,
+			)?;
+			Self::charge_swap_network_fee(&sender, keep_alive)
+		}
+
+		/// Recover the reserves left in the pool of `asset1` and `asset2` in the `fee_tier` once
+		/// all lp tokens but the `MintMinLiquidity` locked in the pool account have been burned,
+		/// and remove the pool.
+		///
+		/// The reserves are sent to the `ProtocolTreasury`. The pool can be created again
+		/// afterwards, with a new lp token.
+		#[pallet::call_index(10)]
+		#[pallet::weight(T::WeightInfo::remove_liquidity())]
+		pub fn reclaim_minimum_liquidity(
+			origin: OriginFor<T>,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+
+			let pool_id = Self::get_pool_id(asset1, asset2, fee_tier);
+			let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+			Self::ensure_not_flash_locked(&pool_id)?;
+
+			let pool_account = Self::get_pool_account(&pool_id);
+			let locked = T::PoolAssets::balance(pool.lp_token.clone(), &pool_account);
+			ensure!(
+				T::PoolAssets::total_issuance(pool.lp_token.clone()) == locked,
+				Error::<T>::PoolStillHasLiquidity
+			);
+
+			let (amount1, amount2) = Self::close_pool(&pool_id, &pool.lp_token)?;
+
+			Self::deposit_event(Event::MinimumLiquidityReclaimed {
+				pool_id,
+				lp_token: pool.lp_token,
+				amount1,
+				amount2,
+			});
+
+			Ok(())
+		}
+
+		/// Creates the pool of `asset1` and `asset2` in the `fee_tier` like [`Pallet::create_pool`]
+		/// and provides its initial liquidity of `amount1` and `amount2` in the same call, minting
+		/// at least `lp_min` lp tokens to `mint_to`.
+		///
+		/// As the pool is never empty between two transactions, nobody can set its price before
+		/// the first deposit.
+		#[pallet::call_index(14)]
+		#[pallet::weight(
+			T::WeightInfo::create_pool()
+				.saturating_add(T::WeightInfo::add_liquidity())
+				.saturating_add(T::OnReserveChange::weight())
+		)]
+		pub fn create_pool_with_liquidity(
+			origin: OriginFor<T>,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+			amount1: T::AssetBalance,
+			amount2: T::AssetBalance,
+			lp_min: T::AssetBalance,
+			mint_to: T::AccountId,
+		) -> DispatchResult {
+			Self::create_pool(origin.clone(), asset1.clone(), asset2.clone(), fee_tier)?;
+
+			let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone(), fee_tier);
+			let lp_token = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?.lp_token;
+			let lp_before = T::PoolAssets::balance(lp_token.clone(), &mint_to);
+			Self::add_liquidity(
+				origin,
+				asset1,
+				asset2,
+				fee_tier,
+				amount1,
+				amount2,
+				amount1,
+				amount2,
+				mint_to.clone(),
+			)?;
+			let lp_minted = T::PoolAssets::balance(lp_token, &mint_to).saturating_sub(lp_before);
+			ensure!(lp_minted >= lp_min, Error::<T>::InsufficientLiquidityMinted);
+
+			Ok(())
+		}
+
+		/// Creates a pool for each of the `pairs` of assets and fee tier like
+		/// [`Pallet::create_pool`], paying the setup fee of each one.
+		///
+		/// Pairs whose pool already exists are skipped if `skip_existing` is set, otherwise they
+		/// fail the whole call.
+		#[pallet::call_index(15)]
+		#[pallet::weight(T::WeightInfo::create_pool().saturating_mul(pairs.len() as u64))]
+		pub fn create_pools(
+			origin: OriginFor<T>,
+			pairs: BoundedVec<(T::MultiAssetId, T::MultiAssetId, Permill), T::MaxPoolsPerBatch>,
+			skip_existing: bool,
+		) -> DispatchResult {
+			ensure_signed(origin.clone())?;
+			for (asset1, asset2, fee_tier) in pairs {
+				let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone(), fee_tier);
+				if skip_existing && Pools::<T>::contains_key(&pool_id) {
+					continue
+				}
+				Self::create_pool(origin.clone(), asset1, asset2, fee_tier)?;
+			}
+			Ok(())
+		}
+
+		/// Removes liquidity on behalf of `owner`, like [`Pallet::remove_liquidity`] signed by
+		/// them, with a `permit` they signed off-chain. Anyone can submit it and pays the fees.
+		///
+		/// The permit expires after its `deadline` and can be used only once. The `signature` is
+		/// over [`Pallet::permit_payload`] of it, which ties it to this chain.
+		#[pallet::call_index(16)]
+		#[pallet::weight(
+			T::WeightInfo::remove_liquidity_with_signature()
+				.saturating_add(T::OnReserveChange::weight())
+		)]
+		pub fn remove_liquidity_with_signature(
+			origin: OriginFor<T>,
+			owner: T::AccountId,
+			permit: RemoveLiquidityPermitOf<T>,
+			signature: T::OffchainSignature,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			ensure!(
+				frame_system::Pallet::<T>::block_number() <= permit.deadline,
+				Error::<T>::DeadlineExpired
+			);
+			let nonce = DexNonces::<T>::get(&owner);
+			ensure!(permit.nonce == nonce, Error::<T>::InvalidNonce);
+			Self::validate_signature(&Self::permit_payload(&permit), &signature, &owner)?;
+			DexNonces::<T>::insert(&owner, nonce.saturating_add(1));
+
+			let RemoveLiquidityPermit {
+				asset1,
+				asset2,
+				fee_tier,
+				lp_token_burn,
+				amount1_min_receive,
+				amount2_min_receive,
+				withdraw_to,
+				..
+			} = permit;
+			Self::remove_liquidity(
+				frame_system::RawOrigin::Signed(owner).into(),
+				asset1,
+				asset2,
+				fee_tier,
+				lp_token_burn,
+				amount1_min_receive,
+				amount2_min_receive,
+				withdraw_to,
+			)
+		}
+
+		/// Wind down up to `limit` pools containing `asset`, e.g. once it has been found to be
+		/// malicious. Each pool is removed, so nobody can swap through or add liquidity to it
+		/// anymore, and its reserves are left for the holders of its lp tokens to redeem pro-rata
+		/// with [`Pallet::redeem_closed_pool`].
+		///
+		/// The native asset is in every pool and is rejected. At most `max_visited` pools are read
+		/// per call, continuing after the last pool read by the previous call for the same asset
+		/// (see [`ForceCloseCursor`]), so this can be called again until it fails with
+		/// `PoolNotFound`.
+		#[pallet::call_index(18)]
+		#[pallet::weight(
+			T::WeightInfo::remove_liquidity()
+				.saturating_mul((*limit).into())
+				.saturating_add(
+					T::DbWeight::get().reads_writes(max_visited.saturating_add(1).into(), 1)
+				)
+		)]
+		pub fn force_close_asset_pools(
+			origin: OriginFor<T>,
+			asset: T::MultiAssetId,
+			limit: u32,
+			max_visited: u32,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(
+				!T::MultiAssetIdConverter::is_native(&asset),
+				Error::<T>::CannotCloseNativePools
+			);
+			ensure!(limit > 0 && max_visited > 0, Error::<T>::ZeroLimit);
+
+			let cursor = ForceCloseCursor::<T>::get()
+				.and_then(|(closing, last)| (closing == asset).then_some(last));
+			let from_start = cursor.is_none();
+			let mut iter = match cursor {
+				Some(last) => Pools::<T>::iter_keys_from(Pools::<T>::hashed_key_for(&last)),
+				None => Pools::<T>::iter_keys(),
+			};
+			let mut pool_ids: Vec<PoolIdOf<T>> = Vec::new();
+			let mut last_visited = None;
+			let mut exhausted = false;
+			for _ in 0..max_visited {
+				if pool_ids.len() == limit as usize {
+					break
+				}
+				let Some(pool_id) = iter.next() else {
+					exhausted = true;
+					break
+				};
+				last_visited = Some(pool_id.clone());
+				if pool_id.0 == asset || pool_id.1 == asset {
+					pool_ids.push(pool_id);
+				}
+			}
+			ensure!(!(from_start && exhausted && pool_ids.is_empty()), Error::<T>::PoolNotFound);
+
+			match last_visited {
+				Some(last) if !exhausted => ForceCloseCursor::<T>::put((asset, last)),
+				_ => ForceCloseCursor::<T>::kill(),
+			}
+
+			for pool_id in pool_ids {
+				let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+				Self::ensure_not_flash_locked(&pool_id)?;
+
+				let pool_account = Self::get_pool_account(&pool_id);
+				let locked = T::PoolAssets::balance(pool.lp_token.clone(), &pool_account);
+				if T::PoolAssets::total_issuance(pool.lp_token.clone()) == locked {
+					// nobody is left to redeem the reserves.
+					let (amount1, amount2) = Self::close_pool(&pool_id, &pool.lp_token)?;
+					Self::deposit_event(Event::MinimumLiquidityReclaimed {
+						pool_id,
+						lp_token: pool.lp_token,
+						amount1,
+						amount2,
+					});
+					continue
+				}
+
+				// the lp tokens locked in the pool account are burned, so that the whole reserves
+				// are redeemed by the remaining lp token holders.
+				if !locked.is_zero() {
+					T::PoolAssets::burn_from(
+						pool.lp_token.clone(),
+						&pool_account,
+						locked,
+						Exact,
+						Polite,
+					)?;
+				}
+				let (asset1, asset2, _) = &pool_id;
+				let amount1 = Self::get_balance(&pool_account, asset1)?;
+				let amount2 = Self::get_balance(&pool_account, asset2)?;
+				Self::remove_pool(&pool_id, &pool.lp_token);
+				ClosedPools::<T>::insert(&pool_id, &pool.lp_token);
+
+				Self::deposit_event(Event::PoolForceClosed {
+					pool_id,
+					lp_token: pool.lp_token,
+					amount1,
+					amount2,
+				});
+			}
+
+			Ok(())
+		}
+
+		/// Burn `lp_token_burn` lp tokens of the pool of `asset1` and `asset2` in the `fee_tier`
+		/// closed by [`Pallet::force_close_asset_pools`] and send their share of its reserves to
+		/// `withdraw_to`.
+		///
+		/// Each asset is paid out on its own, as the closed asset may not be transferable anymore,
+		/// e.g. because its owner froze it. A share that can't be paid out is reported with
+		/// [`Event::ClosedPoolPayoutFailed`] and stays in the pool for the remaining lp token
+		/// holders. The call only fails if nothing could be paid out.
+		///
+		/// No `LiquidityWithdrawalFee` is charged. Once all the lp tokens have been redeemed, the
+		/// pool can be created again.
+		#[pallet::call_index(19)]
+		#[pallet::weight(
+			T::WeightInfo::redeem_closed_pool().saturating_add(T::OnReserveChange::weight())
+		)]
+		pub fn redeem_closed_pool(
+			origin: OriginFor<T>,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+			lp_token_burn: T::AssetBalance,
+			withdraw_to: T::AccountId,
+		) -> DispatchResult {
+			let sender = ensure_signed(origin)?;
+			ensure!(lp_token_burn > Zero::zero(), Error::<T>::ZeroLiquidity);
+			Self::ensure_valid_recipient(&withdraw_to)?;
+
+			let pool_id = Self::get_pool_id(asset1, asset2, fee_tier);
+			let lp_token = ClosedPools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+			let pool_account = Self::get_pool_account(&pool_id);
+			let (asset1, asset2, _) = &pool_id;
+			// a frozen reserve can't be reduced, but it is still owed to the lp token holders.
+			let reserve1 = Self::get_reserve(&pool_account, asset1)?;
+			let reserve2 = Self::get_reserve(&pool_account, asset2)?;
+			let total_supply = T::PoolAssets::total_issuance(lp_token.clone());
+			let amount1 = Self::mul_div(&lp_token_burn, &reserve1, &total_supply)?;
+			let amount2 = Self::mul_div(&lp_token_burn, &reserve2, &total_supply)?;
+
+			T::PoolAssets::burn_from(lp_token.clone(), &sender, lp_token_burn, Exact, Polite)?;
+			T::OnLpBalanceChange::on_lp_balance_change(
+				&sender,
+				&pool_id,
+				BalanceDelta::Decrease(lp_token_burn),
+			);
+			Self::decrease_lp_position(&sender, &pool_id, lp_token_burn);
+
+			// the pool account is reaped with the last redemption.
+			let paid1 = Self::pay_out_closed_pool(&pool_id, asset1, &withdraw_to, amount1);
+			let paid2 = Self::pay_out_closed_pool(&pool_id, asset2, &withdraw_to, amount2);
+			let (amount1, amount2) = match (paid1, paid2) {
+				(Err(error), Err(_)) => return Err(error),
+				(Err(error), Ok(paid)) | (Ok(paid), Err(error)) if paid.is_zero() =>
+					return Err(error),
+				(paid1, paid2) => (paid1.unwrap_or_default(), paid2.unwrap_or_default()),
+			};
+			Self::note_reserve_change(&pool_id)?;
+			if T::PoolAssets::total_issuance(lp_token).is_zero() {
+				ClosedPools::<T>::remove(&pool_id);
+			}
+
+			Self::deposit_event(Event::ClosedPoolRedeemed {
+				who: sender,
+				withdraw_to,
+				pool_id,
+				lp_token_burned: lp_token_burn,
+				amount1,
+				amount2,
+			});
+
+			Ok(())
+		}
+
+		/// Borrow `amount` of `asset_to_borrow` from the pool of `asset1` and `asset2` in the
+		/// `fee_tier` for the duration of this call.
+		///
+		/// The funds are sent to the caller and `T::FlashBorrower` is called with them and
+		/// `callback_data`. By the time it returns, `amount` plus the `FlashFee` must have been
+		/// paid back to the pool, otherwise the whole call is reverted. The pool is locked while
+		/// the loan is outstanding.
+		#[pallet::call_index(8)]
+		#[pallet::weight(
+			T::WeightInfo::swap_exact_tokens_for_tokens()
+				.saturating_add(T::FlashBorrower::weight(callback_data))
+				.saturating_add(T::OnReserveChange::weight())
+		)]
+		pub fn flash_loan(
+			origin: OriginFor<T>,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+			asset_to_borrow: T::MultiAssetId,
+			amount: T::AssetBalance,
+			callback_data: Vec<u8>,
+		) -> DispatchResult {
+			let borrower = ensure_signed(origin)?;
+			ensure!(amount > Zero::zero(), Error::<T>::ZeroAmount);
+			// loans can't be nested, not even against another pool.
+			ensure!(FlashLoanLock::<T>::get().is_none(), Error::<T>::FlashLoanInProgress);
+
+			let pool_id = Self::get_pool_id(asset1, asset2, fee_tier);
+			ensure!(Pools::<T>::contains_key(&pool_id), Error::<T>::PoolNotFound);
+			let (pool_asset1, pool_asset2, _) = &pool_id;
+			let asset_other = if asset_to_borrow == *pool_asset1 {
+				pool_asset2
+			} else if asset_to_borrow == *pool_asset2 {
+				pool_asset1
+			} else {
+				return Err(Error::<T>::AssetNotInPool.into())
+			};
+
+			let pool_account = Self::get_pool_account(&pool_id);
+			let reserve = Self::get_balance(&pool_account, &asset_to_borrow)?;
+			let reserve_other = Self::get_balance(&pool_account, asset_other)?;
+			ensure!(amount < reserve, Error::<T>::AmountOutTooHigh);
+
+			let fee = T::FlashFee::get().mul_ceil(amount);
+			let reserve_owed = reserve.checked_add(&fee).ok_or(Error::<T>::Overflow)?;
+
+			FlashLoanLock::<T>::put(&pool_id);
+			Self::transfer(&asset_to_borrow, &pool_account, &borrower, amount, true)?;
+			T::FlashBorrower::on_flash_loan(
+				&borrower,
+				&pool_account,
+				&asset_to_borrow,
+				amount,
+				fee,
+				&callback_data,
+			)?;
+			FlashLoanLock::<T>::kill();
+
+			ensure!(
+				Self::get_balance(&pool_account, &asset_to_borrow)? >= reserve_owed &&
+					Self::get_balance(&pool_account, asset_other)? >= reserve_other,
+				Error::<T>::FlashLoanNotRepaid
+			);
+			Self::note_reserve_change(&pool_id)?;
+
+			Self::deposit_event(Event::FlashLoan {
+				borrower,
+				pool_id,
+				asset: asset_to_borrow,
+				amount,
+				fee,
+			});
+
+			Ok(())
+		}
+
+		/// Lock `lp_amount` of the lp tokens of the pool of `asset1` and `asset2` in the
+		/// `fee_tier` until the block `lock_until`, in exchange for a boost multiplier, see
+		/// [`Pallet::boost_multiplier`].
+		///
+		/// The lp tokens are frozen in place, so they can neither be transferred nor used to
+		/// remove liquidity until [`Pallet::unlock_liquidity`] is called after the lock ended.
+		/// Fails with [`Error::LpFreezeNotEnforced`] if the `PoolAssets` don't enforce the freeze.
+		/// Locking more lp tokens of the same pool adds them to the existing position, which is
+		/// then locked until the later of the two blocks.
+		#[pallet::call_index(12)]
+		#[pallet::weight(T::WeightInfo::lock_liquidity())]
+		pub fn lock_liquidity(
+			origin: OriginFor<T>,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+			lp_amount: T::AssetBalance,
+			lock_until: BlockNumberFor<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(lp_amount > Zero::zero(), Error::<T>::ZeroAmount);
+
+			let pool_id = Self::get_pool_id(asset1, asset2, fee_tier);
+			let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
+
+			let mut position =
+				LockedPositions::<T>::get(&who, &pool_id).unwrap_or(LockedPosition {
+					amount: Zero::zero(),
+					lock_until,
+					boost: FixedU128::one(),
+				});
+			position.amount =
+				position.amount.checked_add(&lp_amount).ok_or(Error::<T>::Overflow)?;
+			position.lock_until = position.lock_until.max(lock_until);
+
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(position.lock_until > now, Error::<T>::LockInThePast);
+			let duration = position.lock_until - now;
+			let max_duration = T::MaxLockDuration::get();
+			ensure!(duration <= max_duration, Error::<T>::LockTooLong);
+			ensure!(
+				T::PoolAssets::balance(pool.lp_token.clone(), &who) >= position.amount,
+				Error::<T>::InsufficientLpTokens
+			);
+			position.boost = FixedU128::one().saturating_add(FixedU128::saturating_from_rational(
+				duration.saturated_into::<u128>(),
+				max_duration.saturated_into::<u128>(),
+			));
+
+			<Self as MutateFreeze<T::AccountId>>::set_freeze(
+				pool.lp_token.clone(),
+				&FreezeReason::LiquidityLock,
+				&who,
+				position.amount,
+			)?;
+			// the boost is only earned if the locked lp tokens can't be moved.
+			let balance = T::PoolAssets::balance(pool.lp_token.clone(), &who);
+			ensure!(
+				T::PoolAssets::reducible_balance(pool.lp_token, &who, Expendable, Polite) <=
+					balance.saturating_sub(position.amount),
+				Error::<T>::LpFreezeNotEnforced
+			);
+			let lock_until = position.lock_until;
+			LockedPositions::<T>::insert(&who, &pool_id, position);
+
+			Self::deposit_event(Event::LiquidityLocked { who, pool_id, lp_amount, lock_until });
+			Ok(())
+		}
+
+		/// Release the lp tokens locked by [`Pallet::lock_liquidity`] in the pool of `asset1` and
+		/// `asset2` in the `fee_tier`, once the lock has ended.
+		#[pallet::call_index(13)]
+		#[pallet::weight(T::WeightInfo::unlock_liquidity())]
+		pub fn unlock_liquidity(
+			origin: OriginFor<T>,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let pool_id = Self::get_pool_id(asset1, asset2, fee_tier);
+			let position =
+				LockedPositions::<T>::get(&who, &pool_id).ok_or(Error::<T>::NoLockedLiquidity)?;
+			ensure!(
+				frame_system::Pallet::<T>::block_number() >= position.lock_until,
+				Error::<T>::LiquidityStillLocked
+			);
+
+			// the lp tokens of a closed pool can still be unlocked to be redeemed.
+			let lp_token = Pools::<T>::get(&pool_id)
+				.map(|pool| pool.lp_token)
+				.or_else(|| ClosedPools::<T>::get(&pool_id))
+				.ok_or(Error::<T>::PoolNotFound)?;
+			<Self as MutateFreeze<T::AccountId>>::thaw(
+				lp_token,
+				&FreezeReason::LiquidityLock,
+				&who,
+			)?;
+			LockedPositions::<T>::remove(&who, &pool_id);
+
+			Self::deposit_event(Event::LiquidityUnlocked {
+				who,
+				pool_id,
+				lp_amount: position.amount,
+			});
+			Ok(())
+		}
+
+		/// Set the fee charged by the pool of `asset1` and `asset2` in the `fee_tier` to
+		/// `new_fee`.
+		///
+		/// The pool keeps its id, and so its account and lp token; only the fee taken from the
+		/// input of the swaps through it changes. A [`GlobalFee`] takes precedence while set.
+		#[pallet::call_index(20)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_pool_fee(
+			origin: OriginFor<T>,
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+			new_fee: Permill,
+		) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(new_fee < Permill::one(), Error::<T>::InvalidFeeConfiguration);
+
+			let pool_id = Self::get_pool_id(asset1, asset2, fee_tier);
+			let old_fee = Pools::<T>::try_mutate(&pool_id, |maybe_pool| {
+				let pool = maybe_pool.as_mut().ok_or(Error::<T>::PoolNotFound)?;
+				Ok::<_, Error<T>>(mem::replace(&mut pool.fee_tier, new_fee))
+			})?;
+			Self::clear_quote_cache();
+
+			Self::deposit_event(Event::PoolFeeChanged { pool_id, old_fee, new_fee });
+			Ok(())
+		}
+
+		/// Make every pool charge `fee` instead of its own, or let each pool charge its own fee
+		/// again if `fee` is `None`.
+		#[pallet::call_index(21)]
+		#[pallet::weight(T::DbWeight::get().reads_writes(1, 1))]
+		pub fn set_global_fee(origin: OriginFor<T>, fee: Option<Permill>) -> DispatchResult {
+			T::GovernanceOrigin::ensure_origin(origin)?;
+			ensure!(fee != Some(Permill::one()), Error::<T>::InvalidFeeConfiguration);
+
+			let old = GlobalFee::<T>::mutate(|global_fee| mem::replace(global_fee, fee));
+			Self::clear_quote_cache();
+
+			Self::deposit_event(Event::GlobalFeeChanged { old, new: fee });
 			Ok(())
 		}
 	}
 
 	impl<T: Config> Pallet<T> {
-		/// Swap exactly `amount_in` of asset `path[0]` for asset `path[1]`.
+		/// Swap exactly `params.amount_in` of asset `path[0]` for asset `path[1]`.
 		/// If an `amount_out_min` is specified, it will return an error if it is unable to acquire
 		/// the amount desired.
 		///
@@ -716,11 +2322,11 @@ pub mod pallet {
 		pub fn do_swap_exact_tokens_for_tokens(
 			sender: T::AccountId,
 			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
-			amount_in: T::AssetBalance,
-			amount_out_min: Option<T::AssetBalance>,
+			params: SwapExactInParams<T::AssetBalance>,
 			send_to: T::AccountId,
 			keep_alive: bool,
 		) -> Result<T::AssetBalance, DispatchError> {
+			let SwapExactInParams { amount_in, amount_out_min } = params;
 			ensure!(amount_in > Zero::zero(), Error::<T>::ZeroAmount);
 			if let Some(amount_out_min) = amount_out_min {
 				ensure!(amount_out_min > Zero::zero(), Error::<T>::ZeroAmount);
@@ -728,7 +2334,7 @@ pub mod pallet {
 
 			Self::validate_swap_path(&path)?;
 
-			let amounts = Self::get_amounts_out(&amount_in, &path)?;
+			let (amounts, pools) = Self::get_amounts_out(&amount_in, &path)?;
 			let amount_out =
 				*amounts.last().defensive_ok_or("get_amounts_out() returned an empty result")?;
 
@@ -739,13 +2345,99 @@ pub mod pallet {
 				);
 			}
 
-			Self::do_swap(sender, &amounts, path, send_to, keep_alive)?;
+			Self::do_swap(
+				sender,
+				&amounts,
+				&pools,
+				path,
+				send_to,
+				keep_alive,
+				amount_out_min,
+				None,
+			)?;
 			Ok(amount_out)
 		}
 
-		/// Take the `path[0]` asset and swap some amount for `amount_out` of the `path[1]`. If an
-		/// `amount_in_max` is specified, it will return an error if acquiring `amount_out` would be
-		/// too costly.
+		/// Like [`Pallet::do_swap_exact_tokens_for_tokens`] with a single hop, going through the
+		/// pool with `pool_id` rather than the pool of the pair giving the best price.
+		pub fn do_swap_exact_tokens_for_tokens_in_pool(
+			sender: T::AccountId,
+			pool_id: PoolIdOf<T>,
+			asset_in: T::MultiAssetId,
+			params: SwapExactInParams<T::AssetBalance>,
+			send_to: T::AccountId,
+			keep_alive: bool,
+		) -> Result<T::AssetBalance, DispatchError> {
+			let SwapExactInParams { amount_in, amount_out_min } = params;
+			ensure!(amount_in > Zero::zero(), Error::<T>::ZeroAmount);
+			if let Some(amount_out_min) = amount_out_min {
+				ensure!(amount_out_min > Zero::zero(), Error::<T>::ZeroAmount);
+			}
+			let (asset1, asset2, fee_tier) = pool_id.clone();
+			let asset_out = if asset_in == asset1 {
+				asset2
+			} else if asset_in == asset2 {
+				asset1
+			} else {
+				return Err(Error::<T>::AssetNotInPool.into())
+			};
+
+			let fee = Self::swap_pool_fee(&pool_id)?;
+			let (reserve_in, reserve_out) = Self::swap_reserves(&asset_in, &asset_out, fee_tier)?;
+			let amount_out = Self::get_amount_out(&amount_in, &reserve_in, &reserve_out, fee)?;
+			if let Some(amount_out_min) = amount_out_min {
+				ensure!(
+					amount_out >= amount_out_min,
+					Error::<T>::ProvidedMinimumNotSufficientForSwap
+				);
+			}
+
+			let path = vec![asset_in, asset_out].try_into().map_err(|_| Error::<T>::PathError)?;
+			Self::do_swap(
+				sender,
+				&vec![amount_in, amount_out],
+				&vec![pool_id],
+				path,
+				send_to,
+				keep_alive,
+				amount_out_min,
+				None,
+			)?;
+			Ok(amount_out)
+		}
+
+		/// Like [`Pallet::do_swap_exact_tokens_for_tokens`], returning `Ok(None)` without taking
+		/// any funds if the swap would pay out less than `amount_out_min`.
+		pub fn do_try_swap_exact_tokens_for_tokens(
+			sender: T::AccountId,
+			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
+			amount_in: T::AssetBalance,
+			amount_out_min: T::AssetBalance,
+			send_to: T::AccountId,
+			keep_alive: bool,
+		) -> Result<Option<T::AssetBalance>, DispatchError> {
+			ensure!(amount_in > Zero::zero(), Error::<T>::ZeroAmount);
+			Self::validate_swap_path(&path)?;
+			let (amounts, _) = Self::get_amounts_out(&amount_in, &path)?;
+			let amount_out =
+				*amounts.last().defensive_ok_or("get_amounts_out() returned an empty result")?;
+			if amount_out < amount_out_min {
+				return Ok(None)
+			}
+
+			Self::do_swap_exact_tokens_for_tokens(
+				sender,
+				path,
+				SwapExactInParams { amount_in, amount_out_min: Some(amount_out_min) },
+				send_to,
+				keep_alive,
+			)
+			.map(Some)
+		}
+
+		/// Take the `path[0]` asset and swap some amount for `params.amount_out` of the `path[1]`.
+		/// If an `amount_in_max` is specified, it will return an error if acquiring `amount_out`
+		/// would be too costly.
 		///
 		/// Withdraws `path[0]` asset from `sender`, deposits the `path[1]` asset to `send_to`,
 		/// respecting `keep_alive`.
@@ -754,11 +2446,11 @@ pub mod pallet {
 		pub fn do_swap_tokens_for_exact_tokens(
 			sender: T::AccountId,
 			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
-			amount_out: T::AssetBalance,
-			amount_in_max: Option<T::AssetBalance>,
+			params: SwapExactOutParams<T::AssetBalance>,
 			send_to: T::AccountId,
 			keep_alive: bool,
 		) -> Result<T::AssetBalance, DispatchError> {
+			let SwapExactOutParams { amount_out, amount_in_max } = params;
 			ensure!(amount_out > Zero::zero(), Error::<T>::ZeroAmount);
 			if let Some(amount_in_max) = amount_in_max {
 				ensure!(amount_in_max > Zero::zero(), Error::<T>::ZeroAmount);
@@ -766,7 +2458,7 @@ pub mod pallet {
 
 			Self::validate_swap_path(&path)?;
 
-			let amounts = Self::get_amounts_in(&amount_out, &path)?;
+			let (amounts, pools) = Self::get_amounts_in(&amount_out, &path)?;
 			let amount_in =
 				*amounts.first().defensive_ok_or("get_amounts_in() returned an empty result")?;
 
@@ -777,7 +2469,16 @@ pub mod pallet {
 				);
 			}
 
-			Self::do_swap(sender, &amounts, path, send_to, keep_alive)?;
+			Self::do_swap(
+				sender,
+				&amounts,
+				&pools,
+				path,
+				send_to,
+				keep_alive,
+				None,
+				amount_in_max,
+			)?;
 			Ok(amount_in)
 		}
 
@@ -790,8 +2491,13 @@ pub mod pallet {
 			keep_alive: bool,
 		) -> Result<T::AssetBalance, DispatchError> {
 			let result = match T::MultiAssetIdConverter::try_convert(asset_id) {
-				MultiAssetIdConversionResult::Converted(asset_id) =>
-					T::Assets::transfer(asset_id, from, to, amount, Expendable),
+				MultiAssetIdConversionResult::Converted(asset_id) => T::Assets::transfer(
+					asset_id,
+					from,
+					to,
+					amount,
+					Self::asset_preservation(keep_alive),
+				),
 				MultiAssetIdConversionResult::Native => {
 					let preservation = match keep_alive {
 						true => Preserve,
@@ -805,8 +2511,11 @@ pub mod pallet {
 						preservation,
 					)?)?)
 				},
-				MultiAssetIdConversionResult::Unsupported(_) =>
-					Err(Error::<T>::UnsupportedAsset.into()),
+				#[cfg(feature = "nft-pools")]
+				MultiAssetIdConversionResult::Unsupported(asset_id) =>
+					T::NftHandler::transfer(&asset_id, from, to, amount).map(|_| amount),
+				#[cfg(not(feature = "nft-pools"))]
+				MultiAssetIdConversionResult::Unsupported(_) => Err(Error::<T>::UnsupportedAsset.into()),
 			};
 
 			if result.is_ok() {
@@ -820,6 +2529,35 @@ pub mod pallet {
 			result
 		}
 
+		/// The preservation of a transfer of a non-native asset, according to the
+		/// `SwapKeepAlivePolicy`.
+		fn asset_preservation(keep_alive: bool) -> Preservation {
+			match T::SwapKeepAlivePolicy::get() {
+				KeepAlivePolicy::AllAssets if keep_alive => Preserve,
+				_ => Expendable,
+			}
+		}
+
+		/// Returns whether `who` holds at least `amount` of `asset`.
+		///
+		/// This only catches plain underfunding. Whether the balance may be reduced that far, e.g.
+		/// without reaping the account or breaching a freeze, is left to [`Pallet::transfer`], so
+		/// that its more specific errors like `TokenError::NotExpendable` still surface.
+		/// Unsupported assets are left to [`Pallet::transfer`] and always return true.
+		fn can_pay(
+			asset: &T::MultiAssetId,
+			who: &T::AccountId,
+			amount: T::AssetBalance,
+		) -> Result<bool, Error<T>> {
+			let balance = match T::MultiAssetIdConverter::try_convert(asset) {
+				MultiAssetIdConversionResult::Converted(asset) => T::Assets::balance(asset, who),
+				MultiAssetIdConversionResult::Native =>
+					Self::convert_native_balance_to_asset_balance(T::Currency::balance(who))?,
+				MultiAssetIdConversionResult::Unsupported(_) => return Ok(true),
+			};
+			Ok(balance >= amount)
+		}
+
 		/// Convert a `Balance` type to an `AssetBalance`.
 		pub(crate) fn convert_native_balance_to_asset_balance(
 			amount: T::Balance,
@@ -829,78 +2567,363 @@ pub mod pallet {
 				.map_err(|_| Error::<T>::Overflow)
 		}
 
-		/// Convert an `AssetBalance` type to a `Balance`.
-		pub(crate) fn convert_asset_balance_to_native_balance(
-			amount: T::AssetBalance,
-		) -> Result<T::Balance, Error<T>> {
-			T::HigherPrecisionBalance::from(amount)
-				.try_into()
-				.map_err(|_| Error::<T>::Overflow)
+		/// Convert an `AssetBalance` type to a `Balance`.
+		pub(crate) fn convert_asset_balance_to_native_balance(
+			amount: T::AssetBalance,
+		) -> Result<T::Balance, Error<T>> {
+			T::HigherPrecisionBalance::from(amount)
+				.try_into()
+				.map_err(|_| Error::<T>::Overflow)
+		}
+
+		/// Convert a `HigherPrecisionBalance` type to an `AssetBalance`.
+		pub(crate) fn convert_hpb_to_asset_balance(
+			amount: T::HigherPrecisionBalance,
+		) -> Result<T::AssetBalance, Error<T>> {
+			amount.try_into().map_err(|_| Error::<T>::Overflow)
+		}
+
+		/// Swap assets along a `path` through the `pools` of its hops, depositing in `send_to`.
+		pub(crate) fn do_swap(
+			sender: T::AccountId,
+			amounts: &Vec<T::AssetBalance>,
+			pools: &Vec<PoolIdOf<T>>,
+			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
+			send_to: T::AccountId,
+			keep_alive: bool,
+			amount_out_min: Option<T::AssetBalance>,
+			amount_in_max: Option<T::AssetBalance>,
+		) -> Result<(), DispatchError> {
+			ensure!(amounts.len() > 1, Error::<T>::CorrespondenceError);
+			ensure!(pools.len() == amounts.len() - 1, Error::<T>::CorrespondenceError);
+			Self::ensure_valid_recipient(&send_to)?;
+			if let Some([asset1, _]) = &path.get(0..2) {
+				let pool_account =
+					Self::get_pool_account(pools.first().ok_or(Error::<T>::CorrespondenceError)?);
+				// amounts should always contain a corresponding element to path.
+				let first_amount = amounts.first().ok_or(Error::<T>::CorrespondenceError)?;
+
+				// check all the input legs before moving any funds.
+				for (i, assets_pair) in path.windows(2).enumerate() {
+					if let [asset1, asset2] = assets_pair {
+						let pool_id = pools.get(i).ok_or(Error::<T>::CorrespondenceError)?;
+						Self::ensure_not_flash_locked(pool_id)?;
+						let pool_account = Self::get_pool_account(pool_id);
+						let amount_in = amounts.get(i).ok_or(Error::<T>::CorrespondenceError)?;
+						let reserve_in = Self::get_balance(&pool_account, asset1)?;
+						Self::validate_reserve_cap(&reserve_in, amount_in)?;
+
+						let amount_out =
+							amounts.get(i + 1).ok_or(Error::<T>::CorrespondenceError)?;
+						let reserve_out = Self::get_balance(&pool_account, asset2)?;
+						Self::check_price_move(
+							pool_id.clone(),
+							amount_in,
+							amount_out,
+							&reserve_in,
+							&reserve_out,
+						)?;
+					}
+				}
+
+				ensure!(
+					Self::can_pay(asset1, &sender, *first_amount)?,
+					Error::<T>::InsufficientBalance
+				);
+				Self::transfer(asset1, &sender, &pool_account, *first_amount, keep_alive)?;
+
+				let mut i = 0;
+				let path_len = path.len() as u32;
+				for assets_pair in path.windows(2) {
+					if let [asset1, asset2] = assets_pair {
+						let pool_id =
+							pools.get(i as usize).ok_or(Error::<T>::CorrespondenceError)?;
+						let pool_account = Self::get_pool_account(pool_id);
+
+						let amount_out =
+							amounts.get((i + 1) as usize).ok_or(Error::<T>::CorrespondenceError)?;
+
+						let to = if i < path_len - 2 {
+							let next_pool_id =
+								pools.get((i + 1) as usize).ok_or(Error::<T>::PathError)?;
+							Self::get_pool_account(next_pool_id)
+						} else {
+							send_to.clone()
+						};
+
+						let reserve = Self::get_balance(&pool_account, asset2)?;
+						let reserve_left = reserve.saturating_sub(*amount_out);
+						Self::validate_minimal_amount(reserve_left, asset2)
+							.map_err(|_| Error::<T>::ReserveLeftLessThanMinimal)?;
+
+						#[cfg(debug_assertions)]
+						{
+							// the input of this hop has already been transferred to the pool.
+							let amount_in =
+								amounts.get(i as usize).ok_or(Error::<T>::CorrespondenceError)?;
+							let reserve_in = Self::get_balance(&pool_account, asset1)?;
+							debug_assert!(
+								Self::is_constant_product_preserved(
+									&reserve_in.saturating_sub(*amount_in),
+									&reserve,
+									&reserve_in,
+									&reserve_left,
+								),
+								"a swap must never decrease the product of the pool's reserves"
+							);
+						}
+
+						Self::transfer(asset2, &pool_account, &to, *amount_out, true)?;
+						Self::note_swap(pool_id);
+						Self::note_reserve_change(pool_id)?;
+					}
+					i.saturating_inc();
+				}
+				#[cfg(debug_assertions)]
+				for pool_id in pools {
+					Self::ensure_pool_invariant(pool_id);
+				}
+
+				let amount_in = *first_amount;
+				let amount_out = *amounts.last().expect("Always has more than 1 element");
+				let asset_out = path.last().expect("Always has more than 1 element");
+				let (pool_asset1_delta, pool_asset2_delta) =
+					if Self::sort_assets(asset1.clone(), asset_out.clone()).0 == *asset1 {
+						(BalanceDelta::Increase(amount_in), BalanceDelta::Decrease(amount_out))
+					} else {
+						(BalanceDelta::Decrease(amount_out), BalanceDelta::Increase(amount_in))
+					};
+				Self::deposit_event(match T::EventVerbosity::get() {
+					EventLevel::Full => Event::SwapExecuted {
+						who: sender,
+						send_to,
+						path,
+						amount_in,
+						amount_out,
+						pool_asset1_delta,
+						pool_asset2_delta,
+						amount_out_min,
+						amount_in_max,
+					},
+					EventLevel::Minimal => Event::SwapExecutedCompact {
+						who: sender,
+						send_to,
+						path,
+						amount_in,
+						amount_out,
+					},
+				});
+			} else {
+				return Err(Error::<T>::InvalidPath.into())
+			}
+			Ok(())
+		}
+
+		/// Adds `lp_amount` lp tokens minted for `amount1` and `amount2` to the position of `who`.
+		fn increase_lp_position(
+			who: &T::AccountId,
+			pool_id: &PoolIdOf<T>,
+			lp_amount: T::AssetBalance,
+			amount1: T::AssetBalance,
+			amount2: T::AssetBalance,
+		) {
+			LpPositions::<T>::mutate(who, pool_id, |position| {
+				let position = position.get_or_insert_with(Default::default);
+				position.lp_amount = position.lp_amount.saturating_add(lp_amount);
+				position.amount1 = position.amount1.saturating_add(amount1);
+				position.amount2 = position.amount2.saturating_add(amount2);
+			});
+		}
+
+		/// Takes `lp_amount` burned lp tokens and their share of the deposit off the position of
+		/// `who`.
+		fn decrease_lp_position(
+			who: &T::AccountId,
+			pool_id: &PoolIdOf<T>,
+			lp_amount: T::AssetBalance,
+		) {
+			LpPositions::<T>::mutate_exists(who, pool_id, |maybe_position| {
+				let Some(position) = maybe_position else { return };
+				if lp_amount >= position.lp_amount {
+					*maybe_position = None;
+					return
+				}
+				let lp_left = position.lp_amount.saturating_sub(lp_amount);
+				position.amount1 = Self::mul_div(&position.amount1, &lp_left, &position.lp_amount)
+					.unwrap_or_default();
+				position.amount2 = Self::mul_div(&position.amount2, &lp_left, &position.lp_amount)
+					.unwrap_or_default();
+				position.lp_amount = lp_left;
+			});
+		}
+
+		/// The bytes the owner of `permit` has to sign: the [`PERMIT_DOMAIN`] tag, the genesis
+		/// hash of the chain and the permit, SCALE encoded.
+		pub fn permit_payload(permit: &RemoveLiquidityPermitOf<T>) -> Vec<u8> {
+			let genesis_hash = frame_system::Pallet::<T>::block_hash(BlockNumberFor::<T>::zero());
+			(PERMIT_DOMAIN, genesis_hash, permit).encode()
+		}
+
+		/// Ensure `signature` was made by `signer` over `data`.
+		fn validate_signature(
+			data: &Vec<u8>,
+			signature: &T::OffchainSignature,
+			signer: &T::AccountId,
+		) -> DispatchResult {
+			if signature.verify(&**data, signer) {
+				return Ok(())
+			}
+
+			// NOTE: for security reasons modern UIs implicitly wrap the data requested to sign into
+			// <Bytes></Bytes>, that's why we support both wrapped and raw versions.
+			let prefix = b"<Bytes>";
+			let suffix = b"</Bytes>";
+			let mut wrapped: Vec<u8> = Vec::with_capacity(data.len() + prefix.len() + suffix.len());
+			wrapped.extend(prefix);
+			wrapped.extend(data);
+			wrapped.extend(suffix);
+
+			ensure!(signature.verify(&*wrapped, signer), Error::<T>::WrongSignature);
+
+			Ok(())
+		}
+
+		/// Rejects a swap of `amount_in` for `amount_out` against `reserve_in` and `reserve_out` of
+		/// the pool with `pool_id` if it moves the price by more than `MaxPriceMovePerSwap`, and
+		/// emits [`Event::LargePriceImpact`] if it moves it by more than `ImpactAlertThreshold`.
+		fn check_price_move(
+			pool_id: PoolIdOf<T>,
+			amount_in: &T::AssetBalance,
+			amount_out: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			reserve_out: &T::AssetBalance,
+		) -> DispatchResult {
+			let max_move = T::MaxPriceMovePerSwap::get();
+			let alert_threshold = T::ImpactAlertThreshold::get();
+			if max_move.is_none() && alert_threshold == Permill::one() {
+				return Ok(())
+			}
+			let price_move = Self::price_move(amount_in, amount_out, reserve_in, reserve_out)?;
+			if let Some(max_move) = max_move {
+				ensure!(price_move <= max_move, Error::<T>::PriceMoveTooLarge);
+			}
+			if price_move > alert_threshold {
+				Self::deposit_event(Event::LargePriceImpact { pool_id, impact: price_move });
+			}
+			Ok(())
+		}
+
+		/// Charges `sender` the swap network fee of a swap made through the calls of this pallet.
+		///
+		/// The swap calls charge it after the swap, so when the input is in the native asset the
+		/// sender has to afford both.
+		fn charge_swap_network_fee(sender: &T::AccountId, keep_alive: bool) -> DispatchResult {
+			let Some((receiver, fee)) = Self::swap_network_fee()? else { return Ok(()) };
+			let native = T::MultiAssetIdConverter::get_native();
+			ensure!(Self::can_pay(&native, sender, fee)?, Error::<T>::InsufficientBalance);
+			Self::transfer(&native, sender, &receiver, fee, keep_alive)?;
+			Ok(())
 		}
 
-		/// Convert a `HigherPrecisionBalance` type to an `AssetBalance`.
-		pub(crate) fn convert_hpb_to_asset_balance(
-			amount: T::HigherPrecisionBalance,
-		) -> Result<T::AssetBalance, Error<T>> {
-			amount.try_into().map_err(|_| Error::<T>::Overflow)
+		/// The receiver and amount of the swap network fee, `None` if it isn't charged.
+		fn swap_network_fee() -> Result<Option<(T::AccountId, T::AssetBalance)>, Error<T>> {
+			let Some(receiver) = T::NetworkFeeReceiver::get() else { return Ok(None) };
+			let fee = Self::convert_native_balance_to_asset_balance(T::SwapNetworkFee::get())?;
+			Ok((!fee.is_zero()).then_some((receiver, fee)))
 		}
 
-		/// Swap assets along a `path`, depositing in `send_to`.
-		pub(crate) fn do_swap(
-			sender: T::AccountId,
-			amounts: &Vec<T::AssetBalance>,
-			path: BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
-			send_to: T::AccountId,
-			keep_alive: bool,
-		) -> Result<(), DispatchError> {
-			ensure!(amounts.len() > 1, Error::<T>::CorrespondenceError);
-			if let Some([asset1, asset2]) = &path.get(0..2) {
-				let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone());
-				let pool_account = Self::get_pool_account(&pool_id);
-				// amounts should always contain a corresponding element to path.
-				let first_amount = amounts.first().ok_or(Error::<T>::CorrespondenceError)?;
+		/// Notify `T::OnReserveChange` of the current reserves of the pool with `pool_id`.
+		fn note_reserve_change(pool_id: &PoolIdOf<T>) -> DispatchResult {
+			let pool_account = Self::get_pool_account(pool_id);
+			let (asset1, asset2, _) = pool_id;
+			let reserve1 = Self::get_balance(&pool_account, asset1)?;
+			let reserve2 = Self::get_balance(&pool_account, asset2)?;
+			T::OnReserveChange::on_reserve_change(pool_id, reserve1, reserve2);
+			Ok(())
+		}
 
-				Self::transfer(asset1, &sender, &pool_account, *first_amount, keep_alive)?;
+		/// Ensure the pool with `pool_id` isn't locked by an ongoing flash loan.
+		fn ensure_not_flash_locked(pool_id: &PoolIdOf<T>) -> Result<(), Error<T>> {
+			ensure!(
+				FlashLoanLock::<T>::get().as_ref() != Some(pool_id),
+				Error::<T>::FlashLoanInProgress
+			);
+			Ok(())
+		}
 
-				let mut i = 0;
-				let path_len = path.len() as u32;
-				for assets_pair in path.windows(2) {
-					if let [asset1, asset2] = assets_pair {
-						let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone());
-						let pool_account = Self::get_pool_account(&pool_id);
+		/// Burn the lp tokens locked in the account of the pool `pool_id`, send its reserves to
+		/// the `ProtocolTreasury` and remove the pool. Returns the amounts of both assets sent.
+		fn close_pool(
+			pool_id: &PoolIdOf<T>,
+			lp_token: &T::PoolAssetId,
+		) -> Result<(T::AssetBalance, T::AssetBalance), DispatchError> {
+			let pool_account = Self::get_pool_account(pool_id);
+			let locked = T::PoolAssets::balance(lp_token.clone(), &pool_account);
+			let (asset1, asset2, _) = pool_id;
+			let amount1 = Self::get_balance(&pool_account, asset1)?;
+			let amount2 = Self::get_balance(&pool_account, asset2)?;
+
+			if !locked.is_zero() {
+				T::PoolAssets::burn_from(lp_token.clone(), &pool_account, locked, Exact, Polite)?;
+			}
+			let treasury = T::ProtocolTreasury::get();
+			if !amount1.is_zero() {
+				Self::transfer(asset1, &pool_account, &treasury, amount1, false)?;
+			}
+			if !amount2.is_zero() {
+				Self::transfer(asset2, &pool_account, &treasury, amount2, false)?;
+			}
+			Self::note_reserve_change(pool_id)?;
+			Self::remove_pool(pool_id, lp_token);
 
-						let amount_out =
-							amounts.get((i + 1) as usize).ok_or(Error::<T>::CorrespondenceError)?;
+			Ok((amount1, amount2))
+		}
 
-						let to = if i < path_len - 2 {
-							let asset3 = path.get((i + 2) as usize).ok_or(Error::<T>::PathError)?;
-							Self::get_pool_account(&Self::get_pool_id(
-								asset2.clone(),
-								asset3.clone(),
-							))
-						} else {
-							send_to.clone()
-						};
+		/// Sends `amount` of `asset` from the account of the closed pool with `pool_id` to `to`,
+		/// returning the amount sent. If the transfer fails, [`Event::ClosedPoolPayoutFailed`] is
+		/// emitted and the amount stays in the pool.
+		fn pay_out_closed_pool(
+			pool_id: &PoolIdOf<T>,
+			asset: &T::MultiAssetId,
+			to: &T::AccountId,
+			amount: T::AssetBalance,
+		) -> Result<T::AssetBalance, DispatchError> {
+			if amount.is_zero() {
+				return Ok(amount)
+			}
+			let pool_account = Self::get_pool_account(pool_id);
+			frame_support::storage::with_storage_layer(|| {
+				Self::transfer(asset, &pool_account, to, amount, false)
+			})
+			.map_err(|error| {
+				Self::deposit_event(Event::ClosedPoolPayoutFailed {
+					pool_id: pool_id.clone(),
+					withdraw_to: to.clone(),
+					asset: asset.clone(),
+					amount,
+					error,
+				});
+				error
+			})
+		}
 
-						let reserve = Self::get_balance(&pool_account, asset2)?;
-						let reserve_left = reserve.saturating_sub(*amount_out);
-						Self::validate_minimal_amount(reserve_left, asset2)
-							.map_err(|_| Error::<T>::ReserveLeftLessThanMinimal)?;
+		/// Remove the pool `pool_id` with the lp token `lp_token` from storage.
+		fn remove_pool(pool_id: &PoolIdOf<T>, lp_token: &T::PoolAssetId) {
+			Pools::<T>::remove(pool_id);
+			Self::clear_quote_cache();
+			LpTokenToPool::<T>::remove(lp_token);
+			PoolStats::<T>::remove(pool_id);
+			CompoundStates::<T>::remove(pool_id);
+		}
 
-						Self::transfer(asset2, &pool_account, &to, *amount_out, true)?;
-					}
-					i.saturating_inc();
-				}
-				Self::deposit_event(Event::SwapExecuted {
-					who: sender,
-					send_to,
-					path,
-					amount_in: *first_amount,
-					amount_out: *amounts.last().expect("Always has more than 1 element"),
-				});
-			} else {
-				return Err(Error::<T>::InvalidPath.into())
-			}
+		/// The account derived from the `PalletId`.
+		pub fn account_id() -> T::AccountId {
+			T::PalletId::get().into_account_truncating()
+		}
+
+		/// Ensure `who` isn't the pallet account, which no user flow should send funds to.
+		fn ensure_valid_recipient(who: &T::AccountId) -> DispatchResult {
+			ensure!(*who != Self::account_id(), Error::<T>::InvalidRecipient);
 			Ok(())
 		}
 
@@ -909,10 +2932,7 @@ pub mod pallet {
 		/// This actually does computation. If you need to keep using it, then make sure you cache
 		/// the value and only call this once.
 		pub fn get_pool_account(pool_id: &PoolIdOf<T>) -> T::AccountId {
-			let encoded_pool_id = sp_io::hashing::blake2_256(&Encode::encode(pool_id)[..]);
-
-			Decode::decode(&mut TrailingZeroInput::new(encoded_pool_id.as_ref()))
-				.expect("infinite length input; no invalid inputs for type; qed")
+			T::AccountDerivation::pool_account(pool_id)
 		}
 
 		/// Get the `owner`'s balance of `asset`, which could be the chain's native asset or another
@@ -934,13 +2954,44 @@ pub mod pallet {
 			}
 		}
 
-		/// Returns a pool id constructed from 2 assets.
-		/// 1. Native asset should be lower than the other asset ids.
-		/// 2. Two native or two non-native assets are compared by their `Ord` implementation.
+		/// Get the `owner`'s balance of `asset` like [`Self::get_balance`], but only inspecting it,
+		/// so that reserves can still be read and quoted while the transfers of an asset are
+		/// disabled, e.g. because it is frozen. Not for the paths that move funds.
+		fn get_reserve(
+			owner: &T::AccountId,
+			asset: &T::MultiAssetId,
+		) -> Result<T::AssetBalance, Error<T>> {
+			match T::MultiAssetIdConverter::try_convert(asset) {
+				MultiAssetIdConversionResult::Converted(asset_id) =>
+					Ok(<<T as Config>::Assets>::balance(asset_id, owner)),
+				MultiAssetIdConversionResult::Native =>
+					Self::convert_native_balance_to_asset_balance(T::Currency::balance(owner)),
+				MultiAssetIdConversionResult::Unsupported(_) =>
+					Err(Error::<T>::UnsupportedAsset.into()),
+			}
+		}
+
+		/// Returns a pool id constructed from 2 assets and a fee tier, the assets ordered by
+		/// [`Pallet::sort_assets`].
 		///
 		/// We expect deterministic order, so (asset1, asset2) or (asset2, asset1) returns the same
 		/// result.
-		pub fn get_pool_id(asset1: T::MultiAssetId, asset2: T::MultiAssetId) -> PoolIdOf<T> {
+		pub fn get_pool_id(
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+		) -> PoolIdOf<T> {
+			let (asset1, asset2) = Self::sort_assets(asset1, asset2);
+			(asset1, asset2, fee_tier)
+		}
+
+		/// Returns the 2 assets in the order of the pool ids.
+		/// 1. Native asset should be lower than the other asset ids.
+		/// 2. Two native or two non-native assets are compared by their `Ord` implementation.
+		pub fn sort_assets(
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+		) -> (T::MultiAssetId, T::MultiAssetId) {
 			match (
 				T::MultiAssetIdConverter::is_native(&asset1),
 				T::MultiAssetIdConverter::is_native(&asset2),
@@ -958,118 +3009,940 @@ pub mod pallet {
 			}
 		}
 
-		/// Returns the balance of each asset in the pool.
-		/// The tuple result is in the order requested (not necessarily the same as pool order).
+		/// Returns the pool id of `asset1` and `asset2` along with whether `asset1` is its first
+		/// asset, i.e. whether the caller's order matches the pool's.
+		pub fn get_pool_id_with_flag(
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+		) -> (PoolIdOf<T>, bool) {
+			let pool_id = Self::get_pool_id(asset1.clone(), asset2, fee_tier);
+			let is_pool_order = pool_id.0 == asset1;
+			(pool_id, is_pool_order)
+		}
+
+		/// Returns the id of the pool whose lp token is `lp_token`, if any.
+		pub fn pool_of_lp_token(lp_token: T::PoolAssetId) -> Option<PoolIdOf<T>> {
+			LpTokenToPool::<T>::get(lp_token)
+		}
+
+		/// Returns the block the pool of `asset1` and `asset2` in the `fee_tier` was created in,
+		/// `None` if there is no such pool.
+		pub fn pool_created_at(
+			asset1: &T::MultiAssetId,
+			asset2: &T::MultiAssetId,
+			fee_tier: Permill,
+		) -> Option<BlockNumberFor<T>> {
+			Pools::<T>::get(Self::get_pool_id(asset1.clone(), asset2.clone(), fee_tier))
+				.map(|pool| pool.created_at)
+		}
+
+		/// Returns the lifetime statistics of the pool of `asset1` and `asset2` in the
+		/// `fee_tier`.
+		pub fn pool_stats(
+			asset1: &T::MultiAssetId,
+			asset2: &T::MultiAssetId,
+			fee_tier: Permill,
+		) -> PoolStatistics<T::AssetBalance> {
+			PoolStats::<T>::get(Self::get_pool_id(asset1.clone(), asset2.clone(), fee_tier))
+		}
+
+		/// Returns whether the pool of `asset1` and `asset2` in the `fee_tier` has seen no swap
+		/// for more than `max_idle` blocks, or `None` if there is no such pool.
+		pub fn is_pool_stale(
+			asset1: &T::MultiAssetId,
+			asset2: &T::MultiAssetId,
+			fee_tier: Permill,
+			max_idle: BlockNumberFor<T>,
+		) -> Option<bool> {
+			let pool =
+				Pools::<T>::get(Self::get_pool_id(asset1.clone(), asset2.clone(), fee_tier))?;
+			let idle =
+				frame_system::Pallet::<T>::block_number().saturating_sub(pool.last_swap_block);
+			Some(idle > max_idle)
+		}
+
+		/// The number of pools a swap along `path` goes through.
+		fn hops(path: &[T::MultiAssetId]) -> u32 {
+			(path.len() as u32).saturating_sub(1)
+		}
+
+		/// The weight a swap through `hops` pools adds to the benchmarked `swap_*` weights, which
+		/// predate it: [`Self::note_swap`] and the `OnReserveChange` hook for each hop, the
+		/// `LastSwapBlock` of the swapper, and the `PoolInfo` and reserves of the pool in each of
+		/// the `FeeTiers` that every hop picks from.
+		fn swap_weight_overhead(hops: u32) -> Weight {
+			let accesses = u64::from(hops).saturating_add(1);
+			let tier_reads = u64::from(hops)
+				.saturating_mul(T::FeeTiers::get().len() as u64)
+				.saturating_mul(3);
+			T::DbWeight::get()
+				.reads_writes(accesses.saturating_add(tier_reads), accesses)
+				.saturating_add(T::OnReserveChange::weight().saturating_mul(hops.into()))
+		}
+
+		/// Records a swap through the pool in the current block.
+		fn note_swap(pool_id: &PoolIdOf<T>) {
+			Pools::<T>::mutate(pool_id, |maybe_pool| {
+				if let Some(pool) = maybe_pool {
+					pool.last_swap_block = frame_system::Pallet::<T>::block_number();
+				}
+			});
+		}
+
+		/// Returns the balance of each asset in the pool of `asset1` and `asset2` in the
+		/// `fee_tier`. The tuple result is in the order requested (not necessarily the same as
+		/// pool order).
+		///
+		/// Only inspects the balances, see [`Self::get_reserve`]. Swaps price against
+		/// [`Self::swap_reserves`] instead.
 		pub fn get_reserves(
 			asset1: &T::MultiAssetId,
 			asset2: &T::MultiAssetId,
+			fee_tier: Permill,
+		) -> Result<(T::AssetBalance, T::AssetBalance), Error<T>> {
+			Self::read_reserves(asset1, asset2, fee_tier, Self::get_reserve)
+		}
+
+		/// Returns the balance of each asset in the pool that a swap can take out of it, in the
+		/// order requested.
+		fn swap_reserves(
+			asset1: &T::MultiAssetId,
+			asset2: &T::MultiAssetId,
+			fee_tier: Permill,
 		) -> Result<(T::AssetBalance, T::AssetBalance), Error<T>> {
-			let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone());
+			Self::read_reserves(asset1, asset2, fee_tier, Self::get_balance)
+		}
+
+		/// Reads the balance of each asset in the pool with `balance_of`, in the order requested.
+		fn read_reserves(
+			asset1: &T::MultiAssetId,
+			asset2: &T::MultiAssetId,
+			fee_tier: Permill,
+			balance_of: fn(&T::AccountId, &T::MultiAssetId) -> Result<T::AssetBalance, Error<T>>,
+		) -> Result<(T::AssetBalance, T::AssetBalance), Error<T>> {
+			let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone(), fee_tier);
 			let pool_account = Self::get_pool_account(&pool_id);
 
-			let balance1 = Self::get_balance(&pool_account, asset1)?;
-			let balance2 = Self::get_balance(&pool_account, asset2)?;
+			let balance1 = balance_of(&pool_account, asset1)?;
+			let balance2 = balance_of(&pool_account, asset2)?;
 
 			if balance1.is_zero() || balance2.is_zero() {
-				Err(Error::<T>::PoolNotFound)?;
+				ensure!(Pools::<T>::contains_key(&pool_id), Error::<T>::PoolNotFound);
+				Err(Error::<T>::EmptyPool)?;
 			}
 
 			Ok((balance1, balance2))
 		}
 
-		/// Leading to an amount at the end of a `path`, get the required amounts in.
+		/// Returns the balance of each asset in the pool with `pool_id`, in pool order, or `None`
+		/// if there is no such pool.
+		///
+		/// Only reads storage, so it can be called through [`AssetConversionApi::reserves_at`] at
+		/// any block whose state is still available.
+		pub fn pool_reserves(pool_id: &PoolIdOf<T>) -> Option<(T::AssetBalance, T::AssetBalance)> {
+			if !Pools::<T>::contains_key(pool_id) {
+				return None
+			}
+			let pool_account = Self::get_pool_account(pool_id);
+			let (asset1, asset2, _) = pool_id;
+			Some((
+				Self::get_reserve(&pool_account, asset1).ok()?,
+				Self::get_reserve(&pool_account, asset2).ok()?,
+			))
+		}
+
+		/// Returns the non-native asset of the pool with `pool_id`, or `None` if the pool does not
+		/// hold the native asset.
+		///
+		/// The native asset always comes first in a pool id, so this is the second asset.
+		pub fn asset_side(pool_id: &PoolIdOf<T>) -> Option<T::AssetId> {
+			let (asset1, asset2, _) = pool_id;
+			if !T::MultiAssetIdConverter::is_native(asset1) {
+				return None
+			}
+			match T::MultiAssetIdConverter::try_convert(asset2) {
+				MultiAssetIdConversionResult::Converted(asset) => Some(asset),
+				_ => None,
+			}
+		}
+
+		/// Returns the reserve of the native asset of the pool with `pool_id`, or `None` if the
+		/// pool does not hold the native asset.
+		pub fn native_reserve(pool_id: &PoolIdOf<T>) -> Option<T::AssetBalance> {
+			Self::asset_side(pool_id)?;
+			Self::get_reserve(&Self::get_pool_account(pool_id), &pool_id.0).ok()
+		}
+
+		/// Returns the reserve of the non-native asset of the pool with `pool_id`, or `None` if the
+		/// pool does not hold the native asset.
+		pub fn asset_reserve(pool_id: &PoolIdOf<T>) -> Option<T::AssetBalance> {
+			Self::asset_side(pool_id)?;
+			Self::get_reserve(&Self::get_pool_account(pool_id), &pool_id.1).ok()
+		}
+
+		/// Returns up to `limit` pools together with a summary of their state.
+		///
+		/// Iteration starts right after the raw storage key `start_key`, or at the beginning of
+		/// the map if `None`. To fetch the next page, pass the full storage key of the last pool
+		/// returned (see `Pools::<T>::hashed_key_for`). Since the cursor is a storage key, paging
+		/// stays stable across blocks even if pools are created in between.
+		pub fn list_pools(
+			start_key: Option<Vec<u8>>,
+			limit: u32,
+		) -> Vec<(PoolIdOf<T>, PoolInfoSummary<T::AccountId, T::PoolAssetId, T::AssetBalance>)> {
+			let iter = match start_key {
+				Some(key) => Pools::<T>::iter_from(key),
+				None => Pools::<T>::iter(),
+			};
+
+			iter.take(limit as usize)
+				.map(|(pool_id, info)| {
+					let owner = Self::get_pool_account(&pool_id);
+					let reserve1 = Self::get_reserve(&owner, &pool_id.0).unwrap_or_default();
+					let reserve2 = Self::get_reserve(&owner, &pool_id.1).unwrap_or_default();
+					let summary =
+						PoolInfoSummary { owner, lp_token: info.lp_token, reserve1, reserve2 };
+					(pool_id, summary)
+				})
+				.collect()
+		}
+
+		/// Returns what the lp tokens `who` was minted in the pool with `pool_id` are worth if
+		/// withdrawn now, against what the assets deposited for them would be worth if held, both
+		/// in the native asset at the current price of the pool.
+		///
+		/// Pools without the native asset are valued through the native pool of their first
+		/// asset. Returns `None` if there is no such pool or `who` has no position.
+		pub fn lp_pnl(who: &T::AccountId, pool_id: &PoolIdOf<T>) -> Option<LpPnl<T::AssetBalance>> {
+			let position = LpPositions::<T>::get(who, pool_id)?;
+			let pool = Pools::<T>::get(pool_id)?;
+			let (asset1, asset2, _) = pool_id;
+			let pool_account = Self::get_pool_account(pool_id);
+			let reserve1 = Self::get_reserve(&pool_account, asset1).ok()?;
+			let reserve2 = Self::get_reserve(&pool_account, asset2).ok()?;
+			let total_supply = T::PoolAssets::total_issuance(pool.lp_token);
+			let withdrawn1 = Self::mul_div(&position.lp_amount, &reserve1, &total_supply).ok()?;
+			let withdrawn2 = Self::mul_div(&position.lp_amount, &reserve2, &total_supply).ok()?;
+
+			let native = T::MultiAssetIdConverter::get_native();
+			let value = |amount1: T::AssetBalance, amount2: T::AssetBalance| {
+				// the second asset at the current price of the pool, in the first one.
+				let in_asset1 =
+					amount1.checked_add(&Self::mul_div(&amount2, &reserve1, &reserve2).ok()?)?;
+				if *asset1 == native {
+					Some(in_asset1)
+				} else {
+					Self::quote_price_exact_tokens_for_tokens(
+						asset1.clone(),
+						native.clone(),
+						in_asset1,
+						false,
+					)
+				}
+			};
+			Some(LpPnl {
+				value_if_held: value(position.amount1, position.amount2)?,
+				value_if_withdrawn: value(withdrawn1, withdrawn2)?,
+			})
+		}
+
+		/// Checks the solvency and sums up the value locked of up to `limit` pools, starting after
+		/// the raw storage key `start_key`. Page through all the pools as with
+		/// [`Pallet::list_pools`].
+		pub fn dex_health(
+			start_key: Option<Vec<u8>>,
+			limit: u32,
+		) -> DexHealth<PoolIdOf<T>, T::AssetBalance> {
+			let iter = match start_key {
+				Some(key) => Pools::<T>::iter_from(key),
+				None => Pools::<T>::iter(),
+			};
+
+			let native = T::MultiAssetIdConverter::get_native();
+			let mut health: DexHealth<_, T::AssetBalance> =
+				DexHealth { pools: 0, total_value_locked: Zero::zero(), solvency: Vec::new() };
+			for (pool_id, info) in iter.take(limit as usize) {
+				// both sides of a pool are worth the same, so value it at twice one side.
+				let native_value = Self::native_reserve(&pool_id).unwrap_or_else(|| {
+					let asset1 = &pool_id.0;
+					let pool_account = Self::get_pool_account(&pool_id);
+					let reserve1 = Self::get_reserve(&pool_account, asset1).unwrap_or_default();
+					Self::quote_price_exact_tokens_for_tokens(
+						asset1.clone(),
+						native.clone(),
+						reserve1,
+						false,
+					)
+					.unwrap_or_default()
+				});
+				health.total_value_locked = health
+					.total_value_locked
+					.saturating_add(native_value.saturating_mul(2u32.into()));
+
+				let solvent = Self::is_pool_solvent(&pool_id, &info);
+				health.pools.saturating_inc();
+				health.solvency.push((pool_id, solvent));
+			}
+			health
+		}
+
+		/// Realizes the fees accrued by the liquidity of the `AutoCompoundTreasury` in each pool
+		/// with the native asset in turn, for as long as `remaining_weight` allows: withdraws the
+		/// lp tokens worth the growth of the square root of the product of the reserves per lp
+		/// token since the last compounding of the pool, and swaps the other asset into native.
+		///
+		/// The first visit of a pool only records where its compounding starts from, and a pool
+		/// is compounded at most once per `AutoCompoundPeriod`. Continues after the last pool
+		/// visited by the previous call, so that every pool gets its turn. Returns the weight
+		/// used.
+		pub fn auto_compound(remaining_weight: Weight) -> Weight {
+			let Some(treasury) = T::AutoCompoundTreasury::get() else { return Weight::zero() };
+			let per_visit = T::DbWeight::get().reads_writes(6, 1);
+			let per_compound = T::WeightInfo::remove_liquidity()
+				.saturating_add(T::WeightInfo::swap_exact_tokens_for_tokens())
+				.saturating_add(Self::swap_weight_overhead(1))
+				.saturating_add(T::OnReserveChange::weight());
+			let mut used = T::DbWeight::get().reads_writes(1, 1);
+			if remaining_weight.any_lt(used) {
+				return Weight::zero()
+			}
+
+			let iter = match AutoCompoundCursor::<T>::get() {
+				Some(last) => Pools::<T>::iter_from(Pools::<T>::hashed_key_for(&last)),
+				None => Pools::<T>::iter(),
+			};
+			let now = frame_system::Pallet::<T>::block_number();
+			let period = T::AutoCompoundPeriod::get().max(One::one());
+			let mut last_visited = None;
+			let mut exhausted = true;
+			for (pool_id, info) in iter {
+				if remaining_weight
+					.any_lt(used.saturating_add(per_visit).saturating_add(per_compound))
+				{
+					exhausted = false;
+					break
+				}
+				used.saturating_accrue(per_visit);
+				last_visited = Some(pool_id.clone());
+
+				// the price of a pool swapped through in this block may have been moved to sandwich
+				// the compounding.
+				if Self::asset_side(&pool_id).is_none() || info.last_swap_block == now {
+					continue
+				}
+				let state = CompoundStates::<T>::get(&pool_id);
+				if state.as_ref().map_or(false, |state| now < state.next_at) {
+					continue
+				}
+				let Some(root_k_per_lp_token) = Self::root_k_per_lp_token(&pool_id, &info) else {
+					continue
+				};
+				let lp_balance = T::PoolAssets::balance(info.lp_token.clone(), &treasury);
+				let lp_token_burn = match &state {
+					// the fees accrued before the first visit are not told apart from the
+					// liquidity.
+					None => Zero::zero(),
+					Some(state) =>
+						Self::fee_share(lp_balance, state.root_k_per_lp_token, root_k_per_lp_token),
+				};
+				let mut state = match state {
+					// without liquidity, there are no fees to keep accruing towards.
+					Some(state) if !lp_balance.is_zero() => state,
+					_ => CompoundState { root_k_per_lp_token, next_at: now, failures: 0 },
+				};
+				state.next_at = now.saturating_add(period);
+
+				if !lp_token_burn.is_zero() {
+					used.saturating_accrue(per_compound);
+					match frame_support::storage::with_storage_layer(|| {
+						Self::compound(&treasury, pool_id.clone(), lp_token_burn)
+					}) {
+						Ok(()) => {
+							state.root_k_per_lp_token = Self::root_k_per_lp_token(&pool_id, &info)
+								.unwrap_or(root_k_per_lp_token);
+							state.failures = 0;
+						},
+						Err(error) => {
+							state.failures.saturating_inc();
+							let backoff = 2u32.saturating_pow(state.failures.min(16));
+							state.next_at =
+								now.saturating_add(period.saturating_mul(backoff.into()));
+							if state.failures == 1 {
+								Self::deposit_event(Event::CompoundFailed {
+									pool_id: pool_id.clone(),
+									error,
+								});
+							}
+						},
+					}
+				}
+				CompoundStates::<T>::insert(&pool_id, state);
+			}
+
+			if exhausted {
+				AutoCompoundCursor::<T>::kill();
+			} else if let Some(last) = last_visited {
+				AutoCompoundCursor::<T>::put(last);
+			}
+			used
+		}
+
+		/// The square root of the product of the reserves of the pool with `pool_id` per lp token
+		/// of it. Swap fees are the only thing that grows it, so it tracks the fees accrued.
+		fn root_k_per_lp_token(
+			pool_id: &PoolIdOf<T>,
+			info: &PoolInfo<T::PoolAssetId, BlockNumberFor<T>>,
+		) -> Option<FixedU128> {
+			let (reserve1, reserve2) =
+				Self::get_reserves(&pool_id.0, &pool_id.1, pool_id.2).ok()?;
+			let root_k: T::AssetBalance = T::HigherPrecisionBalance::from(reserve1)
+				.checked_mul(&T::HigherPrecisionBalance::from(reserve2))?
+				.integer_sqrt()
+				.try_into()
+				.ok()?;
+			let total_supply = T::PoolAssets::total_issuance(info.lp_token.clone());
+			FixedU128::checked_from_rational(
+				root_k.saturated_into::<u128>(),
+				total_supply.saturated_into::<u128>(),
+			)
+		}
+
+		/// The lp tokens of `lp_balance` worth the fees accrued while the square root of the
+		/// product of the reserves per lp token grew from `from` to `to`. Burning them leaves the
+		/// rest worth what `lp_balance` was worth at `from`.
+		fn fee_share(
+			lp_balance: T::AssetBalance,
+			from: FixedU128,
+			to: FixedU128,
+		) -> T::AssetBalance {
+			if to <= from {
+				return Zero::zero()
+			}
+			multiply_by_rational_with_rounding(
+				lp_balance.saturated_into::<u128>(),
+				to.saturating_sub(from).into_inner(),
+				to.into_inner(),
+				Rounding::Down,
+			)
+			.map_or_else(Zero::zero, |burn| burn.saturated_into())
+		}
+
+		/// Withdraws `lp_token_burn` of the liquidity of `treasury` from the pool with `pool_id`,
+		/// which holds the native asset, and swaps the withdrawn non-native asset into native.
+		///
+		/// The withdrawal is proportional, so it leaves the price of the pool as it is. The swap
+		/// fails if it gets less than `AutoCompoundMaxSlippage` below that price.
+		fn compound(
+			treasury: &T::AccountId,
+			pool_id: PoolIdOf<T>,
+			lp_token_burn: T::AssetBalance,
+		) -> DispatchResult {
+			let (native, asset, fee_tier) = pool_id.clone();
+			let native_before = Self::get_balance(treasury, &native)?;
+			let asset_before = Self::get_balance(treasury, &asset)?;
+
+			Self::remove_liquidity(
+				frame_system::RawOrigin::Signed(treasury.clone()).into(),
+				native.clone(),
+				asset.clone(),
+				fee_tier,
+				lp_token_burn,
+				Zero::zero(),
+				Zero::zero(),
+				treasury.clone(),
+			)?;
+			let amount_in = Self::get_balance(treasury, &asset)?.saturating_sub(asset_before);
+			let (reserve_in, reserve_out) = Self::get_reserves(&asset, &native, fee_tier)?;
+			let spot_out = Self::quote(&amount_in, &reserve_in, &reserve_out)?;
+			let amount_out_min =
+				spot_out.saturating_sub(T::AutoCompoundMaxSlippage::get().mul_ceil(spot_out));
+			Self::do_swap_exact_tokens_for_tokens_in_pool(
+				treasury.clone(),
+				pool_id.clone(),
+				asset,
+				SwapExactInParams { amount_in, amount_out_min: Some(amount_out_min) },
+				treasury.clone(),
+				false,
+			)?;
+
+			let native_received =
+				Self::get_balance(treasury, &native)?.saturating_sub(native_before);
+			Self::deposit_event(Event::FeesCompounded {
+				pool_id,
+				lp_token_burned: lp_token_burn,
+				native_received,
+			});
+			Ok(())
+		}
+
+		/// Records a swap of `who` at the current block, failing if its last swap was less than
+		/// `SwapCooldown` blocks ago.
+		fn ensure_swap_cooldown(who: &T::AccountId) -> DispatchResult {
+			if let Some(cooldown) = T::SwapCooldown::get() {
+				let now = frame_system::Pallet::<T>::block_number();
+				if let Some(last) = LastSwapBlock::<T>::get(who) {
+					ensure!(now >= last.saturating_add(cooldown), Error::<T>::SwapCooldownActive);
+				}
+				LastSwapBlock::<T>::insert(who, now);
+			}
+			Ok(())
+		}
+
+		/// The boost multiplier earned by `who` for the lp tokens locked in the pool with
+		/// `pool_id`, or one if there is no position or its lock has ended.
+		///
+		/// The multiplier grows linearly with the duration of the lock, from one for no lock to
+		/// two for a lock of `MaxLockDuration`, measured when the position was last locked.
+		pub fn boost_multiplier(who: &T::AccountId, pool_id: &PoolIdOf<T>) -> FixedU128 {
+			let now = frame_system::Pallet::<T>::block_number();
+			LockedPositions::<T>::get(who, pool_id)
+				.filter(|position| position.lock_until > now)
+				.map_or_else(FixedU128::one, |position| position.boost)
+		}
+
+		/// Whether the pool with `pool_id` backs its outstanding lp tokens, i.e. holds both of its
+		/// assets if any lp tokens exist.
+		pub fn is_pool_solvent(
+			pool_id: &PoolIdOf<T>,
+			info: &PoolInfo<T::PoolAssetId, BlockNumberFor<T>>,
+		) -> bool {
+			if T::PoolAssets::total_issuance(info.lp_token.clone()).is_zero() {
+				return true
+			}
+			let pool_account = Self::get_pool_account(pool_id);
+			let (asset1, asset2, _) = pool_id;
+			[asset1, asset2].into_iter().all(|asset| {
+				Self::get_reserve(&pool_account, asset).map_or(false, |reserve| !reserve.is_zero())
+			})
+		}
+
+		/// Leading to an amount at the end of a `path`, get the required amounts in, along with
+		/// the pool each hop goes through: the one of its pair asking for the least in.
 		pub(crate) fn get_amounts_in(
 			amount_out: &T::AssetBalance,
 			path: &BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
-		) -> Result<Vec<T::AssetBalance>, DispatchError> {
+		) -> Result<(Vec<T::AssetBalance>, Vec<PoolIdOf<T>>), DispatchError> {
 			let mut amounts: Vec<T::AssetBalance> = vec![*amount_out];
+			let mut pools = Vec::new();
 
 			for assets_pair in path.windows(2).rev() {
 				if let [asset1, asset2] = assets_pair {
-					let (reserve_in, reserve_out) = Self::get_reserves(asset1, asset2)?;
-					let prev_amount = amounts.last().expect("Always has at least one element");
-					let amount_in = Self::get_amount_in(prev_amount, &reserve_in, &reserve_out)?;
+					let prev_amount = *amounts.last().expect("Always has at least one element");
+					let (pool_id, amount_in) = Self::best_pool(
+						asset1,
+						asset2,
+						Self::get_balance,
+						Self::swap_pool_fee,
+						|reserve_in, reserve_out, fee| {
+							Self::get_amount_in(&prev_amount, reserve_in, reserve_out, fee)
+						},
+						|amount_in, best| amount_in < best,
+					)?;
 					amounts.push(amount_in);
+					pools.push(pool_id);
 				}
 			}
 
 			amounts.reverse();
-			Ok(amounts)
+			pools.reverse();
+			Ok((amounts, pools))
 		}
 
-		/// Following an amount into a `path`, get the corresponding amounts out.
+		/// Following an amount into a `path`, get the corresponding amounts out, along with the
+		/// pool each hop goes through: the one of its pair paying out the most.
 		pub(crate) fn get_amounts_out(
 			amount_in: &T::AssetBalance,
 			path: &BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
-		) -> Result<Vec<T::AssetBalance>, DispatchError> {
+		) -> Result<(Vec<T::AssetBalance>, Vec<PoolIdOf<T>>), DispatchError> {
 			let mut amounts: Vec<T::AssetBalance> = vec![*amount_in];
+			let mut pools = Vec::new();
 
 			for assets_pair in path.windows(2) {
 				if let [asset1, asset2] = assets_pair {
-					let (reserve_in, reserve_out) = Self::get_reserves(asset1, asset2)?;
-					let prev_amount = amounts.last().expect("Always has at least one element");
-					let amount_out = Self::get_amount_out(prev_amount, &reserve_in, &reserve_out)?;
+					let prev_amount = *amounts.last().expect("Always has at least one element");
+					let (pool_id, amount_out) = Self::best_pool(
+						asset1,
+						asset2,
+						Self::get_balance,
+						Self::swap_pool_fee,
+						|reserve_in, reserve_out, fee| {
+							Self::get_amount_out(&prev_amount, reserve_in, reserve_out, fee)
+						},
+						|amount_out, best| amount_out > best,
+					)?;
 					amounts.push(amount_out);
+					pools.push(pool_id);
+				}
+			}
+
+			Ok((amounts, pools))
+		}
+
+		/// Picks, among the pools of `asset_in` and `asset_out` in each of the `FeeTiers`, the one
+		/// whose amount is the best by `is_better`, returning its id along with the amount.
+		///
+		/// `amount_of` is given the reserves of each pool, in the order requested and read with
+		/// `balance_of`, and its fee, read with `fee_of`. Fails with the error of the last pool
+		/// that could not be priced if none could, or with `PoolNotFound` if the pair has no pool.
+		fn best_pool(
+			asset_in: &T::MultiAssetId,
+			asset_out: &T::MultiAssetId,
+			balance_of: fn(&T::AccountId, &T::MultiAssetId) -> Result<T::AssetBalance, Error<T>>,
+			fee_of: fn(&PoolIdOf<T>) -> Result<Permill, Error<T>>,
+			amount_of: impl Fn(
+				&T::AssetBalance,
+				&T::AssetBalance,
+				Permill,
+			) -> Result<T::AssetBalance, Error<T>>,
+			is_better: fn(&T::AssetBalance, &T::AssetBalance) -> bool,
+		) -> Result<(PoolIdOf<T>, T::AssetBalance), Error<T>> {
+			let mut best: Option<(PoolIdOf<T>, T::AssetBalance)> = None;
+			let mut error = Error::<T>::PoolNotFound;
+			for fee_tier in T::FeeTiers::get() {
+				let pool_id = Self::get_pool_id(asset_in.clone(), asset_out.clone(), fee_tier);
+				let amount = fee_of(&pool_id).and_then(|fee| {
+					let (reserve_in, reserve_out) =
+						Self::read_reserves(asset_in, asset_out, fee_tier, balance_of)?;
+					amount_of(&reserve_in, &reserve_out, fee)
+				});
+				match amount {
+					Ok(amount)
+						if best.as_ref().map_or(true, |(_, best)| is_better(&amount, best)) =>
+						best = Some((pool_id, amount)),
+					Ok(_) | Err(Error::<T>::PoolNotFound) => {},
+					Err(pool_error) => error = pool_error,
 				}
 			}
+			best.ok_or(error)
+		}
+
+		/// Used by the RPC service to provide current prices.
+		///
+		/// The price is the one of the pool of the pair [`Pallet::swap_exact_tokens_for_tokens`]
+		/// would go through, or of the pool with the best spot price if `include_fee` is false.
+		///
+		/// Returns `None` for any pair of assets without a pool, including ids outside of the
+		/// runtime's asset space, even if the account of such a pool holds a balance.
+		pub fn quote_price_exact_tokens_for_tokens(
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			amount: T::AssetBalance,
+			include_fee: bool,
+		) -> Option<T::AssetBalance> {
+			let (_, amount_out) = Self::best_pool(
+				&asset1,
+				&asset2,
+				Self::get_reserve,
+				Self::quoted_pool_fee,
+				|balance1, balance2, fee| {
+					if include_fee {
+						Self::get_amount_out(&amount, balance1, balance2, fee)
+					} else {
+						Self::quote(&amount, balance1, balance2)
+					}
+				},
+				|amount_out, best| amount_out > best,
+			)
+			.ok()?;
+			Some(amount_out)
+		}
+
+		/// Returns the current price of `asset1` in `asset2` in the pool of the `fee_tier` as the
+		/// rational `(numerator, denominator)`, i.e. the `(reserve2, reserve1)` of the pool in the
+		/// caller's order.
+		///
+		/// Returns `None` if the pool doesn't exist or either of its reserves is empty.
+		pub fn spot_price(
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+		) -> Option<(T::AssetBalance, T::AssetBalance)> {
+			let (reserve1, reserve2) = Self::get_reserves(&asset1, &asset2, fee_tier).ok()?;
+			Some((reserve2, reserve1))
+		}
+
+		/// Returns how much worse than the spot price swapping `amount_in` of `asset_in` for
+		/// `asset_out` in the pool of the `fee_tier` is, i.e.
+		/// `1 - (amount_out / amount_in) / (reserve_out / reserve_in)`. Rounded up.
+		///
+		/// The fee of the pool is part of the impact, so even the smallest trades report about
+		/// the fee. Returns `None` if the pool doesn't exist, is empty or `amount_in` is zero.
+		pub fn price_impact(
+			asset_in: T::MultiAssetId,
+			asset_out: T::MultiAssetId,
+			fee_tier: Permill,
+			amount_in: T::AssetBalance,
+		) -> Option<Permill> {
+			let (reserve_in, reserve_out) =
+				Self::get_reserves(&asset_in, &asset_out, fee_tier).ok()?;
+			let fee =
+				Self::quoted_pool_fee(&Self::get_pool_id(asset_in, asset_out, fee_tier)).ok()?;
+			let amount_out =
+				Self::get_amount_out(&amount_in, &reserve_in, &reserve_out, fee).ok()?;
+
+			// 1 - amount_out * reserve_in / (amount_in * reserve_out)
+			let executed = T::HigherPrecisionBalance::from(amount_out)
+				.checked_mul(&T::HigherPrecisionBalance::from(reserve_in))?;
+			let spot = T::HigherPrecisionBalance::from(amount_in)
+				.checked_mul(&T::HigherPrecisionBalance::from(reserve_out))?;
+			if spot.is_zero() {
+				return None
+			}
+			let parts = Self::div_rounding(
+				&spot.checked_sub(&executed)?.checked_mul(&Permill::ACCURACY.into())?,
+				&spot,
+				Rounding::Up,
+			)
+			.ok()?;
+			let parts: u32 = Self::convert_hpb_to_asset_balance(parts).ok()?.try_into().ok()?;
+			Some(Permill::from_parts(parts))
+		}
+
+		/// Returns the fee-free price of `amount` in both directions in the pool of the `fee_tier`
+		/// as `(a1_to_a2, a2_to_a1)`, i.e. `amount` of `asset1` quoted in `asset2` and `amount` of
+		/// `asset2` quoted in `asset1`.
+		///
+		/// The pool reserves are only read once for both quotes.
+		pub fn quote_both_directions(
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+			amount: T::AssetBalance,
+		) -> Option<(T::AssetBalance, T::AssetBalance)> {
+			let (reserve1, reserve2) = Self::get_reserves(&asset1, &asset2, fee_tier).ok()?;
+			let a1_to_a2 = Self::quote(&amount, &reserve1, &reserve2).ok()?;
+			let a2_to_a1 = Self::quote(&amount, &reserve2, &reserve1).ok()?;
+			Some((a1_to_a2, a2_to_a1))
+		}
 
-			Ok(amounts)
+		/// Returns the `(expected_out, min_out)` of swapping exactly `amount_in` of `asset1` for
+		/// `asset2`, where `min_out` allows the price to move against the caller by `slippage`.
+		///
+		/// `min_out` is rounded down and can be used as the `amount_out_min` of
+		/// [`Pallet::swap_exact_tokens_for_tokens`].
+		pub fn quote_with_slippage(
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			amount_in: T::AssetBalance,
+			slippage: Permill,
+		) -> Option<(T::AssetBalance, T::AssetBalance)> {
+			let expected_out =
+				Self::quote_price_exact_tokens_for_tokens(asset1, asset2, amount_in, true)?;
+			let min_out = expected_out.saturating_sub(slippage.mul_ceil(expected_out));
+			Some((expected_out, min_out))
 		}
 
 		/// Used by the RPC service to provide current prices.
-		pub fn quote_price_exact_tokens_for_tokens(
+		///
+		/// The price is the one of the pool of the pair [`Pallet::swap_tokens_for_exact_tokens`]
+		/// would go through, or of the pool with the best spot price if `include_fee` is false.
+		///
+		/// Returns `None` for any pair of assets without a pool, like
+		/// [`Pallet::quote_price_exact_tokens_for_tokens`].
+		pub fn quote_price_tokens_for_exact_tokens(
 			asset1: T::MultiAssetId,
 			asset2: T::MultiAssetId,
 			amount: T::AssetBalance,
 			include_fee: bool,
 		) -> Option<T::AssetBalance> {
-			let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone());
+			let (_, amount_in) = Self::best_pool(
+				&asset1,
+				&asset2,
+				Self::get_reserve,
+				Self::quoted_pool_fee,
+				|balance1, balance2, fee| {
+					if include_fee {
+						Self::get_amount_in(&amount, balance1, balance2, fee)
+					} else {
+						Self::quote(&amount, balance2, balance1)
+					}
+				},
+				|amount_in, best| amount_in < best,
+			)
+			.ok()?;
+			Some(amount_in)
+		}
+
+		/// Calculates the optimal amount from the reserves, rounded down.
+		pub fn quote(
+			amount: &T::AssetBalance,
+			reserve1: &T::AssetBalance,
+			reserve2: &T::AssetBalance,
+		) -> Result<T::AssetBalance, Error<T>> {
+			Self::quote_with_rounding(amount, reserve1, reserve2, Rounding::Down)
+		}
+
+		/// Like [`Pallet::quote`], rounding as given by `rounding`.
+		pub fn quote_with_rounding(
+			amount: &T::AssetBalance,
+			reserve1: &T::AssetBalance,
+			reserve2: &T::AssetBalance,
+			rounding: Rounding,
+		) -> Result<T::AssetBalance, Error<T>> {
+			ensure!(!reserve1.is_zero() && !reserve2.is_zero(), Error::<T>::InsufficientLiquidity);
+			// amount * reserve2 / reserve1
+			Self::mul_div_rounding(amount, reserve2, reserve1, rounding)
+		}
+
+		/// Computes the amounts [`Pallet::add_liquidity`] would take from the provider and the
+		/// amount of lp tokens it would mint, without altering any state.
+		///
+		/// The returned `(amount1, amount2, lp_token_amount)` follow the order of the given assets.
+		pub fn compute_add_liquidity(
+			asset1: T::MultiAssetId,
+			asset2: T::MultiAssetId,
+			fee_tier: Permill,
+			amount1_desired: T::AssetBalance,
+			amount2_desired: T::AssetBalance,
+		) -> Result<(T::AssetBalance, T::AssetBalance, T::AssetBalance), Error<T>> {
+			let (pool_id, is_pool_order) = Self::get_pool_id_with_flag(asset1, asset2, fee_tier);
+			let (amount1_desired, amount2_desired) = if is_pool_order {
+				(amount1_desired, amount2_desired)
+			} else {
+				(amount2_desired, amount1_desired)
+			};
+			ensure!(
+				amount1_desired > Zero::zero() && amount2_desired > Zero::zero(),
+				Error::<T>::WrongDesiredAmount
+			);
+
+			let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
 			let pool_account = Self::get_pool_account(&pool_id);
+			let reserve1 = Self::get_reserve(&pool_account, &pool_id.0)?;
+			let reserve2 = Self::get_reserve(&pool_account, &pool_id.1)?;
+
+			let (amount1, amount2) = Self::calc_add_liquidity_amounts(
+				&amount1_desired,
+				&amount2_desired,
+				&Zero::zero(),
+				&Zero::zero(),
+				&reserve1,
+				&reserve2,
+			)?;
+			let total_supply = T::PoolAssets::total_issuance(pool.lp_token);
+			let lp_token_amount = Self::calc_lp_amount_to_mint(
+				&amount1,
+				&amount2,
+				&reserve1,
+				&reserve2,
+				&total_supply,
+			)?;
 
-			let balance1 = Self::get_balance(&pool_account, &asset1).ok()?;
-			let balance2 = Self::get_balance(&pool_account, &asset2).ok()?;
-			if !balance1.is_zero() {
-				if include_fee {
-					Self::get_amount_out(&amount, &balance1, &balance2).ok()
-				} else {
-					Self::quote(&amount, &balance1, &balance2).ok()
-				}
+			if is_pool_order {
+				Ok((amount1, amount2, lp_token_amount))
 			} else {
-				None
+				Ok((amount2, amount1, lp_token_amount))
 			}
 		}
 
-		/// Used by the RPC service to provide current prices.
-		pub fn quote_price_tokens_for_exact_tokens(
+		/// Calculates the amounts of both assets to add to a pool with the given reserves, keeping
+		/// the ratio of the pool. Amounts and reserves are expected in the order of the pool id.
+		pub(super) fn calc_add_liquidity_amounts(
+			amount1_desired: &T::AssetBalance,
+			amount2_desired: &T::AssetBalance,
+			amount1_min: &T::AssetBalance,
+			amount2_min: &T::AssetBalance,
+			reserve1: &T::AssetBalance,
+			reserve2: &T::AssetBalance,
+		) -> Result<(T::AssetBalance, T::AssetBalance), Error<T>> {
+			if reserve1.is_zero() || reserve2.is_zero() {
+				return Ok((*amount1_desired, *amount2_desired))
+			}
+
+			let amount2_optimal = Self::quote(amount1_desired, reserve1, reserve2)?;
+			if amount2_optimal <= *amount2_desired {
+				ensure!(
+					amount2_optimal >= *amount2_min,
+					Error::<T>::AssetTwoDepositDidNotMeetMinimum
+				);
+				Ok((*amount1_desired, amount2_optimal))
+			} else {
+				let amount1_optimal = Self::quote(amount2_desired, reserve2, reserve1)?;
+				ensure!(
+					amount1_optimal <= *amount1_desired,
+					Error::<T>::OptimalAmountLessThanDesired
+				);
+				ensure!(
+					amount1_optimal >= *amount1_min,
+					Error::<T>::AssetOneDepositDidNotMeetMinimum
+				);
+				Ok((amount1_optimal, *amount2_desired))
+			}
+		}
+
+		/// Computes the amounts [`Pallet::remove_liquidity`] would pay out for burning
+		/// `lp_amount` lp tokens of the pool of `asset1` and `asset2` in the `fee_tier`, after the
+		/// `LiquidityWithdrawalFee`, without altering any state.
+		///
+		/// The returned `(amount1, amount2)` follow the order of the given assets.
+		pub fn lp_redemption_value(
 			asset1: T::MultiAssetId,
 			asset2: T::MultiAssetId,
-			amount: T::AssetBalance,
-			include_fee: bool,
-		) -> Option<T::AssetBalance> {
-			let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone());
+			fee_tier: Permill,
+			lp_amount: T::AssetBalance,
+		) -> Result<(T::AssetBalance, T::AssetBalance), Error<T>> {
+			let (pool_id, is_pool_order) = Self::get_pool_id_with_flag(asset1, asset2, fee_tier);
+			let pool = Pools::<T>::get(&pool_id).ok_or(Error::<T>::PoolNotFound)?;
 			let pool_account = Self::get_pool_account(&pool_id);
-
-			let balance1 = Self::get_balance(&pool_account, &asset1).ok()?;
-			let balance2 = Self::get_balance(&pool_account, &asset2).ok()?;
-			if !balance1.is_zero() {
-				if include_fee {
-					Self::get_amount_in(&amount, &balance1, &balance2).ok()
-				} else {
-					Self::quote(&amount, &balance2, &balance1).ok()
-				}
+			let reserve1 = Self::get_reserve(&pool_account, &pool_id.0)?;
+			let reserve2 = Self::get_reserve(&pool_account, &pool_id.1)?;
+			let total_supply = T::PoolAssets::total_issuance(pool.lp_token);
+
+			let (amount1, amount2) =
+				Self::calc_redemption_amounts(&lp_amount, &reserve1, &reserve2, &total_supply)?;
+			if is_pool_order {
+				Ok((amount1, amount2))
 			} else {
-				None
+				Ok((amount2, amount1))
 			}
 		}
 
-		/// Calculates the optimal amount from the reserves.
-		pub fn quote(
-			amount: &T::AssetBalance,
+		/// Calculates the amounts of a pool with the given reserves and lp token `total_supply`
+		/// that burning `lp_token_burn` lp tokens redeems, after the `LiquidityWithdrawalFee`.
+		pub(super) fn calc_redemption_amounts(
+			lp_token_burn: &T::AssetBalance,
+			reserve1: &T::AssetBalance,
+			reserve2: &T::AssetBalance,
+			total_supply: &T::AssetBalance,
+		) -> Result<(T::AssetBalance, T::AssetBalance), Error<T>> {
+			let withdrawal_fee_amount = T::LiquidityWithdrawalFee::get() * *lp_token_burn;
+			let lp_redeem_amount = lp_token_burn.saturating_sub(withdrawal_fee_amount);
+
+			let amount1 = Self::mul_div(&lp_redeem_amount, reserve1, total_supply)?;
+			let amount2 = Self::mul_div(&lp_redeem_amount, reserve2, total_supply)?;
+			Ok((amount1, amount2))
+		}
+
+		/// Calculates the amount of lp tokens to mint for adding `amount1` and `amount2` to a pool
+		/// with the given reserves and lp token `total_supply`.
+		pub(super) fn calc_lp_amount_to_mint(
+			amount1: &T::AssetBalance,
+			amount2: &T::AssetBalance,
 			reserve1: &T::AssetBalance,
 			reserve2: &T::AssetBalance,
+			total_supply: &T::AssetBalance,
 		) -> Result<T::AssetBalance, Error<T>> {
-			// amount * reserve2 / reserve1
-			Self::mul_div(amount, reserve2, reserve1)
+			let lp_token_amount = if total_supply.is_zero() {
+				Self::calc_lp_amount_for_zero_supply(amount1, amount2)?
+			} else {
+				let side1 = Self::mul_div(amount1, total_supply, reserve1)?;
+				let side2 = Self::mul_div(amount2, total_supply, reserve2)?;
+				let (low, high) = (side1.min(side2), side1.max(side2));
+				if let Some(tolerance) = T::MaxLpRatioImbalance::get() {
+					ensure!(
+						high.saturating_sub(low) <= tolerance * high,
+						Error::<T>::RatioImbalance
+					);
+				}
+				low
+			};
+
+			ensure!(
+				lp_token_amount > T::MintMinLiquidity::get(),
+				Error::<T>::InsufficientLiquidityMinted
+			);
+			Ok(lp_token_amount)
 		}
 
 		pub(super) fn calc_lp_amount_for_zero_supply(
@@ -1093,63 +3966,194 @@ pub mod pallet {
 			a: &T::AssetBalance,
 			b: &T::AssetBalance,
 			c: &T::AssetBalance,
+		) -> Result<T::AssetBalance, Error<T>> {
+			Self::mul_div_rounding(a, b, c, Rounding::Down)
+		}
+
+		fn mul_div_rounding(
+			a: &T::AssetBalance,
+			b: &T::AssetBalance,
+			c: &T::AssetBalance,
+			rounding: Rounding,
 		) -> Result<T::AssetBalance, Error<T>> {
 			let a = T::HigherPrecisionBalance::from(*a);
 			let b = T::HigherPrecisionBalance::from(*b);
 			let c = T::HigherPrecisionBalance::from(*c);
 
-			let result = a
-				.checked_mul(&b)
-				.ok_or(Error::<T>::Overflow)?
-				.checked_div(&c)
-				.ok_or(Error::<T>::Overflow)?;
+			let result =
+				Self::div_rounding(&a.checked_mul(&b).ok_or(Error::<T>::Overflow)?, &c, rounding)?;
 
 			result.try_into().map_err(|_| Error::<T>::Overflow)
 		}
 
+		/// Divides `numerator` by `denominator`, rounding as given by `rounding`.
+		fn div_rounding(
+			numerator: &T::HigherPrecisionBalance,
+			denominator: &T::HigherPrecisionBalance,
+			rounding: Rounding,
+		) -> Result<T::HigherPrecisionBalance, Error<T>> {
+			let quotient = numerator.checked_div(denominator).ok_or(Error::<T>::Overflow)?;
+			let remainder = numerator
+				.checked_sub(&quotient.checked_mul(denominator).ok_or(Error::<T>::Overflow)?)
+				.ok_or(Error::<T>::Overflow)?;
+			let twice_remainder =
+				|| remainder.checked_mul(&2u32.into()).ok_or(Error::<T>::Overflow);
+
+			let round_up = match rounding {
+				Rounding::Down => false,
+				Rounding::Up => !remainder.is_zero(),
+				Rounding::NearestPrefUp => twice_remainder()? >= *denominator,
+				Rounding::NearestPrefDown => twice_remainder()? > *denominator,
+			};
+			if round_up {
+				quotient.checked_add(&One::one()).ok_or(Error::<T>::Overflow)
+			} else {
+				Ok(quotient)
+			}
+		}
+
 		/// Calculates amount out.
 		///
-		/// Given an input amount of an asset and pair reserves, returns the maximum output amount
-		/// of the other asset.
+		/// Given an input amount of an asset, pair reserves and the `fee` of the pool, returns the
+		/// maximum output amount of the other asset.
+		///
+		/// The result is rounded down, so that `get_amount_in(get_amount_out(x)) <= x`: quoting the
+		/// output back never asks for more than was paid in.
 		pub fn get_amount_out(
 			amount_in: &T::AssetBalance,
 			reserve_in: &T::AssetBalance,
 			reserve_out: &T::AssetBalance,
+			fee: Permill,
 		) -> Result<T::AssetBalance, Error<T>> {
-			let amount_in = T::HigherPrecisionBalance::from(*amount_in);
-			let reserve_in = T::HigherPrecisionBalance::from(*reserve_in);
-			let reserve_out = T::HigherPrecisionBalance::from(*reserve_out);
+			Self::get_amount_out_with_rounding(
+				amount_in,
+				reserve_in,
+				reserve_out,
+				fee,
+				Rounding::Down,
+			)
+		}
 
+		/// Like [`Pallet::get_amount_out`], rounding as given by `rounding`.
+		///
+		/// Swaps always round down, anything else can pay out more than the pool can afford.
+		pub fn get_amount_out_with_rounding(
+			amount_in: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			reserve_out: &T::AssetBalance,
+			fee: Permill,
+			rounding: Rounding,
+		) -> Result<T::AssetBalance, Error<T>> {
 			if reserve_in.is_zero() || reserve_out.is_zero() {
 				return Err(Error::<T>::ZeroLiquidity.into())
 			}
 
-			let amount_in_with_fee = amount_in
-				.checked_mul(&(T::HigherPrecisionBalance::from(1000u32) - (T::LPFee::get().into())))
-				.ok_or(Error::<T>::Overflow)?;
+			let fee_multiplier = Self::fee_multiplier(fee)?;
+			if let Some(amount_out) =
+				Self::get_amount_out_u128(amount_in, reserve_in, reserve_out, fee, rounding)
+			{
+				return Ok(amount_out)
+			}
+
+			let amount_in = T::HigherPrecisionBalance::from(*amount_in);
+			let reserve_in = T::HigherPrecisionBalance::from(*reserve_in);
+			let reserve_out = T::HigherPrecisionBalance::from(*reserve_out);
+
+			let amount_in_with_fee =
+				amount_in.checked_mul(&fee_multiplier).ok_or(Error::<T>::Overflow)?;
 
 			let numerator =
 				amount_in_with_fee.checked_mul(&reserve_out).ok_or(Error::<T>::Overflow)?;
 
 			let denominator = reserve_in
-				.checked_mul(&1000u32.into())
+				.checked_mul(&Permill::ACCURACY.into())
 				.ok_or(Error::<T>::Overflow)?
 				.checked_add(&amount_in_with_fee)
 				.ok_or(Error::<T>::Overflow)?;
 
-			let result = numerator.checked_div(&denominator).ok_or(Error::<T>::Overflow)?;
+			let result = Self::div_rounding(&numerator, &denominator, rounding)?;
+
+			result.try_into().map_err(|_| Error::<T>::Overflow)
+		}
+
+		/// The amount of `amount_in` to swap into the other asset of a pool with `reserve_in` and
+		/// `fee`, so that the rest of `amount_in` and the swap's output can be added to the pool in
+		/// its new ratio. Rounded down, so the swapped amount never exceeds the optimum.
+		///
+		/// With `g = 1 - fee`, swapping `s` balances the holdings when
+		/// `g * s^2 + (1 + g) * reserve_in * s - amount_in * reserve_in = 0`, whose positive
+		/// root is `(sqrt(((1 + g) * reserve_in)^2 + 4 * g * amount_in * reserve_in) -
+		/// (1 + g) * reserve_in) / (2 * g)`.
+		pub fn optimal_swap_fraction(
+			amount_in: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			fee: Permill,
+		) -> Result<T::AssetBalance, Error<T>> {
+			if reserve_in.is_zero() {
+				return Err(Error::<T>::ZeroLiquidity)
+			}
+
+			// `g` and `1 + g` in parts per million.
+			let g = T::HigherPrecisionBalance::from(Permill::ACCURACY)
+				.checked_sub(&fee.deconstruct().into())
+				.filter(|g| !g.is_zero())
+				.ok_or(Error::<T>::InvalidFeeConfiguration)?;
+			let one_plus_g =
+				g.checked_add(&Permill::ACCURACY.into()).ok_or(Error::<T>::Overflow)?;
+			let amount_in = T::HigherPrecisionBalance::from(*amount_in);
+			let reserve_in = T::HigherPrecisionBalance::from(*reserve_in);
+
+			// everything below is scaled by `Permill::ACCURACY`, which cancels out in the root.
+			let b = one_plus_g.checked_mul(&reserve_in).ok_or(Error::<T>::Overflow)?;
+			let four_a_c = g
+				.checked_mul(&Permill::ACCURACY.into())
+				.and_then(|x| x.checked_mul(&4u32.into()))
+				.and_then(|x| x.checked_mul(&amount_in))
+				.and_then(|x| x.checked_mul(&reserve_in))
+				.ok_or(Error::<T>::Overflow)?;
+			let discriminant = b
+				.checked_mul(&b)
+				.and_then(|x| x.checked_add(&four_a_c))
+				.ok_or(Error::<T>::Overflow)?;
+			let numerator =
+				discriminant.integer_sqrt().checked_sub(&b).ok_or(Error::<T>::Overflow)?;
+			let denominator = g.checked_mul(&2u32.into()).ok_or(Error::<T>::Overflow)?;
 
+			let result = Self::div_rounding(&numerator, &denominator, Rounding::Down)?;
 			result.try_into().map_err(|_| Error::<T>::Overflow)
 		}
 
 		/// Calculates amount in.
 		///
-		/// Given an output amount of an asset and pair reserves, returns a required input amount
-		/// of the other asset.
+		/// Given an output amount of an asset, pair reserves and the `fee` of the pool, returns a
+		/// required input amount of the other asset.
+		///
+		/// The result is rounded up, so that `get_amount_out(get_amount_in(y)) >= y`: paying the
+		/// returned amount in always gets at least `y` out.
 		pub fn get_amount_in(
 			amount_out: &T::AssetBalance,
 			reserve_in: &T::AssetBalance,
 			reserve_out: &T::AssetBalance,
+			fee: Permill,
+		) -> Result<T::AssetBalance, Error<T>> {
+			Self::get_amount_in_with_rounding(
+				amount_out,
+				reserve_in,
+				reserve_out,
+				fee,
+				Rounding::Up,
+			)
+		}
+
+		/// Like [`Pallet::get_amount_in`], rounding as given by `rounding`.
+		///
+		/// Swaps always round up, anything else can charge less than the pool is owed.
+		pub fn get_amount_in_with_rounding(
+			amount_out: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			reserve_out: &T::AssetBalance,
+			fee: Permill,
+			rounding: Rounding,
 		) -> Result<T::AssetBalance, Error<T>> {
 			let amount_out = T::HigherPrecisionBalance::from(*amount_out);
 			let reserve_in = T::HigherPrecisionBalance::from(*reserve_in);
@@ -1166,24 +4170,181 @@ pub mod pallet {
 			let numerator = reserve_in
 				.checked_mul(&amount_out)
 				.ok_or(Error::<T>::Overflow)?
-				.checked_mul(&1000u32.into())
+				.checked_mul(&Permill::ACCURACY.into())
 				.ok_or(Error::<T>::Overflow)?;
 
 			let denominator = reserve_out
 				.checked_sub(&amount_out)
 				.ok_or(Error::<T>::Overflow)?
-				.checked_mul(&(T::HigherPrecisionBalance::from(1000u32) - T::LPFee::get().into()))
+				.checked_mul(&Self::fee_multiplier(fee)?)
 				.ok_or(Error::<T>::Overflow)?;
 
-			let result = numerator
-				.checked_div(&denominator)
-				.ok_or(Error::<T>::Overflow)?
-				.checked_add(&One::one())
-				.ok_or(Error::<T>::Overflow)?;
+			let result = Self::div_rounding(&numerator, &denominator, rounding)?;
 
 			result.try_into().map_err(|_| Error::<T>::Overflow)
 		}
 
+		/// Computes [`Pallet::get_amount_out_with_rounding`] in `u128`, dividing with
+		/// `multiply_by_rational_with_rounding` so that the numerator may exceed a `u128`.
+		///
+		/// Returns `None` if an amount or the denominator does not fit in a `u128`, leaving the
+		/// computation to `HigherPrecisionBalance`.
+		fn get_amount_out_u128(
+			amount_in: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			reserve_out: &T::AssetBalance,
+			fee: Permill,
+			rounding: Rounding,
+		) -> Option<T::AssetBalance> {
+			let amount_in: u128 = (*amount_in).try_into().ok()?;
+			let reserve_in: u128 = (*reserve_in).try_into().ok()?;
+			let reserve_out: u128 = (*reserve_out).try_into().ok()?;
+			let fee_multiplier = Permill::ACCURACY.checked_sub(fee.deconstruct())?;
+
+			let amount_in_with_fee = amount_in.checked_mul(fee_multiplier.into())?;
+			let denominator = reserve_in
+				.checked_mul(Permill::ACCURACY.into())?
+				.checked_add(amount_in_with_fee)?;
+
+			multiply_by_rational_with_rounding(
+				amount_in_with_fee,
+				reserve_out,
+				denominator,
+				rounding,
+			)?
+			.try_into()
+			.ok()
+		}
+
+		/// The share of an input amount, in parts per million, that is left after the `fee`.
+		fn fee_multiplier(fee: Permill) -> Result<T::HigherPrecisionBalance, Error<T>> {
+			T::HigherPrecisionBalance::from(Permill::ACCURACY)
+				.checked_sub(&fee.deconstruct().into())
+				.filter(|multiplier| !multiplier.is_zero())
+				.ok_or(Error::<T>::InvalidFeeConfiguration)
+		}
+
+		/// Panics if the pool with `pool_id` has lp tokens in circulation but an empty reserve.
+		///
+		/// Only compiled in debug builds, where it runs at the end of every call that changes
+		/// the reserves. The reserves are unsigned balances, so they can't go negative.
+		#[cfg(debug_assertions)]
+		pub(crate) fn ensure_pool_invariant(pool_id: &PoolIdOf<T>) {
+			let Some(pool) = Pools::<T>::get(pool_id) else { return };
+			if T::PoolAssets::total_issuance(pool.lp_token).is_zero() {
+				return
+			}
+			let pool_account = Self::get_pool_account(pool_id);
+			let has_reserve =
+				|asset| Self::get_balance(&pool_account, asset).map_or(false, |r| !r.is_zero());
+			debug_assert!(
+				has_reserve(&pool_id.0) && has_reserve(&pool_id.1),
+				"a pool with lp tokens in circulation must have both reserves"
+			);
+		}
+
+		/// The share of the input of every swap through `pool` taken by its liquidity providers.
+		fn pool_fee(pool: &PoolInfo<T::PoolAssetId, BlockNumberFor<T>>) -> Permill {
+			GlobalFee::<T>::get().unwrap_or(pool.fee_tier)
+		}
+
+		/// The fee of the pool with `pool_id`, failing if it doesn't exist.
+		fn swap_pool_fee(pool_id: &PoolIdOf<T>) -> Result<Permill, Error<T>> {
+			let pool = Pools::<T>::get(pool_id).ok_or(Error::<T>::PoolNotFound)?;
+			Ok(Self::pool_fee(&pool))
+		}
+
+		/// Like [`Self::swap_pool_fee`], but remembering the fee of the pool in the [`QuoteCache`]
+		/// for the rest of the dispatch. Outside of a dispatch, the pool is read every time.
+		fn quoted_pool_fee(pool_id: &PoolIdOf<T>) -> Result<Permill, Error<T>> {
+			let max_len = T::QuoteCacheSize::get() as usize;
+			if max_len.is_zero() {
+				return Self::swap_pool_fee(pool_id)
+			}
+			with_context::<QuoteCache<PoolIdOf<T>>, _>(|cache| {
+				let cache = &mut cache.or_default().0;
+				if let Some((_, fee)) = cache.iter().find(|(cached, _)| cached == pool_id) {
+					return Ok(*fee)
+				}
+				let fee = Self::swap_pool_fee(pool_id)?;
+				// once the cache is full, further pools are read every time.
+				if cache.len() < max_len {
+					cache.push((pool_id.clone(), fee));
+				}
+				Ok(fee)
+			})
+			// not in a dispatch.
+			.unwrap_or_else(|| Self::swap_pool_fee(pool_id))
+		}
+
+		/// Forget the pools remembered by the price quotes of the current dispatch.
+		fn clear_quote_cache() {
+			with_context::<QuoteCache<PoolIdOf<T>>, _>(|cache| cache.clear());
+		}
+
+		/// Returns true if the product of the reserves did not decrease from `(old1, old2)` to
+		/// `(new1, new2)`, which must hold after every swap.
+		///
+		/// If either product can not be represented by `HigherPrecisionBalance` there is nothing to
+		/// compare, and true is returned.
+		pub(crate) fn is_constant_product_preserved(
+			old1: &T::AssetBalance,
+			old2: &T::AssetBalance,
+			new1: &T::AssetBalance,
+			new2: &T::AssetBalance,
+		) -> bool {
+			let product = |a: &T::AssetBalance, b: &T::AssetBalance| {
+				T::HigherPrecisionBalance::from(*a)
+					.checked_mul(&T::HigherPrecisionBalance::from(*b))
+			};
+			match (product(old1, old2), product(new1, new2)) {
+				(Some(before), Some(after)) => after >= before,
+				_ => true,
+			}
+		}
+
+		/// Returns by how much swapping `amount_in` for `amount_out` moves the price of the input
+		/// asset, `reserve_out / reserve_in`, down. Rounded up.
+		pub(crate) fn price_move(
+			amount_in: &T::AssetBalance,
+			amount_out: &T::AssetBalance,
+			reserve_in: &T::AssetBalance,
+			reserve_out: &T::AssetBalance,
+		) -> Result<Permill, Error<T>> {
+			let amount_in = T::HigherPrecisionBalance::from(*amount_in);
+			let amount_out = T::HigherPrecisionBalance::from(*amount_out);
+			let reserve_in = T::HigherPrecisionBalance::from(*reserve_in);
+			let reserve_out = T::HigherPrecisionBalance::from(*reserve_out);
+
+			// 1 - (reserve_out - amount_out) / (reserve_in + amount_in) / (reserve_out /
+			// reserve_in)
+			let denominator = reserve_out
+				.checked_mul(&reserve_in.checked_add(&amount_in).ok_or(Error::<T>::Overflow)?)
+				.ok_or(Error::<T>::Overflow)?;
+			ensure!(!denominator.is_zero(), Error::<T>::ZeroLiquidity);
+			let numerator = denominator
+				.checked_sub(
+					&reserve_out
+						.checked_sub(&amount_out)
+						.ok_or(Error::<T>::Overflow)?
+						.checked_mul(&reserve_in)
+						.ok_or(Error::<T>::Overflow)?,
+				)
+				.ok_or(Error::<T>::Overflow)?;
+
+			let parts = numerator
+				.checked_mul(&Permill::ACCURACY.into())
+				.ok_or(Error::<T>::Overflow)?
+				.checked_add(&denominator.checked_sub(&One::one()).ok_or(Error::<T>::Overflow)?)
+				.ok_or(Error::<T>::Overflow)?
+				.checked_div(&denominator)
+				.ok_or(Error::<T>::Overflow)?;
+			let parts: u32 = Self::convert_hpb_to_asset_balance(parts)?
+				.try_into()
+				.map_err(|_| Error::<T>::Overflow)?;
+			Ok(Permill::from_parts(parts))
+		}
+
 		/// Ensure that a `value` meets the minimum balance requirements of an `asset` class.
 		fn validate_minimal_amount(
 			value: T::AssetBalance,
@@ -1207,19 +4368,29 @@ pub mod pallet {
 			Ok(())
 		}
 
+		/// Ensure that adding `amount` to `reserve` does not exceed `MaxReserve`.
+		fn validate_reserve_cap(
+			reserve: &T::AssetBalance,
+			amount: &T::AssetBalance,
+		) -> Result<(), Error<T>> {
+			let new_reserve = reserve.checked_add(amount).ok_or(Error::<T>::ReserveCapExceeded)?;
+			ensure!(new_reserve <= T::MaxReserve::get(), Error::<T>::ReserveCapExceeded);
+			Ok(())
+		}
+
 		/// Ensure that a path is valid.
 		fn validate_swap_path(
 			path: &BoundedVec<T::MultiAssetId, T::MaxSwapPathLength>,
 		) -> Result<(), DispatchError> {
 			ensure!(path.len() >= 2, Error::<T>::InvalidPath);
 
-			// validate all the pools in the path are unique
-			let mut pools = BoundedBTreeSet::<PoolIdOf<T>, T::MaxSwapPathLength>::new();
+			// validate all the pairs in the path are unique, as any of their pools could be used
+			let mut pairs =
+				BoundedBTreeSet::<(T::MultiAssetId, T::MultiAssetId), T::MaxSwapPathLength>::new();
 			for assets_pair in path.windows(2) {
 				if let [asset1, asset2] = assets_pair {
-					let pool_id = Self::get_pool_id(asset1.clone(), asset2.clone());
-					let new_element =
-						pools.try_insert(pool_id).map_err(|_| Error::<T>::Overflow)?;
+					let pair = Self::sort_assets(asset1.clone(), asset2.clone());
+					let new_element = pairs.try_insert(pair).map_err(|_| Error::<T>::Overflow)?;
 					if !new_element {
 						return Err(Error::<T>::NonUniquePath.into())
 					}
@@ -1247,13 +4418,15 @@ impl<T: Config> Swap<T::AccountId, T::HigherPrecisionBalance, T::MultiAssetId> f
 		send_to: T::AccountId,
 		keep_alive: bool,
 	) -> Result<T::HigherPrecisionBalance, DispatchError> {
-		let path = path.try_into().map_err(|_| Error::<T>::PathError)?;
+		let path = path.try_into().map_err(|_| Error::<T>::PathTooLong)?;
 		let amount_out_min = amount_out_min.map(Self::convert_hpb_to_asset_balance).transpose()?;
 		let amount_out = Self::do_swap_exact_tokens_for_tokens(
 			sender,
 			path,
-			Self::convert_hpb_to_asset_balance(amount_in)?,
-			amount_out_min,
+			SwapExactInParams {
+				amount_in: Self::convert_hpb_to_asset_balance(amount_in)?,
+				amount_out_min,
+			},
 			send_to,
 			keep_alive,
 		)?;
@@ -1268,13 +4441,15 @@ impl<T: Config> Swap<T::AccountId, T::HigherPrecisionBalance, T::MultiAssetId> f
 		send_to: T::AccountId,
 		keep_alive: bool,
 	) -> Result<T::HigherPrecisionBalance, DispatchError> {
-		let path = path.try_into().map_err(|_| Error::<T>::PathError)?;
+		let path = path.try_into().map_err(|_| Error::<T>::PathTooLong)?;
 		let amount_in_max = amount_in_max.map(Self::convert_hpb_to_asset_balance).transpose()?;
 		let amount_in = Self::do_swap_tokens_for_exact_tokens(
 			sender,
 			path,
-			Self::convert_hpb_to_asset_balance(amount_out)?,
-			amount_in_max,
+			SwapExactOutParams {
+				amount_out: Self::convert_hpb_to_asset_balance(amount_out)?,
+				amount_in_max,
+			},
 			send_to,
 			keep_alive,
 		)?;
@@ -1282,6 +4457,114 @@ impl<T: Config> Swap<T::AccountId, T::HigherPrecisionBalance, T::MultiAssetId> f
 	}
 }
 
+impl<T: Config> fungibles::Inspect<T::AccountId> for Pallet<T> {
+	type AssetId = T::PoolAssetId;
+	type Balance = T::AssetBalance;
+
+	fn total_issuance(asset: T::PoolAssetId) -> T::AssetBalance {
+		T::PoolAssets::total_issuance(asset)
+	}
+
+	fn minimum_balance(asset: T::PoolAssetId) -> T::AssetBalance {
+		T::PoolAssets::minimum_balance(asset)
+	}
+
+	fn total_balance(asset: T::PoolAssetId, who: &T::AccountId) -> T::AssetBalance {
+		T::PoolAssets::total_balance(asset, who)
+	}
+
+	fn balance(asset: T::PoolAssetId, who: &T::AccountId) -> T::AssetBalance {
+		T::PoolAssets::balance(asset, who)
+	}
+
+	fn reducible_balance(
+		asset: T::PoolAssetId,
+		who: &T::AccountId,
+		preservation: Preservation,
+		force: Fortitude,
+	) -> T::AssetBalance {
+		T::PoolAssets::reducible_balance(asset, who, preservation, force)
+	}
+
+	fn can_deposit(
+		asset: T::PoolAssetId,
+		who: &T::AccountId,
+		amount: T::AssetBalance,
+		provenance: Provenance,
+	) -> DepositConsequence {
+		T::PoolAssets::can_deposit(asset, who, amount, provenance)
+	}
+
+	fn can_withdraw(
+		asset: T::PoolAssetId,
+		who: &T::AccountId,
+		amount: T::AssetBalance,
+	) -> WithdrawConsequence<T::AssetBalance> {
+		T::PoolAssets::can_withdraw(asset, who, amount)
+	}
+
+	fn asset_exists(asset: T::PoolAssetId) -> bool {
+		T::PoolAssets::asset_exists(asset)
+	}
+}
+
+/// The pallet keeps the freezes of the lp tokens, the `PoolAssets` are expected to enforce them,
+/// e.g. through `pallet_assets::FrozenBalanceOf`.
+impl<T: Config> fungibles::InspectFreeze<T::AccountId> for Pallet<T> {
+	type Id = FreezeReason;
+
+	fn balance_frozen(
+		asset: T::PoolAssetId,
+		id: &FreezeReason,
+		who: &T::AccountId,
+	) -> T::AssetBalance {
+		LpFreezes::<T>::get((asset, who, id))
+	}
+
+	fn can_freeze(_: T::PoolAssetId, _: &FreezeReason, _: &T::AccountId) -> bool {
+		true
+	}
+
+	fn balance_frozen_total(asset: T::PoolAssetId, who: &T::AccountId) -> T::AssetBalance {
+		LpFreezes::<T>::iter_prefix_values((asset, who))
+			.max()
+			.unwrap_or_else(Zero::zero)
+	}
+}
+
+impl<T: Config> fungibles::MutateFreeze<T::AccountId> for Pallet<T> {
+	fn set_freeze(
+		asset: T::PoolAssetId,
+		id: &FreezeReason,
+		who: &T::AccountId,
+		amount: T::AssetBalance,
+	) -> DispatchResult {
+		if amount.is_zero() {
+			return <Self as fungibles::MutateFreeze<T::AccountId>>::thaw(asset, id, who)
+		}
+		LpFreezes::<T>::insert((asset, who, id), amount);
+		Ok(())
+	}
+
+	fn extend_freeze(
+		asset: T::PoolAssetId,
+		id: &FreezeReason,
+		who: &T::AccountId,
+		amount: T::AssetBalance,
+	) -> DispatchResult {
+		if amount.is_zero() {
+			return Ok(())
+		}
+		LpFreezes::<T>::mutate((asset, who, id), |frozen| *frozen = (*frozen).max(amount));
+		Ok(())
+	}
+
+	fn thaw(asset: T::PoolAssetId, id: &FreezeReason, who: &T::AccountId) -> DispatchResult {
+		LpFreezes::<T>::remove((asset, who, id));
+		Ok(())
+	}
+}
+
 sp_api::decl_runtime_apis! {
 	/// This runtime api allows people to query the size of the liquidity pools
 	/// and quote prices for swaps.
@@ -1302,8 +4585,63 @@ sp_api::decl_runtime_apis! {
 		/// (Use `amount_out_min` to control slippage.)
 		fn quote_price_exact_tokens_for_tokens(asset1: AssetId, asset2: AssetId, amount: AssetBalance, include_fee: bool) -> Option<Balance>;
 
-		/// Returns the size of the liquidity pool for the given asset pair.
-		fn get_reserves(asset1: AssetId, asset2: AssetId) -> Option<(Balance, Balance)>;
+		/// Returns the size of the liquidity pool for the given asset pair in the `fee_tier`.
+		fn get_reserves(asset1: AssetId, asset2: AssetId, fee_tier: sp_runtime::Permill) -> Option<(Balance, Balance)>;
+
+		/// Returns the reserves of the pool with `pool_id`, in pool order, as of the block the
+		/// api is called at. On an archive node this gives the reserves at any past block.
+		///
+		/// See [`Pallet::pool_reserves`].
+		#[api_version(2)]
+		fn reserves_at(pool_id: (AssetId, AssetId, sp_runtime::Permill)) -> Option<(Balance, Balance)>;
+
+		/// Returns the current price of `asset1` in `asset2` in the pool of the `fee_tier` as a
+		/// `(numerator, denominator)` rational.
+		///
+		/// See [`Pallet::spot_price`].
+		#[api_version(2)]
+		fn spot_price(asset1: AssetId, asset2: AssetId, fee_tier: sp_runtime::Permill) -> Option<(AssetBalance, AssetBalance)>;
+
+		/// Returns the expected output of [`Pallet::swap_exact_tokens_for_tokens`] along with the
+		/// minimum output tolerating `slippage`.
+		///
+		/// See [`Pallet::quote_with_slippage`].
+		#[api_version(2)]
+		fn quote_with_slippage(
+			asset1: AssetId,
+			asset2: AssetId,
+			amount_in: AssetBalance,
+			slippage: sp_runtime::Permill,
+		) -> Option<(AssetBalance, AssetBalance)>;
+	}
+
+	/// This runtime api allows people to enumerate the liquidity pools and value the positions
+	/// held in them.
+	pub trait AssetConversionPoolsApi<AssetBalance, AssetId, AccountId, PoolAssetId> where
+		AssetBalance: frame_support::traits::tokens::Balance,
+		AssetId: Codec,
+		AccountId: Codec,
+		PoolAssetId: Codec
+	{
+		/// Returns up to `limit` pools, starting after the raw storage key `start_key`.
+		///
+		/// See [`Pallet::list_pools`] for how to page through all the pools.
+		fn list_pools(
+			start_key: Option<Vec<u8>>,
+			limit: u32,
+		) -> Vec<((AssetId, AssetId, sp_runtime::Permill), PoolInfoSummary<AccountId, PoolAssetId, AssetBalance>)>;
+
+		/// Checks the solvency and sums up the value locked of up to `limit` pools, starting after
+		/// the raw storage key `start_key`.
+		///
+		/// See [`Pallet::dex_health`].
+		fn dex_health(start_key: Option<Vec<u8>>, limit: u32) -> DexHealth<(AssetId, AssetId, sp_runtime::Permill), AssetBalance>;
+
+		/// Returns the value of the liquidity `who` provided to the pool with `pool_id` if held
+		/// against if withdrawn now, in the native asset.
+		///
+		/// See [`Pallet::lp_pnl`].
+		fn lp_pnl(who: AccountId, pool_id: (AssetId, AssetId, sp_runtime::Permill)) -> Option<LpPnl<AssetBalance>>;
 	}
 }
 
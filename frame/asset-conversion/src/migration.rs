@@ -0,0 +1,500 @@
+// This file is part of Substrate.
+
+// Copyright (C) 2023 Parity Technologies (UK) Ltd.
+// SPDX-License-Identifier: Apache-2.0
+
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// 	http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage migrations for the asset conversion pallet.
+
+use super::*;
+use frame_support::{pallet_prelude::*, traits::OnRuntimeUpgrade, weights::Weight};
+use frame_system::pallet_prelude::BlockNumberFor;
+use sp_runtime::Saturating;
+
+#[cfg(feature = "try-runtime")]
+use sp_runtime::TryRuntimeError;
+
+/// Migrations to storage version 1, which adds [`PoolInfo::last_swap_block`].
+pub mod v1 {
+	use super::*;
+
+	/// The [`PoolInfo`] of storage version 0.
+	#[derive(Decode)]
+	pub struct OldPoolInfo<PoolAssetId> {
+		/// Liquidity pool asset
+		pub lp_token: PoolAssetId,
+	}
+
+	/// The [`PoolInfo`] of storage version 1.
+	#[derive(Decode, Encode, MaxEncodedLen, TypeInfo)]
+	pub struct PoolInfoV1<PoolAssetId, BlockNumber> {
+		/// Liquidity pool asset
+		pub lp_token: PoolAssetId,
+		/// The block of the last swap through the pool, or of its creation if there was none yet.
+		pub last_swap_block: BlockNumber,
+	}
+
+	/// The [`Pools`] of storage version 1.
+	#[frame_support::storage_alias]
+	pub(crate) type Pools<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		v4::PoolIdV3Of<T>,
+		PoolInfoV1<<T as Config>::PoolAssetId, BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// Translates every pool to the [`PoolInfoV1`], taking the block of the upgrade as the block
+	/// of the last swap.
+	pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let onchain_version = Pallet::<T>::on_chain_storage_version();
+
+			if onchain_version == 0 {
+				let now = frame_system::Pallet::<T>::block_number();
+				let mut translated = 0u64;
+				Pools::<T>::translate::<OldPoolInfo<T::PoolAssetId>, _>(|_, old| {
+					translated.saturating_inc();
+					Some(PoolInfoV1 { lp_token: old.lp_token, last_swap_block: now })
+				});
+
+				StorageVersion::new(1).put::<Pallet<T>>();
+				T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+			} else {
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let onchain_version = Pallet::<T>::on_chain_storage_version();
+			ensure!(onchain_version == 0, "migration from version 0 to 1.");
+			let prev_count = Pools::<T>::iter_keys().count();
+			Ok((prev_count as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(prev_count: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let prev_count: u32 = Decode::decode(&mut prev_count.as_slice()).expect(
+				"the state parameter should be something that was generated by pre_upgrade",
+			);
+			let post_count = Pools::<T>::iter().count() as u32;
+			ensure!(
+				prev_count == post_count,
+				"the records count before and after the migration should be the same"
+			);
+			ensure!(Pallet::<T>::on_chain_storage_version() == 1, "wrong storage version");
+
+			Ok(())
+		}
+	}
+}
+
+/// Migrations to storage version 2, which adds [`PoolInfo::created_at`].
+pub mod v2 {
+	use super::*;
+	use v4::Pools;
+
+	/// Translates every pool to the [`v4::PoolInfoV3`]. The creation block of the existing pools
+	/// isn't known, they are all taken to be created at block `0`.
+	pub struct MigrateToV2<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let onchain_version = Pallet::<T>::on_chain_storage_version();
+
+			if onchain_version == 1 {
+				let mut translated = 0u64;
+				Pools::<T>::translate::<v1::PoolInfoV1<T::PoolAssetId, BlockNumberFor<T>>, _>(
+					|_, old| {
+						translated.saturating_inc();
+						Some(v4::PoolInfoV3 {
+							lp_token: old.lp_token,
+							last_swap_block: old.last_swap_block,
+							created_at: Zero::zero(),
+						})
+					},
+				);
+
+				StorageVersion::new(2).put::<Pallet<T>>();
+				T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+			} else {
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let onchain_version = Pallet::<T>::on_chain_storage_version();
+			ensure!(onchain_version == 1, "migration from version 1 to 2.");
+			let prev_count = Pools::<T>::iter_keys().count();
+			Ok((prev_count as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(prev_count: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let prev_count: u32 = Decode::decode(&mut prev_count.as_slice()).expect(
+				"the state parameter should be something that was generated by pre_upgrade",
+			);
+			let post_count = Pools::<T>::iter().count() as u32;
+			ensure!(
+				prev_count == post_count,
+				"the records count before and after the migration should be the same"
+			);
+			ensure!(Pallet::<T>::on_chain_storage_version() == 2, "wrong storage version");
+
+			Ok(())
+		}
+	}
+}
+
+/// Migrations to storage version 3, which fills [`LpTokenToPool`] for the existing pools.
+pub mod v3 {
+	use super::*;
+	use v4::{LpTokenToPool, Pools};
+
+	/// Maps the lp token of every pool back to its pool id.
+	pub struct MigrateToV3<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			let onchain_version = Pallet::<T>::on_chain_storage_version();
+
+			if onchain_version == 2 {
+				let mut indexed = 0u64;
+				for (pool_id, pool) in Pools::<T>::iter() {
+					indexed.saturating_inc();
+					LpTokenToPool::<T>::insert(pool.lp_token, pool_id);
+				}
+
+				StorageVersion::new(3).put::<Pallet<T>>();
+				T::DbWeight::get().reads_writes(indexed + 1, indexed + 1)
+			} else {
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let onchain_version = Pallet::<T>::on_chain_storage_version();
+			ensure!(onchain_version == 2, "migration from version 2 to 3.");
+			Ok(Vec::new())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_: Vec<u8>) -> Result<(), TryRuntimeError> {
+			for (pool_id, pool) in Pools::<T>::iter() {
+				ensure!(
+					LpTokenToPool::<T>::get(pool.lp_token) == Some(pool_id),
+					"every lp token should map back to its pool"
+				);
+			}
+			ensure!(Pallet::<T>::on_chain_storage_version() == 3, "wrong storage version");
+
+			Ok(())
+		}
+	}
+}
+
+/// Migrations to storage version 4, which adds the fee tier to the pool ids and
+/// [`PoolInfo::fee_tier`].
+pub mod v4 {
+	use super::*;
+	use frame_support::traits::{
+		fungible::{Inspect as InspectFungible, Mutate as MutateFungible},
+		fungibles::{Inspect, Mutate},
+		tokens::{Fortitude::Polite, Preservation::Expendable},
+	};
+	use sp_runtime::Permill;
+
+	/// The pool id of storage versions 0 to 3, which doesn't have a fee tier.
+	pub type PoolIdV3Of<T> = (<T as Config>::MultiAssetId, <T as Config>::MultiAssetId);
+
+	/// The [`PoolInfo`] of storage versions 2 and 3.
+	#[derive(Decode, Encode, MaxEncodedLen, TypeInfo)]
+	pub struct PoolInfoV3<PoolAssetId, BlockNumber> {
+		/// Liquidity pool asset
+		pub lp_token: PoolAssetId,
+		/// The block of the last swap through the pool, or of its creation if there was none yet.
+		pub last_swap_block: BlockNumber,
+		/// The block the pool was created at.
+		pub created_at: BlockNumber,
+	}
+
+	/// The [`Pools`] of storage versions 2 and 3.
+	#[frame_support::storage_alias]
+	pub(crate) type Pools<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		PoolIdV3Of<T>,
+		PoolInfoV3<<T as Config>::PoolAssetId, BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// The [`LpTokenToPool`] of storage version 3.
+	#[frame_support::storage_alias]
+	pub(crate) type LpTokenToPool<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		<T as Config>::PoolAssetId,
+		PoolIdV3Of<T>,
+		OptionQuery,
+	>;
+
+	/// The [`ClosedPools`] of storage version 3.
+	#[frame_support::storage_alias]
+	type ClosedPools<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		PoolIdV3Of<T>,
+		<T as Config>::PoolAssetId,
+		OptionQuery,
+	>;
+
+	/// The [`ForceCloseCursor`] of storage version 3.
+	#[frame_support::storage_alias]
+	type ForceCloseCursor<T: Config> =
+		StorageValue<Pallet<T>, (<T as Config>::MultiAssetId, PoolIdV3Of<T>), OptionQuery>;
+
+	/// The [`PoolStats`] of storage version 3.
+	#[frame_support::storage_alias]
+	type PoolStats<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		PoolIdV3Of<T>,
+		PoolStatistics<<T as Config>::AssetBalance>,
+		OptionQuery,
+	>;
+
+	/// The [`AutoCompoundCursor`] of storage version 3.
+	#[frame_support::storage_alias]
+	type AutoCompoundCursor<T: Config> = StorageValue<Pallet<T>, PoolIdV3Of<T>, OptionQuery>;
+
+	/// The [`CompoundStates`] of storage version 3.
+	#[frame_support::storage_alias]
+	type CompoundStates<T: Config> = StorageMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		PoolIdV3Of<T>,
+		CompoundState<BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// The [`LpPositions`] of storage version 3.
+	#[frame_support::storage_alias]
+	type LpPositions<T: Config> = StorageDoubleMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::AccountId,
+		Blake2_128Concat,
+		PoolIdV3Of<T>,
+		LpPosition<<T as Config>::AssetBalance>,
+		OptionQuery,
+	>;
+
+	/// The [`LockedPositions`] of storage version 3.
+	#[frame_support::storage_alias]
+	type LockedPositions<T: Config> = StorageDoubleMap<
+		Pallet<T>,
+		Blake2_128Concat,
+		<T as frame_system::Config>::AccountId,
+		Blake2_128Concat,
+		PoolIdV3Of<T>,
+		LockedPosition<<T as Config>::AssetBalance, BlockNumberFor<T>>,
+		OptionQuery,
+	>;
+
+	/// The [`FlashLoanLock`] of storage version 3.
+	#[frame_support::storage_alias]
+	type FlashLoanLock<T: Config> = StorageValue<Pallet<T>, PoolIdV3Of<T>, OptionQuery>;
+
+	/// Puts every existing pool in the fee tier of the `LPFee`, re-keying everything stored by
+	/// pool id, and moves the funds of the pools, open or closed, to their new accounts.
+	///
+	/// `OldAccountDerivation` is the `AccountDerivation` of the runtime before the upgrade, which
+	/// derived the pool accounts from pool ids without a fee tier. Whatever can't be moved out of
+	/// an old pool account, like the reserved metadata deposit of the lp token, stays with it.
+	pub struct MigrateToV4<T, OldAccountDerivation>(
+		sp_std::marker::PhantomData<(T, OldAccountDerivation)>,
+	);
+
+	impl<T, OldAccountDerivation> MigrateToV4<T, OldAccountDerivation>
+	where
+		T: Config,
+		OldAccountDerivation: PoolAccountDerivation<T::AccountId, PoolIdV3Of<T>>,
+	{
+		/// The id of the pool with the `old` id.
+		fn pool_id((asset1, asset2): PoolIdV3Of<T>) -> PoolIdOf<T> {
+			(asset1, asset2, Permill::from_perthousand(T::LPFee::get()))
+		}
+
+		/// Moves the balances of the account of the pool with the `old` id to the new one.
+		fn move_pool_funds(old: &PoolIdV3Of<T>, lp_token: &T::PoolAssetId) -> DispatchResult {
+			let from = OldAccountDerivation::pool_account(old);
+			let to = Pallet::<T>::get_pool_account(&Self::pool_id(old.clone()));
+			if from == to {
+				return Ok(())
+			}
+			frame_system::Pallet::<T>::inc_providers(&to);
+
+			let amount = T::Currency::reducible_balance(&from, Expendable, Polite);
+			if !amount.is_zero() {
+				T::Currency::transfer(&from, &to, amount, Expendable)?;
+			}
+			for asset in [&old.0, &old.1] {
+				if let MultiAssetIdConversionResult::Converted(asset) =
+					T::MultiAssetIdConverter::try_convert(asset)
+				{
+					let amount =
+						T::Assets::reducible_balance(asset.clone(), &from, Expendable, Polite);
+					if !amount.is_zero() {
+						T::Assets::transfer(asset, &from, &to, amount, Expendable)?;
+					}
+				}
+			}
+			let amount =
+				T::PoolAssets::reducible_balance(lp_token.clone(), &from, Expendable, Polite);
+			if !amount.is_zero() {
+				T::PoolAssets::transfer(lp_token.clone(), &from, &to, amount, Expendable)?;
+			}
+			Ok(())
+		}
+	}
+
+	impl<T, OldAccountDerivation> OnRuntimeUpgrade for MigrateToV4<T, OldAccountDerivation>
+	where
+		T: Config,
+		OldAccountDerivation: PoolAccountDerivation<T::AccountId, PoolIdV3Of<T>>,
+	{
+		fn on_runtime_upgrade() -> Weight {
+			let onchain_version = Pallet::<T>::on_chain_storage_version();
+			if onchain_version != 3 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let fee_tier = Permill::from_perthousand(T::LPFee::get());
+			// every read entry is written twice, once removed and once inserted.
+			let mut entries = 0u64;
+			// the pool accounts, each with up to 4 balances to move.
+			let mut accounts = 0u64;
+
+			// the old and new keys share the prefix of each map, so the old entries are all taken
+			// out before the new ones are inserted.
+			let pools: Vec<_> = Pools::<T>::drain().collect();
+			for (old_id, old) in pools {
+				entries.saturating_accrue(2);
+				accounts.saturating_inc();
+				if let Err(error) = Self::move_pool_funds(&old_id, &old.lp_token) {
+					frame_support::defensive!("the funds of a pool could not be moved", error);
+				}
+				let pool_id = Self::pool_id(old_id);
+				crate::LpTokenToPool::<T>::insert(old.lp_token.clone(), pool_id.clone());
+				crate::Pools::<T>::insert(
+					pool_id,
+					PoolInfo {
+						lp_token: old.lp_token,
+						last_swap_block: old.last_swap_block,
+						created_at: old.created_at,
+						fee_tier,
+					},
+				);
+			}
+			let closed_pools: Vec<_> = ClosedPools::<T>::drain().collect();
+			for (old_id, lp_token) in closed_pools {
+				entries.saturating_inc();
+				accounts.saturating_inc();
+				if let Err(error) = Self::move_pool_funds(&old_id, &lp_token) {
+					frame_support::defensive!(
+						"the funds of a closed pool could not be moved",
+						error
+					);
+				}
+				crate::ClosedPools::<T>::insert(Self::pool_id(old_id), lp_token);
+			}
+			let stats: Vec<_> = PoolStats::<T>::drain().collect();
+			for (old_id, pool_stats) in stats {
+				entries.saturating_inc();
+				crate::PoolStats::<T>::insert(Self::pool_id(old_id), pool_stats);
+			}
+			let compound_states: Vec<_> = CompoundStates::<T>::drain().collect();
+			for (old_id, state) in compound_states {
+				entries.saturating_inc();
+				crate::CompoundStates::<T>::insert(Self::pool_id(old_id), state);
+			}
+			let positions: Vec<_> = LpPositions::<T>::drain().collect();
+			for (who, old_id, position) in positions {
+				entries.saturating_inc();
+				crate::LpPositions::<T>::insert(who, Self::pool_id(old_id), position);
+			}
+			let locked_positions: Vec<_> = LockedPositions::<T>::drain().collect();
+			for (who, old_id, position) in locked_positions {
+				entries.saturating_inc();
+				crate::LockedPositions::<T>::insert(who, Self::pool_id(old_id), position);
+			}
+			if let Some((asset, old_id)) = ForceCloseCursor::<T>::take() {
+				crate::ForceCloseCursor::<T>::put((asset, Self::pool_id(old_id)));
+			}
+			if let Some(old_id) = AutoCompoundCursor::<T>::take() {
+				crate::AutoCompoundCursor::<T>::put(Self::pool_id(old_id));
+			}
+			if let Some(old_id) = FlashLoanLock::<T>::take() {
+				crate::FlashLoanLock::<T>::put(Self::pool_id(old_id));
+			}
+
+			StorageVersion::new(4).put::<Pallet<T>>();
+			// the 3 storage values and the storage version.
+			let values = 4;
+			// the balances, accounts, assets and provider of each pool account.
+			let balances = accounts.saturating_mul(8);
+			T::DbWeight::get().reads_writes(
+				entries.saturating_add(balances).saturating_add(values),
+				entries.saturating_mul(2).saturating_add(balances).saturating_add(values),
+			)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			let onchain_version = Pallet::<T>::on_chain_storage_version();
+			ensure!(onchain_version == 3, "migration from version 3 to 4.");
+			let prev_count = Pools::<T>::iter_keys().count();
+			Ok((prev_count as u32).encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(prev_count: Vec<u8>) -> Result<(), TryRuntimeError> {
+			let prev_count: u32 = Decode::decode(&mut prev_count.as_slice()).expect(
+				"the state parameter should be something that was generated by pre_upgrade",
+			);
+			let fee_tier = Permill::from_perthousand(T::LPFee::get());
+			let mut post_count = 0u32;
+			for (pool_id, pool) in crate::Pools::<T>::iter() {
+				post_count.saturating_inc();
+				ensure!(
+					pool_id.2 == fee_tier && pool.fee_tier == fee_tier,
+					"every pool should be in the fee tier of the `LPFee`"
+				);
+				ensure!(
+					crate::LpTokenToPool::<T>::get(pool.lp_token) == Some(pool_id),
+					"every lp token should map back to its pool"
+				);
+			}
+			ensure!(
+				prev_count == post_count,
+				"the records count before and after the migration should be the same"
+			);
+			ensure!(Pallet::<T>::on_chain_storage_version() == 4, "wrong storage version");
+
+			Ok(())
+		}
+	}
+}
@@ -20,19 +20,26 @@
 use super::*;
 use crate as pallet_asset_conversion;
 
+use codec::{Decode, Encode};
 use frame_support::{
 	construct_runtime,
 	instances::{Instance1, Instance2},
 	ord_parameter_types, parameter_types,
-	traits::{AsEnsureOriginWithArg, ConstU128, ConstU32, ConstU64},
+	traits::{
+		fungible, fungibles, tokens::Preservation, AsEnsureOriginWithArg, ConstU128, ConstU32,
+		ConstU64, ConstU8,
+	},
+	weights::Weight,
 	PalletId,
 };
 use frame_system::{EnsureSigned, EnsureSignedBy};
+use pallet_assets::{FrozenBalance, FrozenBalanceOf};
 use sp_arithmetic::Permill;
 use sp_core::H256;
 use sp_runtime::{
-	traits::{AccountIdConversion, BlakeTwo256, IdentityLookup},
-	BuildStorage,
+	bounded_vec,
+	traits::{AccountIdConversion, BlakeTwo256, IdentifyAccount, IdentityLookup, Lazy, Verify},
+	BoundedVec, BuildStorage, DispatchResult, RuntimeDebug,
 };
 
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -85,6 +92,7 @@ impl pallet_balances::Config for Test {
 	type MaxReserves = ConstU32<50>;
 	type ReserveIdentifier = [u8; 8];
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 	type RuntimeHoldReason = ();
 	type MaxHolds = ();
@@ -114,6 +122,27 @@ impl pallet_assets::Config<Instance1> for Test {
 	}
 }
 
+parameter_types! {
+	pub storage LpMetadataDepositBase: u128 = 0;
+	pub storage EnforceLpFreezes: bool = true;
+}
+
+/// Enforces the lp freezes of the pallet unless `EnforceLpFreezes` is turned off.
+pub struct MockLpFreezer;
+impl FrozenBalance<u32, u128, u128> for MockLpFreezer {
+	fn frozen_balance(asset: u32, who: &u128) -> Option<u128> {
+		if EnforceLpFreezes::get() {
+			FrozenBalanceOf::<AssetConversion>::frozen_balance(asset, who)
+		} else {
+			None
+		}
+	}
+
+	fn died(asset: u32, who: &u128) {
+		FrozenBalanceOf::<AssetConversion>::died(asset, who)
+	}
+}
+
 impl pallet_assets::Config<Instance2> for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type Balance = u128;
@@ -126,11 +155,11 @@ impl pallet_assets::Config<Instance2> for Test {
 	type ForceOrigin = frame_system::EnsureRoot<Self::AccountId>;
 	type AssetDeposit = ConstU128<0>;
 	type AssetAccountDeposit = ConstU128<0>;
-	type MetadataDepositBase = ConstU128<0>;
+	type MetadataDepositBase = LpMetadataDepositBase;
 	type MetadataDepositPerByte = ConstU128<0>;
 	type ApprovalDeposit = ConstU128<0>;
 	type StringLimit = ConstU32<50>;
-	type Freezer = ();
+	type Freezer = MockLpFreezer;
 	type Extra = ();
 	type WeightInfo = ();
 	type CallbackHandle = ();
@@ -142,9 +171,194 @@ impl pallet_assets::Config<Instance2> for Test {
 parameter_types! {
 	pub const AssetConversionPalletId: PalletId = PalletId(*b"py/ascon");
 	pub storage AllowMultiAssetPools: bool = true;
+	pub storage MaxReserve: u128 = u128::MAX;
+	pub storage FeeTiers: BoundedVec<Permill, ConstU32<3>> = bounded_vec![
+		Permill::from_parts(500),
+		Permill::from_parts(3_000),
+		Permill::from_percent(1),
+	];
+	pub storage LPFee: u32 = 3; // means 0.3%
+	pub const ProtocolTreasury: u128 = 1000;
+	pub storage FlashFee: Permill = Permill::from_percent(1);
+	pub storage MaxPriceMovePerSwap: Option<Permill> = None;
+	pub storage ImpactAlertThreshold: Permill = Permill::one(); // never alert
+	pub storage SwapCooldown: Option<u64> = None;
+	pub storage MaxLpMintPerTx: Option<u128> = None;
+	pub storage MaxLpRatioImbalance: Option<Permill> = None;
+	pub storage SwapNetworkFee: u128 = 0;
+	pub storage NetworkFeeReceiver: Option<u128> = None;
+	pub const AutoCompoundPeriod: u64 = 10;
+	pub storage AutoCompoundMaxSlippage: Permill = Permill::from_percent(2);
+	pub storage AutoCompoundTreasury: Option<u128> = None;
+	pub storage AllowSingleSidedDust: bool = false;
+	pub storage SwapKeepAlivePolicy: KeepAlivePolicy = KeepAlivePolicy::NativeOnly;
+	pub storage EventVerbosity: EventLevel = EventLevel::Full;
 	pub storage LiquidityWithdrawalFee: Permill = Permill::from_percent(0); // should be non-zero if AllowMultiAssetPools is true, otherwise can be zero
 }
 
+/// Denies lp tokens to account `666`, allows everyone else.
+pub struct LpTransferPolicy;
+impl frame_support::traits::Contains<u128> for LpTransferPolicy {
+	fn contains(who: &u128) -> bool {
+		*who != 666
+	}
+}
+
+/// Denies asset `666`, allows every other one.
+pub struct AssetAllowList;
+impl frame_support::traits::Contains<u32> for AssetAllowList {
+	fn contains(asset: &u32) -> bool {
+		*asset != 666
+	}
+}
+
+parameter_types! {
+	pub storage LpBalanceChanges: Vec<(u128, (NativeOrAssetId<u32>, NativeOrAssetId<u32>, Permill), BalanceDelta<u128>)> = vec![];
+}
+
+/// Records every lp balance change in `LpBalanceChanges`.
+pub struct MockLpTracker;
+impl OnLpBalanceChange<u128, (NativeOrAssetId<u32>, NativeOrAssetId<u32>, Permill), u128>
+	for MockLpTracker
+{
+	fn on_lp_balance_change(
+		who: &u128,
+		pool_id: &(NativeOrAssetId<u32>, NativeOrAssetId<u32>, Permill),
+		delta: BalanceDelta<u128>,
+	) {
+		let mut changes = LpBalanceChanges::get();
+		changes.push((*who, *pool_id, delta));
+		LpBalanceChanges::set(&changes);
+	}
+}
+
+parameter_types! {
+	pub storage ReserveChanges: Vec<((NativeOrAssetId<u32>, NativeOrAssetId<u32>, Permill), u128, u128)> = vec![];
+}
+
+/// Records the reserves of every reserve change in `ReserveChanges`.
+pub struct MockReserveMirror;
+impl OnReserveChange<(NativeOrAssetId<u32>, NativeOrAssetId<u32>, Permill), u128>
+	for MockReserveMirror
+{
+	fn on_reserve_change(
+		pool_id: &(NativeOrAssetId<u32>, NativeOrAssetId<u32>, Permill),
+		reserve1: u128,
+		reserve2: u128,
+	) {
+		let mut changes = ReserveChanges::get();
+		changes.push((*pool_id, reserve1, reserve2));
+		ReserveChanges::set(&changes);
+	}
+
+	fn weight() -> Weight {
+		Weight::zero()
+	}
+}
+
+/// The signer of a `MockSignature`, identifying as the account it wraps.
+pub struct MockSigner(pub u128);
+impl IdentifyAccount for MockSigner {
+	type AccountId = u128;
+
+	fn into_account(self) -> u128 {
+		self.0
+	}
+}
+
+/// A signature of the account `.0` over the message `.1`.
+#[derive(Clone, PartialEq, Eq, Encode, Decode, RuntimeDebug, scale_info::TypeInfo)]
+pub struct MockSignature(pub u128, pub Vec<u8>);
+impl Verify for MockSignature {
+	type Signer = MockSigner;
+
+	fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &u128) -> bool {
+		signer == &self.0 && msg.get() == &self.1[..]
+	}
+}
+
+/// Signs permits with `MockSignature`s in the benchmarks.
+#[cfg(feature = "runtime-benchmarks")]
+pub struct MockBenchmarkHelper;
+#[cfg(feature = "runtime-benchmarks")]
+impl BenchmarkHelper<u32, NativeOrAssetId<u32>, MockSigner, u128, MockSignature>
+	for MockBenchmarkHelper
+{
+	fn asset_id(asset_id: u32) -> u32 {
+		asset_id
+	}
+
+	fn multiasset_id(asset_id: u32) -> NativeOrAssetId<u32> {
+		NativeOrAssetId::Asset(asset_id)
+	}
+
+	fn signer() -> (MockSigner, u128) {
+		(MockSigner(7), 7)
+	}
+
+	fn sign(signer: &MockSigner, message: &[u8]) -> MockSignature {
+		MockSignature(signer.0, message.to_vec())
+	}
+}
+
+/// `MockFlashBorrower` repays the loan together with its fee.
+pub const FLASH_REPAY: u8 = 0;
+/// `MockFlashBorrower` repays the loan but not its fee.
+pub const FLASH_REPAY_WITHOUT_FEE: u8 = 1;
+/// `MockFlashBorrower` keeps the loan.
+pub const FLASH_KEEP: u8 = 2;
+/// `MockFlashBorrower` sells the loan for native in the pool it was borrowed from.
+pub const FLASH_SWAP_IN_POOL: u8 = 3;
+
+/// Flash borrower acting according to the first byte of the callback data, see the `FLASH_*`
+/// constants.
+pub struct MockFlashBorrower;
+impl FlashBorrower<u128, NativeOrAssetId<u32>, u128> for MockFlashBorrower {
+	fn on_flash_loan(
+		borrower: &u128,
+		pool_account: &u128,
+		asset: &NativeOrAssetId<u32>,
+		amount: u128,
+		fee: u128,
+		data: &[u8],
+	) -> DispatchResult {
+		let repay = |amount| match asset {
+			NativeOrAssetId::Native => <Balances as fungible::Mutate<u128>>::transfer(
+				borrower,
+				pool_account,
+				amount,
+				Preservation::Expendable,
+			),
+			NativeOrAssetId::Asset(id) => <Assets as fungibles::Mutate<u128>>::transfer(
+				*id,
+				borrower,
+				pool_account,
+				amount,
+				Preservation::Expendable,
+			),
+			#[cfg(feature = "nft-pools")]
+			NativeOrAssetId::Class(..) => Err(sp_runtime::TokenError::Unsupported.into()),
+		};
+		match data.first().copied().unwrap_or(FLASH_REPAY) {
+			FLASH_REPAY => repay(amount + fee).map(|_| ()),
+			FLASH_REPAY_WITHOUT_FEE => repay(amount).map(|_| ()),
+			FLASH_SWAP_IN_POOL => AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(*borrower),
+				vec![*asset, NativeOrAssetId::Native].try_into().unwrap(),
+				amount,
+				1,
+				*borrower,
+				false,
+			),
+			_ => Ok(()),
+		}
+	}
+
+	fn weight(_data: &[u8]) -> Weight {
+		Weight::zero()
+	}
+}
+
 ord_parameter_types! {
 	pub const AssetConversionOrigin: u128 = AccountIdConversion::<u128>::into_account_truncating(&AssetConversionPalletId::get());
 }
@@ -158,14 +372,47 @@ impl Config for Test {
 	type Assets = Assets;
 	type PoolAssets = PoolAssets;
 	type PalletId = AssetConversionPalletId;
+	type OffchainSignature = MockSignature;
+	type OffchainPublic = MockSigner;
 	type WeightInfo = ();
-	type LPFee = ConstU32<3>; // means 0.3%
+	type FeeTiers = FeeTiers;
+	type MaxFeeTiers = ConstU32<3>;
+	type LPFee = LPFee;
 	type PoolSetupFee = ConstU128<100>; // should be more or equal to the existential deposit
 	type PoolSetupFeeReceiver = AssetConversionOrigin;
+	type SwapNetworkFee = SwapNetworkFee;
+	type NetworkFeeReceiver = NetworkFeeReceiver;
 	type LiquidityWithdrawalFee = LiquidityWithdrawalFee;
+	type AllowSingleSidedDust = AllowSingleSidedDust;
+	type SwapKeepAlivePolicy = SwapKeepAlivePolicy;
+	type EventVerbosity = EventVerbosity;
 	type AllowMultiAssetPools = AllowMultiAssetPools;
 	type MaxSwapPathLength = ConstU32<4>;
 	type MintMinLiquidity = ConstU128<100>; // 100 is good enough when the main currency has 12 decimals.
+	type LpTransferPolicy = LpTransferPolicy;
+	type AssetAllowList = AssetAllowList;
+	type MaxReserve = MaxReserve;
+	type AccountDerivation = HashedPoolAccount;
+	type GovernanceOrigin = frame_system::EnsureRoot<u128>;
+	type ProtocolTreasury = ProtocolTreasury;
+	type LpTokenDecimals = ConstU8<12>;
+	type OnLpBalanceChange = MockLpTracker;
+	type OnReserveChange = MockReserveMirror;
+	type FlashBorrower = MockFlashBorrower;
+	type FlashFee = FlashFee;
+	type MaxPriceMovePerSwap = MaxPriceMovePerSwap;
+	type ImpactAlertThreshold = ImpactAlertThreshold;
+	type QuoteCacheSize = ConstU32<4>;
+	type MaxLockDuration = ConstU64<100>;
+	type SwapCooldown = SwapCooldown;
+	type AutoCompoundTreasury = AutoCompoundTreasury;
+	type AutoCompoundPeriod = AutoCompoundPeriod;
+	type AutoCompoundMaxSlippage = AutoCompoundMaxSlippage;
+	#[cfg(feature = "nft-pools")]
+	type NftHandler = ();
+	type MaxLpMintPerTx = MaxLpMintPerTx;
+	type MaxLpRatioImbalance = MaxLpRatioImbalance;
+	type MaxPoolsPerBatch = ConstU32<5>;
 
 	type Balance = u128;
 	type HigherPrecisionBalance = sp_core::U256;
@@ -174,7 +421,7 @@ impl Config for Test {
 	type MultiAssetIdConverter = NativeOrAssetIdConverter<u32>;
 
 	#[cfg(feature = "runtime-benchmarks")]
-	type BenchmarkHelper = ();
+	type BenchmarkHelper = MockBenchmarkHelper;
 }
 
 pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
@@ -16,13 +16,18 @@
 // limitations under the License.
 
 use crate::{mock::*, *};
+use codec::Encode;
 use frame_support::{
 	assert_noop, assert_ok,
 	instances::Instance1,
 	traits::{fungible::Inspect, fungibles::InspectEnumerable, Get},
 };
-use sp_arithmetic::Permill;
-use sp_runtime::{DispatchError, TokenError};
+use sp_arithmetic::{traits::One, FixedU128, Permill, Rounding};
+use sp_core::U256;
+use sp_runtime::{bounded_vec, DispatchError, TokenError};
+
+/// The fee tier of most pools of the tests, the one of the `LPFee`.
+const FEE_TIER: Permill = Permill::from_parts(3_000);
 
 fn events() -> Vec<Event<Test>> {
 	let result = System::events()
@@ -66,11 +71,7 @@ fn pool_assets() -> Vec<u32> {
 
 fn create_tokens(owner: u128, tokens: Vec<NativeOrAssetId<u32>>) {
 	for token_id in tokens {
-		let MultiAssetIdConversionResult::Converted(asset_id) =
-			NativeOrAssetIdConverter::try_convert(&token_id)
-		else {
-			unreachable!("invalid token")
-		};
+		let Some(asset_id) = token_id.as_asset() else { unreachable!("invalid token") };
 		assert_ok!(Assets::force_create(RuntimeOrigin::root(), asset_id, owner, false, 1));
 	}
 }
@@ -79,6 +80,8 @@ fn balance(owner: u128, token_id: NativeOrAssetId<u32>) -> u128 {
 	match token_id {
 		NativeOrAssetId::Native => <<Test as Config>::Currency>::free_balance(owner),
 		NativeOrAssetId::Asset(token_id) => <<Test as Config>::Assets>::balance(token_id, owner),
+		#[cfg(feature = "nft-pools")]
+		NativeOrAssetId::Class(..) => 0,
 	}
 }
 
@@ -90,6 +93,34 @@ fn get_ed() -> u128 {
 	<<Test as Config>::Currency>::minimum_balance()
 }
 
+/// Every `(reserve_in, reserve_out, amount)` made of amounts spread over many magnitudes,
+/// including primes and the largest balances, for the tests of the amount helpers.
+fn sample_swaps() -> impl Iterator<Item = (u128, u128, u128)> {
+	const AMOUNTS: [u128; 16] = [
+		1,
+		2,
+		3,
+		7,
+		10,
+		997,
+		1_000,
+		65_537,
+		1_000_000,
+		999_999_937,
+		1_000_000_000_000,
+		u32::MAX as u128,
+		1_000_000_000_000_003,
+		u64::MAX as u128 / 3,
+		u64::MAX as u128 / 2,
+		u64::MAX as u128,
+	];
+	AMOUNTS.into_iter().flat_map(|reserve_in| {
+		AMOUNTS.into_iter().flat_map(move |reserve_out| {
+			AMOUNTS.into_iter().map(move |amount| (reserve_in, reserve_out, amount))
+		})
+	})
+}
+
 macro_rules! bvec {
 	($( $x:tt )*) => {
 		vec![$( $x )*].try_into().unwrap()
@@ -105,6 +136,7 @@ fn check_pool_accounts_dont_collide() {
 		let account = AssetConversion::get_pool_account(&(
 			NativeOrAssetId::Native,
 			NativeOrAssetId::Asset(i),
+			FEE_TIER,
 		));
 		if map.contains(&account) {
 			panic!("Collision at {}", i);
@@ -113,6 +145,37 @@ fn check_pool_accounts_dont_collide() {
 	}
 }
 
+#[test]
+fn pool_account_derivation_schemes_work() {
+	use sp_runtime::traits::AccountIdConversion;
+	use NativeOrAssetId::{Asset, Native};
+
+	let hashed = |pool_id: &PoolIdOf<Test>| -> u128 {
+		<HashedPoolAccount as PoolAccountDerivation<_, _>>::pool_account(pool_id)
+	};
+	let truncated = |pool_id: &PoolIdOf<Test>| -> u128 {
+		<TruncatedSubAccount<AssetConversionPalletId> as PoolAccountDerivation<_, _>>::pool_account(
+			pool_id,
+		)
+	};
+
+	let pool1 = (Native, Asset(1), FEE_TIER);
+	// only differs from `pool1` in the third byte of the encoded asset id.
+	let pool2 = (Native, Asset(1 + (1 << 16)), FEE_TIER);
+
+	// the hash based scheme, used by the mock runtime, derives distinct accounts.
+	assert_ne!(hashed(&pool1), hashed(&pool2));
+	assert_eq!(hashed(&pool1), AssetConversion::get_pool_account(&pool1));
+
+	// a `u128` account only fits the type id, the pallet id and the first 4 bytes of the encoded
+	// pool id, so the truncating scheme derives the same account for both.
+	assert_eq!(
+		truncated(&pool1),
+		AssetConversionPalletId::get().into_sub_account_truncating(&pool1)
+	);
+	assert_eq!(truncated(&pool1), truncated(&pool2));
+}
+
 #[test]
 fn check_max_numbers() {
 	new_test_ext().execute_with(|| {
@@ -121,16 +184,147 @@ fn check_max_numbers() {
 		assert_eq!(AssetConversion::quote(&u128::MAX, &u128::MAX, &1u128).ok().unwrap(), 1);
 
 		assert_eq!(
-			AssetConversion::get_amount_out(&100u128, &u128::MAX, &u128::MAX).ok().unwrap(),
+			AssetConversion::get_amount_out(&100u128, &u128::MAX, &u128::MAX, FEE_TIER)
+				.ok()
+				.unwrap(),
 			99
 		);
 		assert_eq!(
-			AssetConversion::get_amount_in(&100u128, &u128::MAX, &u128::MAX).ok().unwrap(),
+			AssetConversion::get_amount_in(&100u128, &u128::MAX, &u128::MAX, FEE_TIER)
+				.ok()
+				.unwrap(),
 			101
 		);
 	});
 }
 
+#[test]
+fn fee_and_liquidity_constants_are_in_metadata() {
+	new_test_ext().execute_with(|| {
+		let constants = AssetConversion::pallet_constants_metadata();
+		let value_of = |name: &str| {
+			constants
+				.iter()
+				.find(|c| c.name == name)
+				.map(|c| c.value.clone())
+				.unwrap_or_else(|| panic!("`{}` is not exposed in the metadata", name))
+		};
+
+		assert_eq!(value_of("LPFee"), codec::Encode::encode(&3u32));
+		assert_eq!(value_of("FeeTiers"), codec::Encode::encode(&FeeTiers::get()));
+		assert_eq!(value_of("MintMinLiquidity"), codec::Encode::encode(&100u128));
+		assert_eq!(value_of("PalletId"), codec::Encode::encode(&AssetConversionPalletId::get()));
+	});
+}
+
+#[test]
+fn out_of_range_fee_is_rejected() {
+	new_test_ext().execute_with(|| {
+		let fee = Permill::one();
+		assert!(matches!(
+			AssetConversion::get_amount_out(&100, &1000, &1000, fee),
+			Err(Error::<Test>::InvalidFeeConfiguration)
+		));
+		assert!(matches!(
+			AssetConversion::get_amount_in(&100, &1000, &1000, fee),
+			Err(Error::<Test>::InvalidFeeConfiguration)
+		));
+
+		let fee = Permill::from_parts(999_999);
+		assert!(AssetConversion::get_amount_out(&100, &1000, &1000, fee).is_ok());
+		assert!(AssetConversion::get_amount_in(&100, &1000, &1000, fee).is_ok());
+	});
+}
+
+#[test]
+fn integrity_test_accepts_the_mock_config() {
+	use frame_support::traits::Hooks;
+
+	new_test_ext().execute_with(|| {
+		<AssetConversion as Hooks<BlockNumberFor<Test>>>::integrity_test();
+	});
+}
+
+#[test]
+#[should_panic(expected = "the `LPFee` should be less than 100%")]
+fn integrity_test_catches_out_of_range_fee() {
+	use frame_support::traits::Hooks;
+
+	new_test_ext().execute_with(|| {
+		LPFee::set(&1000);
+		<AssetConversion as Hooks<BlockNumberFor<Test>>>::integrity_test();
+	});
+}
+
+#[test]
+#[should_panic(expected = "the `FeeTiers` should not be empty")]
+fn integrity_test_catches_missing_fee_tiers() {
+	use frame_support::traits::Hooks;
+
+	new_test_ext().execute_with(|| {
+		FeeTiers::set(&Default::default());
+		<AssetConversion as Hooks<BlockNumberFor<Test>>>::integrity_test();
+	});
+}
+
+#[test]
+#[should_panic(expected = "the `FeeTiers` should be less than 100%")]
+fn integrity_test_catches_out_of_range_fee_tier() {
+	use frame_support::traits::Hooks;
+
+	new_test_ext().execute_with(|| {
+		FeeTiers::set(&bounded_vec![FEE_TIER, Permill::one()]);
+		<AssetConversion as Hooks<BlockNumberFor<Test>>>::integrity_test();
+	});
+}
+
+#[test]
+fn amount_helpers_never_decrease_constant_product() {
+	new_test_ext().execute_with(|| {
+		// a small xorshift generator, good enough to spread the inputs over many magnitudes.
+		let mut seed = 0x2545_f491_4f6c_dd1du64;
+		let mut next = |max_bits: u32| {
+			seed ^= seed << 13;
+			seed ^= seed >> 7;
+			seed ^= seed << 17;
+			let bits = 1 + (seed % max_bits as u64) as u32;
+			(seed as u128 | ((seed as u128) << 64)) >> (128 - bits)
+		};
+
+		for _ in 0..10_000 {
+			let reserve_in = next(100).max(1);
+			let reserve_out = next(100).max(2);
+
+			let amount_in = next(100).max(1);
+			if let Ok(amount_out) =
+				AssetConversion::get_amount_out(&amount_in, &reserve_in, &reserve_out, FEE_TIER)
+			{
+				assert!(amount_out < reserve_out);
+				assert!(AssetConversion::is_constant_product_preserved(
+					&reserve_in,
+					&reserve_out,
+					&(reserve_in + amount_in),
+					&(reserve_out - amount_out),
+				));
+			}
+
+			let amount_out = next(100) % reserve_out;
+			if let Ok(amount_in) =
+				AssetConversion::get_amount_in(&amount_out, &reserve_in, &reserve_out, FEE_TIER)
+			{
+				if let Some(new_reserve_in) = reserve_in.checked_add(amount_in) {
+					assert!(AssetConversion::is_constant_product_preserved(
+						&reserve_in,
+						&reserve_out,
+						&new_reserve_in,
+						&(reserve_out - amount_out),
+					));
+				}
+			}
+		}
+	});
+}
+
 #[test]
 fn can_create_pool() {
 	new_test_ext().execute_with(|| {
@@ -139,13 +333,18 @@ fn can_create_pool() {
 		let user = 1;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
-		let pool_id = (token_1, token_2);
+		let pool_id = (token_1, token_2, FEE_TIER);
 
 		create_tokens(user, vec![token_2]);
 
 		let lp_token = AssetConversion::get_next_pool_asset_id();
 		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 1000));
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_2, token_1));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER
+		));
 
 		let setup_fee = <<Test as Config>::PoolSetupFee as Get<<Test as Config>::Balance>>::get();
 		let pool_account = <<Test as Config>::PoolSetupFeeReceiver as Get<u128>>::get();
@@ -170,389 +369,5425 @@ fn can_create_pool() {
 		assert_eq!(pool_assets(), vec![lp_token]);
 
 		assert_noop!(
-			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_1),
+			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_1, FEE_TIER),
 			Error::<Test>::EqualAssets
 		);
 		assert_noop!(
-			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_2, token_2),
+			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_2, token_2, FEE_TIER),
 			Error::<Test>::EqualAssets
 		);
 
 		// validate we can create Asset(1)/Asset(2) pool
 		let token_1 = NativeOrAssetId::Asset(1);
 		create_tokens(user, vec![token_1]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
 		// validate we can force the first asset to be the Native currency only
 		AllowMultiAssetPools::set(&false);
 		let token_1 = NativeOrAssetId::Asset(3);
 		assert_noop!(
-			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2),
+			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2, FEE_TIER),
 			Error::<Test>::PoolMustContainNativeCurrency
 		);
 	});
 }
 
 #[test]
-fn create_same_pool_twice_should_fail() {
+fn lp_token_metadata_uses_the_configured_decimals() {
 	new_test_ext().execute_with(|| {
+		use frame_support::traits::fungibles::metadata::Inspect as InspectMetadata;
+
 		let user = 1;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
 
 		create_tokens(user, vec![token_2]);
-
 		let lp_token = AssetConversion::get_next_pool_asset_id();
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_2, token_1));
-		let expected_free = lp_token + 1;
-		assert_eq!(expected_free, AssetConversion::get_next_pool_asset_id());
-
-		assert_noop!(
-			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_2, token_1),
-			Error::<Test>::PoolExists
-		);
-		assert_eq!(expected_free, AssetConversion::get_next_pool_asset_id());
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
-		// Try switching the same tokens around:
-		assert_noop!(
-			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2),
-			Error::<Test>::PoolExists
+		assert_eq!(
+			<PoolAssets as InspectMetadata<u128>>::decimals(lp_token),
+			<<Test as Config>::LpTokenDecimals as Get<u8>>::get()
 		);
-		assert_eq!(expected_free, AssetConversion::get_next_pool_asset_id());
+		assert_eq!(<PoolAssets as InspectMetadata<u128>>::symbol(lp_token), b"LP".to_vec());
 	});
 }
 
 #[test]
-fn different_pools_should_have_different_lp_tokens() {
+fn lp_token_metadata_deposit_is_paid_by_the_pool_creator() {
 	new_test_ext().execute_with(|| {
 		let user = 1;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
-		let token_3 = NativeOrAssetId::Asset(3);
-		let pool_id_1_2 = (token_1, token_2);
-		let pool_id_1_3 = (token_1, token_3);
-
-		create_tokens(user, vec![token_2, token_3]);
-
-		let lp_token2_1 = AssetConversion::get_next_pool_asset_id();
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_2, token_1));
-		let lp_token3_1 = AssetConversion::get_next_pool_asset_id();
+		let pool_id = (token_1, token_2, FEE_TIER);
+		let deposit = 2 * get_ed();
+		LpMetadataDepositBase::set(&deposit);
 
-		assert_eq!(
-			events(),
-			[Event::<Test>::PoolCreated {
-				creator: user,
-				pool_id: pool_id_1_2,
-				pool_account: AssetConversion::get_pool_account(&pool_id_1_2),
-				lp_token: lp_token2_1
-			}]
-		);
+		create_tokens(user, vec![token_2]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000));
+		let before = balance(user, NativeOrAssetId::Native);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_3, token_1));
+		let setup_fee = <<Test as Config>::PoolSetupFee as Get<<Test as Config>::Balance>>::get();
+		// the creator also pays the deposit of the pool's account of `token_2`.
+		let account_deposit =
+			<<Test as pallet_assets::Config<Instance1>>::AssetAccountDeposit as Get<u128>>::get();
 		assert_eq!(
-			events(),
-			[Event::<Test>::PoolCreated {
-				creator: user,
-				pool_id: pool_id_1_3,
-				pool_account: AssetConversion::get_pool_account(&pool_id_1_3),
-				lp_token: lp_token3_1,
-			}]
+			balance(user, NativeOrAssetId::Native),
+			before - setup_fee - account_deposit - deposit
 		);
 
-		assert_ne!(lp_token2_1, lp_token3_1);
+		// the deposit is held by the pool account and is not part of the native reserve.
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		assert_eq!(Balances::reserved_balance(pool_account), deposit);
+		assert_eq!(balance(pool_account, NativeOrAssetId::Native), 0);
+		assert!(matches!(
+			AssetConversion::get_reserves(&token_1, &token_2, FEE_TIER),
+			Err(Error::<Test>::EmptyPool)
+		));
 	});
 }
 
 #[test]
-fn can_add_liquidity() {
+fn create_same_pool_twice_should_fail() {
 	new_test_ext().execute_with(|| {
 		let user = 1;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
-		let token_3 = NativeOrAssetId::Asset(3);
-
-		create_tokens(user, vec![token_2, token_3]);
-		let lp_token1 = AssetConversion::get_next_pool_asset_id();
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
-		let lp_token2 = AssetConversion::get_next_pool_asset_id();
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_3));
 
-		let ed = get_ed();
-		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 * 2 + ed));
-		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
-		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 3, user, 1000));
+		create_tokens(user, vec![token_2]);
 
-		assert_ok!(AssetConversion::add_liquidity(
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
 			RuntimeOrigin::signed(user),
-			token_1,
 			token_2,
-			10000,
-			10,
-			10000,
-			10,
-			user,
-		));
-
-		let pool_id = (token_1, token_2);
-		assert!(events().contains(&Event::<Test>::LiquidityAdded {
-			who: user,
-			mint_to: user,
-			pool_id,
-			amount1_provided: 10000,
-			amount2_provided: 10,
-			lp_token: lp_token1,
-			lp_token_minted: 216,
-		}));
-		let pallet_account = AssetConversion::get_pool_account(&pool_id);
-		assert_eq!(balance(pallet_account, token_1), 10000);
-		assert_eq!(balance(pallet_account, token_2), 10);
-		assert_eq!(balance(user, token_1), 10000 + ed);
-		assert_eq!(balance(user, token_2), 1000 - 10);
-		assert_eq!(pool_balance(user, lp_token1), 216);
-
-		// try to pass the non-native - native assets, the result should be the same
-		assert_ok!(AssetConversion::add_liquidity(
-			RuntimeOrigin::signed(user),
-			token_3,
 			token_1,
-			10,
-			10000,
-			10,
-			10000,
-			user,
+			FEE_TIER
 		));
+		let expected_free = lp_token + 1;
+		assert_eq!(expected_free, AssetConversion::get_next_pool_asset_id());
 
-		let pool_id = (token_1, token_3);
-		assert!(events().contains(&Event::<Test>::LiquidityAdded {
-			who: user,
-			mint_to: user,
-			pool_id,
-			amount1_provided: 10000,
-			amount2_provided: 10,
-			lp_token: lp_token2,
-			lp_token_minted: 216,
-		}));
-		let pallet_account = AssetConversion::get_pool_account(&pool_id);
-		assert_eq!(balance(pallet_account, token_1), 10000);
-		assert_eq!(balance(pallet_account, token_3), 10);
-		assert_eq!(balance(user, token_1), ed);
-		assert_eq!(balance(user, token_3), 1000 - 10);
-		assert_eq!(pool_balance(user, lp_token2), 216);
+		assert_noop!(
+			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_2, token_1, FEE_TIER),
+			Error::<Test>::PoolExists
+		);
+		assert_eq!(expected_free, AssetConversion::get_next_pool_asset_id());
+
+		// Try switching the same tokens around:
+		assert_noop!(
+			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2, FEE_TIER),
+			Error::<Test>::PoolExists
+		);
+		assert_eq!(expected_free, AssetConversion::get_next_pool_asset_id());
 	});
 }
 
 #[test]
-fn add_tiny_liquidity_leads_to_insufficient_liquidity_minted_error() {
+fn a_pair_can_have_a_pool_in_each_fee_tier() {
+	use std::collections::BTreeSet;
+
 	new_test_ext().execute_with(|| {
 		let user = 1;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
+		let [low, mid, high] = [Permill::from_parts(500), FEE_TIER, Permill::from_percent(1)];
 
 		create_tokens(user, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
-
-		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 1000));
-		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100_000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100_000));
 
-		assert_noop!(
-			AssetConversion::add_liquidity(
+		for fee_tier in [low, mid, high] {
+			assert_ok!(AssetConversion::create_pool(
 				RuntimeOrigin::signed(user),
 				token_1,
 				token_2,
-				1,
-				1,
-				1,
-				1,
-				user
-			),
-			Error::<Test>::AmountOneLessThanMinimal
+				fee_tier
+			));
+		}
+		assert_noop!(
+			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_2, token_1, low),
+			Error::<Test>::PoolExists
 		);
-
 		assert_noop!(
-			AssetConversion::add_liquidity(
+			AssetConversion::create_pool(
 				RuntimeOrigin::signed(user),
 				token_1,
 				token_2,
-				get_ed(),
-				1,
-				1,
-				1,
-				user
+				Permill::from_percent(2)
 			),
-			Error::<Test>::InsufficientLiquidityMinted
+			Error::<Test>::InvalidFeeTier
 		);
-	});
-}
 
-#[test]
-fn add_tiny_liquidity_directly_to_pool_address() {
-	new_test_ext().execute_with(|| {
-		let user = 1;
-		let token_1 = NativeOrAssetId::Native;
-		let token_2 = NativeOrAssetId::Asset(2);
-		let token_3 = NativeOrAssetId::Asset(3);
-
-		create_tokens(user, vec![token_2, token_3]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_3));
-
-		let ed = get_ed();
-		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 * 2 + ed));
-		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
-		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 3, user, 1000));
+		let pool_ids = [low, mid, high].map(|fee_tier| (token_1, token_2, fee_tier));
+		assert_eq!(pools(), pool_ids.to_vec());
+		let accounts = pool_ids.map(|pool_id| AssetConversion::get_pool_account(&pool_id));
+		let lp_tokens = pool_ids.map(|pool_id| Pools::<Test>::get(pool_id).unwrap().lp_token);
+		assert_eq!(accounts.iter().collect::<BTreeSet<_>>().len(), 3);
+		assert_eq!(lp_tokens.iter().collect::<BTreeSet<_>>().len(), 3);
+		for (pool_id, fee_tier) in pool_ids.iter().zip([low, mid, high]) {
+			assert_eq!(Pools::<Test>::get(pool_id).unwrap().fee_tier, fee_tier);
+		}
 
-		// check we're still able to add the liquidity even when the pool already has some token_1
-		let pallet_account = AssetConversion::get_pool_account(&(token_1, token_2));
-		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), pallet_account, 1000));
+		// the 1% pool holds twice as much of `token_2` as the others.
+		for (fee_tier, liquidity2) in [(low, 10_000), (mid, 10_000), (high, 20_000)] {
+			assert_ok!(AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				fee_tier,
+				10_000,
+				liquidity2,
+				1,
+				1,
+				user,
+			));
+		}
+		let reserves = |i: usize| (balance(accounts[i], token_1), balance(accounts[i], token_2));
 
-		assert_ok!(AssetConversion::add_liquidity(
+		// selling `token_2`, the pool with the lowest fee pays out the most.
+		let amount_out = AssetConversion::get_amount_out(&100, &10_000, &10_000, low).unwrap();
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_2, token_1, 100, true),
+			Some(amount_out)
+		);
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
 			RuntimeOrigin::signed(user),
-			token_1,
-			token_2,
-			10000,
+			bvec![token_2, token_1],
+			100,
+			1,
+			user,
+			false,
+		));
+		assert_eq!(reserves(0), (10_000 - amount_out, 10_100));
+		assert_eq!(reserves(1), (10_000, 10_000));
+		assert_eq!(reserves(2), (10_000, 20_000));
+
+		// buying `token_2`, the better price of the 1% pool makes up for its fee.
+		let amount_out = AssetConversion::get_amount_out(&100, &10_000, &20_000, high).unwrap();
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_2, 100, true),
+			Some(amount_out)
+		);
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			100,
+			1,
+			user,
+			false,
+		));
+		assert_eq!(reserves(1), (10_000, 10_000));
+		assert_eq!(reserves(2), (10_100, 20_000 - amount_out));
+
+		// and it asks for the least to buy an exact amount.
+		let amount_in =
+			AssetConversion::get_amount_in(&100, &10_100, &(20_000 - amount_out), high).unwrap();
+		assert_eq!(
+			AssetConversion::quote_price_tokens_for_exact_tokens(token_1, token_2, 100, true),
+			Some(amount_in)
+		);
+		assert_ok!(AssetConversion::swap_tokens_for_exact_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			100,
+			amount_in,
+			user,
+			false,
+		));
+		assert_eq!(reserves(1), (10_000, 10_000));
+		assert_eq!(reserves(2), (10_100 + amount_in, 20_000 - amount_out - 100));
+
+		// each pool charges the fee of its tier.
+		let amount_out = AssetConversion::get_amount_out(&1_000, &10_000, &10_000, mid).unwrap();
+		assert!(
+			amount_out < AssetConversion::get_amount_out(&1_000, &10_000, &10_000, low).unwrap()
+		);
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens_in_pool(
+			RuntimeOrigin::signed(user),
+			lp_tokens[1],
+			token_2,
+			1_000,
+			amount_out,
+			user,
+			false,
+		));
+		assert_eq!(reserves(1), (10_000 - amount_out, 11_000));
+	});
+}
+
+#[test]
+fn set_pool_fee_reports_the_old_and_new_fee() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+		let [low, high] = [Permill::from_parts(500), Permill::from_percent(1)];
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100_000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100_000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10_000,
+			10_000,
+			1,
+			1,
+			user,
+		));
+
+		assert_noop!(
+			AssetConversion::set_pool_fee(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				high
+			),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			AssetConversion::set_pool_fee(RuntimeOrigin::root(), token_1, token_2, low, high),
+			Error::<Test>::PoolNotFound
+		);
+		assert_noop!(
+			AssetConversion::set_pool_fee(
+				RuntimeOrigin::root(),
+				token_1,
+				token_2,
+				FEE_TIER,
+				Permill::one()
+			),
+			Error::<Test>::InvalidFeeConfiguration
+		);
+
+		assert_ok!(AssetConversion::set_pool_fee(
+			RuntimeOrigin::root(),
+			token_2,
+			token_1,
+			FEE_TIER,
+			high
+		));
+		assert_eq!(
+			events().last(),
+			Some(&Event::<Test>::PoolFeeChanged { pool_id, old_fee: FEE_TIER, new_fee: high })
+		);
+		assert_ok!(AssetConversion::set_pool_fee(
+			RuntimeOrigin::root(),
+			token_1,
+			token_2,
+			FEE_TIER,
+			low
+		));
+		assert_eq!(
+			events().last(),
+			Some(&Event::<Test>::PoolFeeChanged { pool_id, old_fee: high, new_fee: low })
+		);
+
+		// the pool keeps its id, and swaps through it are charged the new fee.
+		assert_eq!(Pools::<Test>::get(pool_id).unwrap().fee_tier, low);
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_2, 1_000, true),
+			AssetConversion::get_amount_out(&1_000, &10_000, &10_000, low).ok()
+		);
+	});
+}
+
+#[test]
+fn set_global_fee_reports_the_old_and_new_fee() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let global = Permill::from_percent(1);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100_000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100_000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10_000,
+			10_000,
+			1,
+			1,
+			user,
+		));
+		let quote =
+			|| AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_2, 1_000, true);
+
+		assert_noop!(
+			AssetConversion::set_global_fee(RuntimeOrigin::signed(user), Some(global)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			AssetConversion::set_global_fee(RuntimeOrigin::root(), Some(Permill::one())),
+			Error::<Test>::InvalidFeeConfiguration
+		);
+
+		assert_ok!(AssetConversion::set_global_fee(RuntimeOrigin::root(), Some(global)));
+		assert_eq!(
+			events().last(),
+			Some(&Event::<Test>::GlobalFeeChanged { old: None, new: Some(global) })
+		);
+		assert_eq!(quote(), AssetConversion::get_amount_out(&1_000, &10_000, &10_000, global).ok());
+
+		// the global fee takes precedence over the fee of the pool.
+		assert_ok!(AssetConversion::set_pool_fee(
+			RuntimeOrigin::root(),
+			token_1,
+			token_2,
+			FEE_TIER,
+			Permill::from_parts(500)
+		));
+		assert_eq!(quote(), AssetConversion::get_amount_out(&1_000, &10_000, &10_000, global).ok());
+
+		assert_ok!(AssetConversion::set_global_fee(RuntimeOrigin::root(), None));
+		assert_eq!(
+			events().last(),
+			Some(&Event::<Test>::GlobalFeeChanged { old: Some(global), new: None })
+		);
+		assert_eq!(GlobalFee::<Test>::get(), None);
+		assert_eq!(
+			quote(),
+			AssetConversion::get_amount_out(&1_000, &10_000, &10_000, Permill::from_parts(500))
+				.ok()
+		);
+	});
+}
+
+#[test]
+fn create_pool_fails_when_pool_asset_ids_are_exhausted() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+
+		create_tokens(user, vec![token_2, token_3]);
+
+		// the second to last id can still be used.
+		NextPoolAssetId::<Test>::set(Some(u32::MAX - 1));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_eq!(AssetConversion::get_next_pool_asset_id(), u32::MAX);
+
+		// but incrementing past the last one is not possible.
+		assert_noop!(
+			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_3, FEE_TIER),
+			Error::<Test>::PoolAssetIdExhausted
+		);
+	});
+}
+
+#[test]
+fn can_find_pool_by_lp_token() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+
+		create_tokens(user, vec![token_2, token_3]);
+
+		let lp_token1 = AssetConversion::get_next_pool_asset_id();
+		assert_eq!(AssetConversion::pool_of_lp_token(lp_token1), None);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER
+		));
+		let lp_token2 = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_3,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_eq!(
+			AssetConversion::pool_of_lp_token(lp_token1),
+			Some((token_1, token_2, FEE_TIER))
+		);
+		assert_eq!(
+			AssetConversion::pool_of_lp_token(lp_token2),
+			Some((token_2, token_3, FEE_TIER))
+		);
+		assert_eq!(AssetConversion::pool_of_lp_token(lp_token2 + 1), None);
+	});
+}
+
+#[test]
+fn different_pools_should_have_different_lp_tokens() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+		let pool_id_1_2 = (token_1, token_2, FEE_TIER);
+		let pool_id_1_3 = (token_1, token_3, FEE_TIER);
+
+		create_tokens(user, vec![token_2, token_3]);
+
+		let lp_token2_1 = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER
+		));
+		let lp_token3_1 = AssetConversion::get_next_pool_asset_id();
+
+		assert_eq!(
+			events(),
+			[Event::<Test>::PoolCreated {
+				creator: user,
+				pool_id: pool_id_1_2,
+				pool_account: AssetConversion::get_pool_account(&pool_id_1_2),
+				lp_token: lp_token2_1
+			}]
+		);
+
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_3,
+			token_1,
+			FEE_TIER
+		));
+		assert_eq!(
+			events(),
+			[Event::<Test>::PoolCreated {
+				creator: user,
+				pool_id: pool_id_1_3,
+				pool_account: AssetConversion::get_pool_account(&pool_id_1_3),
+				lp_token: lp_token3_1,
+			}]
+		);
+
+		assert_ne!(lp_token2_1, lp_token3_1);
+	});
+}
+
+#[test]
+fn can_add_liquidity() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+
+		create_tokens(user, vec![token_2, token_3]);
+		let lp_token1 = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		let lp_token2 = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_3,
+			FEE_TIER
+		));
+
+		let ed = get_ed();
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 * 2 + ed));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 3, user, 1000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
 			10,
 			10000,
 			10,
 			user,
 		));
 
-		// check the same but for token_3 (non-native token)
-		let pallet_account = AssetConversion::get_pool_account(&(token_1, token_3));
-		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, pallet_account, 1));
-		assert_ok!(AssetConversion::add_liquidity(
+		let pool_id = (token_1, token_2, FEE_TIER);
+		assert!(events().contains(&Event::<Test>::LiquidityAdded {
+			who: user,
+			mint_to: user,
+			pool_id,
+			amount1_provided: 10000,
+			amount2_provided: 10,
+			lp_token: lp_token1,
+			lp_token_minted: 216,
+		}));
+		let pallet_account = AssetConversion::get_pool_account(&pool_id);
+		assert_eq!(balance(pallet_account, token_1), 10000);
+		assert_eq!(balance(pallet_account, token_2), 10);
+		assert_eq!(balance(user, token_1), 10000 + ed);
+		assert_eq!(balance(user, token_2), 1000 - 10);
+		assert_eq!(pool_balance(user, lp_token1), 216);
+
+		// try to pass the non-native - native assets, the result should be the same
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_3,
+			token_1,
+			FEE_TIER,
+			10,
+			10000,
+			10,
+			10000,
+			user,
+		));
+
+		let pool_id = (token_1, token_3, FEE_TIER);
+		assert!(events().contains(&Event::<Test>::LiquidityAdded {
+			who: user,
+			mint_to: user,
+			pool_id,
+			amount1_provided: 10000,
+			amount2_provided: 10,
+			lp_token: lp_token2,
+			lp_token_minted: 216,
+		}));
+		let pallet_account = AssetConversion::get_pool_account(&pool_id);
+		assert_eq!(balance(pallet_account, token_1), 10000);
+		assert_eq!(balance(pallet_account, token_3), 10);
+		assert_eq!(balance(user, token_1), ed);
+		assert_eq!(balance(user, token_3), 1000 - 10);
+		assert_eq!(pool_balance(user, lp_token2), 216);
+	});
+}
+
+#[test]
+fn compute_add_liquidity_matches_add_liquidity() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		let ed = get_ed();
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 20000 + ed));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		// empty pool: the desired amounts are used as is.
+		assert_eq!(
+			AssetConversion::compute_add_liquidity(token_1, token_2, FEE_TIER, 10000, 10).unwrap(),
+			(10000, 10, 216)
+		);
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			10,
+			10000,
+			10,
+			user,
+		));
+
+		// the pool ratio is kept, so less than the desired amount of asset 2 is used. The
+		// result follows the order of the given assets.
+		let (amount2, amount1, lp_minted) =
+			AssetConversion::compute_add_liquidity(token_2, token_1, FEE_TIER, 100, 5000).unwrap();
+		assert_eq!((amount1, amount2), (5000, 5));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER,
+			100,
+			5000,
+			1,
+			1,
+			user,
+		));
+		assert!(events().contains(&Event::<Test>::LiquidityAdded {
+			who: user,
+			mint_to: user,
+			pool_id,
+			amount1_provided: amount1,
+			amount2_provided: amount2,
+			lp_token,
+			lp_token_minted: lp_minted,
+		}));
+
+		assert!(matches!(
+			AssetConversion::compute_add_liquidity(
+				token_1,
+				NativeOrAssetId::Asset(3),
+				FEE_TIER,
+				10,
+				10
+			),
+			Err(Error::<Test>::PoolNotFound)
+		));
+	});
+}
+
+#[test]
+fn add_liquidity_respects_lp_transfer_policy() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let denied = 666;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		let ed = get_ed();
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + ed));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		assert_noop!(
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				10000,
+				10,
+				10000,
+				10,
+				denied,
+			),
+			Error::<Test>::LpRecipientNotAllowed
+		);
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			10,
+			10000,
+			10,
+			user,
+		));
+	});
+}
+
+#[test]
+fn deposits_beyond_max_reserve_are_rejected() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		let ed = get_ed();
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 20000 + ed));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		MaxReserve::set(&10000);
+
+		// the pool can be filled up to the cap.
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			100,
+			10000,
+			100,
+			user,
+		));
+
+		// but no further deposits of the native asset are accepted.
+		assert_noop!(
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				1000,
+				10,
+				1,
+				1,
+				user,
+			),
+			Error::<Test>::ReserveCapExceeded
+		);
+		assert_noop!(
+			AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, token_2],
+				1000,
+				1,
+				user,
+				false,
+			),
+			Error::<Test>::ReserveCapExceeded
+		);
+
+		// swapping into the other side of the pool is still possible.
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1],
+			10,
+			1,
+			user,
+			false,
+		));
+	});
+}
+
+#[test]
+fn add_tiny_liquidity_leads_to_insufficient_liquidity_minted_error() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 1000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		assert_noop!(
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				1,
+				1,
+				1,
+				1,
+				user
+			),
+			Error::<Test>::AmountOneLessThanMinimal
+		);
+
+		assert_noop!(
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				get_ed(),
+				1,
+				1,
+				1,
+				user
+			),
+			Error::<Test>::InsufficientLiquidityMinted
+		);
+	});
+}
+
+#[test]
+fn add_tiny_liquidity_directly_to_pool_address() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+
+		create_tokens(user, vec![token_2, token_3]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_3,
+			FEE_TIER
+		));
+
+		let ed = get_ed();
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 * 2 + ed));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 3, user, 1000));
+
+		// check we're still able to add the liquidity even when the pool already has some token_1
+		let pallet_account = AssetConversion::get_pool_account(&(token_1, token_2, FEE_TIER));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), pallet_account, 1000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			10,
+			10000,
+			10,
+			user,
+		));
+
+		// check the same but for token_3 (non-native token)
+		let pallet_account = AssetConversion::get_pool_account(&(token_1, token_3, FEE_TIER));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, pallet_account, 1));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_3,
+			FEE_TIER,
+			10000,
+			10,
+			10000,
+			10,
+			user,
+		));
+	});
+}
+
+#[test]
+fn can_remove_liquidity() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000000000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1000000000,
+			100000,
+			1000000000,
+			100000,
+			user,
+		));
+
+		let total_lp_received = pool_balance(user, lp_token);
+		LiquidityWithdrawalFee::set(&Permill::from_percent(10));
+
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			total_lp_received,
+			0,
+			0,
+			user,
+		));
+
+		assert!(events().contains(&Event::<Test>::LiquidityRemoved {
+			who: user,
+			withdraw_to: user,
+			pool_id,
+			amount1: 899991000,
+			amount2: 89999,
+			lp_token,
+			lp_token_burned: total_lp_received,
+			withdrawal_fee: <Test as Config>::LiquidityWithdrawalFee::get(),
+			amount1_min_receive: 0,
+			amount2_min_receive: 0,
+		}));
+
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		assert_eq!(balance(pool_account, token_1), 100009000);
+		assert_eq!(balance(pool_account, token_2), 10001);
+		assert_eq!(pool_balance(pool_account, lp_token), 100);
+
+		assert_eq!(balance(user, token_1), 10000000000 - 1000000000 + 899991000);
+		assert_eq!(balance(user, token_2), 89999);
+		assert_eq!(pool_balance(user, lp_token), 0);
+	});
+}
+
+#[test]
+fn can_remove_single_sided_dust_from_lopsided_pool() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000000000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1000000000,
+			1000,
+			1,
+			1,
+			user,
+		));
+
+		// 100 of the 1000000 lp tokens are worth a tenth of token_2, which rounds down to zero.
+		let remove = || {
+			AssetConversion::remove_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				100,
+				0,
+				0,
+				user,
+			)
+		};
+		assert_noop!(remove(), Error::<Test>::AssetTwoWithdrawalDidNotMeetMinimum);
+
+		AllowSingleSidedDust::set(&true);
+		let native_before = balance(user, token_1);
+		let lp_before = pool_balance(user, lp_token);
+		assert_ok!(remove());
+
+		assert_eq!(balance(user, token_1), native_before + 100000);
+		assert_eq!(balance(user, token_2), 0);
+		assert_eq!(pool_balance(user, lp_token), lp_before - 100);
+	});
+}
+
+#[test]
+fn router_can_manage_liquidity_on_behalf_of_a_user() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let router = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 1000));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), router, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, router, 1000));
+
+		// the router pays, the user gets the lp tokens.
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(router),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			10000,
+			200,
+			user,
+		));
+		assert_eq!(balance(router, token_1), 100000 - 10000);
+		assert_eq!(balance(router, token_2), 1000 - 200);
+		assert_eq!(balance(user, token_1), 1000);
+		assert_eq!(pool_balance(router, lp_token), 0);
+		let lp = pool_balance(user, lp_token);
+		assert_eq!(lp, 1314);
+
+		// the router can't burn lp tokens it doesn't hold.
+		assert!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(router),
+			token_1,
+			token_2,
+			FEE_TIER,
+			lp,
+			0,
+			0,
+			user,
+		)
+		.is_err());
+
+		// once the user hands them over, the router withdraws them straight to the user.
+		assert_ok!(PoolAssets::transfer(RuntimeOrigin::signed(user), lp_token, router, lp));
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(router),
+			token_1,
+			token_2,
+			FEE_TIER,
+			lp,
+			9000,
+			180,
+			user,
+		));
+		assert!(events().contains(&Event::<Test>::LiquidityRemoved {
+			who: router,
+			withdraw_to: user,
+			pool_id,
+			amount1: 9292,
+			amount2: 185,
+			lp_token,
+			lp_token_burned: lp,
+			withdrawal_fee: <Test as Config>::LiquidityWithdrawalFee::get(),
+			amount1_min_receive: 9000,
+			amount2_min_receive: 180,
+		}));
+		assert_eq!(balance(user, token_1), 1000 + 9292);
+		assert_eq!(balance(user, token_2), 185);
+		assert_eq!(balance(router, token_1), 100000 - 10000);
+		assert_eq!(pool_balance(router, lp_token), 0);
+	});
+}
+
+#[test]
+fn can_remove_liquidity_into_single_asset() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000000000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1000000000,
+			100000,
+			1000000000,
+			100000,
+			user,
+		));
+
+		let lp_to_burn = pool_balance(user, lp_token) / 2;
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		let reserve_1 = balance(pool_account, token_1);
+		let reserve_2 = balance(pool_account, token_2);
+		let total_supply = pool_balance(user, lp_token) + pool_balance(pool_account, lp_token);
+
+		// the native side of the share plus the asset side swapped into native.
+		let removed_1 = lp_to_burn * reserve_1 / total_supply;
+		let removed_2 = lp_to_burn * reserve_2 / total_supply;
+		let swapped = AssetConversion::get_amount_out(
+			&removed_2,
+			&(reserve_2 - removed_2),
+			&(reserve_1 - removed_1),
+			FEE_TIER,
+		)
+		.unwrap();
+		let native_before = balance(user, token_1);
+		let asset_before = balance(user, token_2);
+
+		assert_noop!(
+			AssetConversion::remove_liquidity_single(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				lp_to_burn,
+				token_1,
+				removed_1 + swapped + 1,
+				user,
+			),
+			Error::<Test>::ProvidedMinimumNotSufficientForSwap
+		);
+		assert_noop!(
+			AssetConversion::remove_liquidity_single(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				lp_to_burn,
+				NativeOrAssetId::Asset(3),
+				0,
+				user,
+			),
+			Error::<Test>::AssetNotInPool
+		);
+
+		assert_ok!(AssetConversion::remove_liquidity_single(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER,
+			lp_to_burn,
+			token_1,
+			removed_1 + swapped,
+			user,
+		));
+
+		assert_eq!(balance(user, token_1), native_before + removed_1 + swapped);
+		assert_eq!(balance(user, token_2), asset_before);
+		assert_eq!(balance(pool_account, token_1), reserve_1 - removed_1 - swapped);
+		assert_eq!(balance(pool_account, token_2), reserve_2);
+		assert!(events().contains(&Event::<Test>::LiquidityRemoved {
+			who: user,
+			withdraw_to: user,
+			pool_id,
+			amount1: removed_1,
+			amount2: removed_2,
+			lp_token,
+			lp_token_burned: lp_to_burn,
+			withdrawal_fee: <Test as Config>::LiquidityWithdrawalFee::get(),
+			amount1_min_receive: removed_1 + swapped,
+			amount2_min_receive: 0,
+		}));
+	});
+}
+
+#[test]
+fn lifetime_lp_minted_only_increases() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_eq!(AssetConversion::pool_stats(&token_1, &token_2, FEE_TIER).lifetime_lp_minted, 0);
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 20000 + get_ed()));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		let add_liquidity = || {
+			assert_ok!(AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				10000,
+				100,
+				1,
+				1,
+				user,
+			));
+		};
+
+		add_liquidity();
+		let first_minted = pool_balance(user, lp_token);
+		assert_eq!(
+			AssetConversion::pool_stats(&token_1, &token_2, FEE_TIER).lifetime_lp_minted,
+			first_minted
+		);
+
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			first_minted,
+			0,
+			0,
+			user,
+		));
+		assert_eq!(
+			AssetConversion::pool_stats(&token_2, &token_1, FEE_TIER).lifetime_lp_minted,
+			first_minted
+		);
+
+		add_liquidity();
+		let second_minted = pool_balance(user, lp_token);
+		assert_eq!(
+			AssetConversion::pool_stats(&token_1, &token_2, FEE_TIER).lifetime_lp_minted,
+			first_minted + second_minted
+		);
+	});
+}
+
+#[test]
+fn liquidity_removed_event_reports_the_minimums() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000000000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1000000000,
+			100000,
+			1000000000,
+			100000,
+			user,
+		));
+
+		// the minimums are given in the caller's order, but reported in the pool's.
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER,
+			pool_balance(user, lp_token),
+			89_000,
+			899_000_000,
+			user,
+		));
+
+		let (amount1, amount2, amount1_min_receive, amount2_min_receive) = events()
+			.into_iter()
+			.find_map(|e| match e {
+				Event::<Test>::LiquidityRemoved {
+					amount1,
+					amount2,
+					amount1_min_receive,
+					amount2_min_receive,
+					..
+				} => Some((amount1, amount2, amount1_min_receive, amount2_min_receive)),
+				_ => None,
+			})
+			.unwrap();
+		assert_eq!((amount1_min_receive, amount2_min_receive), (899_000_000, 89_000));
+		assert!(amount1 >= amount1_min_receive && amount2 >= amount2_min_receive);
+	});
+}
+
+#[test]
+fn removing_all_liquidity_keeps_pool_account_alive() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + get_ed()));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			100,
+			10000,
+			100,
+			user,
+		));
+
+		let total_lp_received = pool_balance(user, lp_token);
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			total_lp_received,
+			0,
+			0,
+			user,
+		));
+
+		// only the liquidity locked by `MintMinLiquidity` is left in the pool.
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		assert_eq!(balance(pool_account, token_1), 1000);
+		assert_eq!(balance(pool_account, token_2), 10);
+		assert!(System::account_exists(&pool_account));
+	});
+}
+
+#[test]
+fn can_reclaim_minimum_liquidity_of_a_drained_pool() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+		let treasury = ProtocolTreasury::get();
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + get_ed()));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			100,
+			10000,
+			100,
+			user,
+		));
+
+		assert_noop!(
+			AssetConversion::reclaim_minimum_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER
+			),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			AssetConversion::reclaim_minimum_liquidity(
+				RuntimeOrigin::root(),
+				token_1,
+				token_2,
+				FEE_TIER
+			),
+			Error::<Test>::PoolStillHasLiquidity
+		);
+
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			pool_balance(user, lp_token),
+			0,
+			0,
+			user,
+		));
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		assert_eq!(balance(pool_account, token_1), 1000);
+		assert_eq!(balance(pool_account, token_2), 10);
+
+		assert_ok!(AssetConversion::reclaim_minimum_liquidity(
+			RuntimeOrigin::root(),
+			token_2,
+			token_1,
+			FEE_TIER
+		));
+		assert!(events().contains(&Event::<Test>::MinimumLiquidityReclaimed {
+			pool_id,
+			lp_token,
+			amount1: 1000,
+			amount2: 10,
+		}));
+
+		assert_eq!(balance(pool_account, token_1), 0);
+		assert_eq!(balance(pool_account, token_2), 0);
+		assert_eq!(balance(treasury, token_1), 1000);
+		assert_eq!(balance(treasury, token_2), 10);
+		assert_eq!(PoolAssets::total_supply(lp_token), 0);
+		assert!(Pools::<Test>::get(&pool_id).is_none());
+		assert!(LpTokenToPool::<Test>::get(lp_token).is_none());
+
+		// the pool can be created again.
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+	});
+}
+
+#[test]
+fn burning_protocol_lp_donates_liquidity_to_remaining_lps() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let treasury = ProtocolTreasury::get();
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), treasury, get_ed()));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		for mint_to in [user, treasury] {
+			assert_ok!(AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				10000,
+				100,
+				1,
+				1,
+				mint_to,
+			));
+		}
+
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		let user_lp = pool_balance(user, lp_token);
+		let treasury_lp = pool_balance(treasury, lp_token);
+		let redeemable = || {
+			let total_supply =
+				<PoolAssets as frame_support::traits::fungibles::Inspect<u128>>::total_issuance(
+					lp_token,
+				);
+			user_lp * balance(pool_account, token_1) / total_supply
+		};
+		let redeemable_before = redeemable();
+
+		assert_noop!(
+			AssetConversion::burn_protocol_lp(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				treasury_lp
+			),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			AssetConversion::burn_protocol_lp(
+				RuntimeOrigin::root(),
+				token_1,
+				token_2,
+				FEE_TIER,
+				treasury_lp + 1
+			),
+			Error::<Test>::InsufficientProtocolLp
+		);
+
+		assert_ok!(AssetConversion::burn_protocol_lp(
+			RuntimeOrigin::root(),
+			token_2,
+			token_1,
+			FEE_TIER,
+			treasury_lp
+		));
+		assert!(events().contains(&Event::<Test>::ProtocolLpBurned {
+			pool_id,
+			lp_token,
+			amount: treasury_lp,
+		}));
+
+		assert_eq!(pool_balance(treasury, lp_token), 0);
+		assert_eq!(balance(pool_account, token_1), 20000);
+		assert!(redeemable() > redeemable_before);
+	});
+}
+
+#[test]
+fn can_not_redeem_more_lp_tokens_than_were_minted() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + get_ed()));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			10,
+			10000,
+			10,
+			user,
+		));
+
+		// Only 216 lp_tokens_minted
+		assert_eq!(pool_balance(user, lp_token), 216);
+
+		assert_noop!(
+			AssetConversion::remove_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				216 + 1, // Try and redeem 10 lp tokens while only 9 minted.
+				0,
+				0,
+				user,
+			),
+			DispatchError::Token(TokenError::FundsUnavailable)
+		);
+	});
+}
+
+#[test]
+fn quote_on_empty_pool_reports_insufficient_liquidity() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		let pool_account = AssetConversion::get_pool_account(&(token_1, token_2, FEE_TIER));
+		assert_eq!(balance(pool_account, token_1), 0);
+		assert_eq!(balance(pool_account, token_2), 0);
+		assert!(matches!(
+			AssetConversion::quote(&100, &0, &0),
+			Err(Error::<Test>::InsufficientLiquidity)
+		));
+		assert!(matches!(
+			AssetConversion::quote(&100, &1000, &0),
+			Err(Error::<Test>::InsufficientLiquidity)
+		));
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_2, 100, false),
+			None
+		);
+	});
+}
+
+#[test]
+fn can_quote_price() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(
+				NativeOrAssetId::Native,
+				NativeOrAssetId::Asset(2),
+				3000,
+				false,
+			),
+			Some(60)
+		);
+		// Check it still gives same price:
+		// (if the above accidentally exchanged then it would not give same quote as before)
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(
+				NativeOrAssetId::Native,
+				NativeOrAssetId::Asset(2),
+				3000,
+				false,
+			),
+			Some(60)
+		);
+
+		// Check inverse:
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(
+				NativeOrAssetId::Asset(2),
+				NativeOrAssetId::Native,
+				60,
+				false,
+			),
+			Some(3000)
+		);
+	});
+}
+
+#[test]
+fn spot_price_follows_the_callers_order() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_eq!(AssetConversion::spot_price(token_1, token_2, FEE_TIER), None);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_eq!(AssetConversion::spot_price(token_1, token_2, FEE_TIER), None);
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		assert_eq!(AssetConversion::spot_price(token_1, token_2, FEE_TIER), Some((200, 10000)));
+		assert_eq!(AssetConversion::spot_price(token_2, token_1, FEE_TIER), Some((10000, 200)));
+	});
+}
+
+#[test]
+fn can_take_a_flash_loan() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let borrower = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, borrower, 10));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			1000,
+			10000,
+			1000,
+			user,
+		));
+
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		let (reserve1, reserve2) =
+			AssetConversion::get_reserves(&token_1, &token_2, FEE_TIER).unwrap();
+
+		// 1% of 500
+		let fee = 5;
+		assert_ok!(AssetConversion::flash_loan(
+			RuntimeOrigin::signed(borrower),
+			token_2,
+			token_1,
+			FEE_TIER,
+			token_2,
+			500,
+			vec![FLASH_REPAY],
+		));
+		assert!(events().contains(&Event::<Test>::FlashLoan {
+			borrower,
+			pool_id,
+			asset: token_2,
+			amount: 500,
+			fee,
+		}));
+
+		// the fee ends up in the pool.
+		assert_eq!(balance(pool_account, token_1), reserve1);
+		assert_eq!(balance(pool_account, token_2), reserve2 + fee);
+		assert_eq!(balance(borrower, token_2), 10 - fee);
+		assert_eq!(FlashLoanLock::<Test>::get(), None);
+
+		// the pool is unlocked once the loan is repaid.
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			100,
+			1,
+			user,
+			false,
+		));
+	});
+}
+
+#[test]
+fn flash_loan_is_reverted_unless_repaid() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let borrower = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, borrower, 10));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			1000,
+			10000,
+			1000,
+			user,
+		));
+
+		for behaviour in [FLASH_KEEP, FLASH_REPAY_WITHOUT_FEE] {
+			assert_noop!(
+				AssetConversion::flash_loan(
+					RuntimeOrigin::signed(borrower),
+					token_1,
+					token_2,
+					FEE_TIER,
+					token_2,
+					500,
+					vec![behaviour],
+				),
+				Error::<Test>::FlashLoanNotRepaid
+			);
+		}
+
+		// the pool can't be traded against while the loan is outstanding.
+		assert_noop!(
+			AssetConversion::flash_loan(
+				RuntimeOrigin::signed(borrower),
+				token_1,
+				token_2,
+				FEE_TIER,
+				token_2,
+				500,
+				vec![FLASH_SWAP_IN_POOL],
+			),
+			Error::<Test>::FlashLoanInProgress
+		);
+
+		// the whole reserve can't be lent out.
+		assert_noop!(
+			AssetConversion::flash_loan(
+				RuntimeOrigin::signed(borrower),
+				token_1,
+				token_2,
+				FEE_TIER,
+				token_2,
+				1000,
+				vec![FLASH_REPAY],
+			),
+			Error::<Test>::AmountOutTooHigh
+		);
+
+		assert_noop!(
+			AssetConversion::flash_loan(
+				RuntimeOrigin::signed(borrower),
+				token_1,
+				token_2,
+				FEE_TIER,
+				NativeOrAssetId::Asset(3),
+				500,
+				vec![FLASH_REPAY],
+			),
+			Error::<Test>::AssetNotInPool
+		);
+	});
+}
+
+#[test]
+fn minting_dust_lp_to_a_new_account_fails() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let new_holder = 3;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 2000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			1000,
+			10000,
+			1000,
+			user,
+		));
+
+		let pool_account = AssetConversion::get_pool_account(&(token_1, token_2, FEE_TIER));
+		assert_ok!(PoolAssets::force_asset_status(
+			RuntimeOrigin::root(),
+			lp_token,
+			pool_account,
+			pool_account,
+			pool_account,
+			pool_account,
+			1000,
+			false,
+			false,
+		));
+
+		// 1000 native and 100 of the asset mint about 10% of the lp supply, well below 1000.
+		assert_noop!(
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				1000,
+				100,
+				1000,
+				100,
+				new_holder,
+			),
+			Error::<Test>::LpBelowMinBalance
+		);
+
+		// an existing holder can still receive less than the minimum.
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1000,
+			100,
+			1000,
+			100,
+			user,
+		));
+	});
+}
+
+#[test]
+fn amount_in_and_out_round_trip_in_favour_of_the_pool() {
+	new_test_ext().execute_with(|| {
+		// with an exact division, rounding `get_amount_in` up must not add one on top.
+		let fee = Permill::zero();
+		assert_eq!(AssetConversion::get_amount_out(&1000, &1000, &1000, fee).ok(), Some(500));
+		assert_eq!(AssetConversion::get_amount_in(&500, &1000, &1000, fee).ok(), Some(1000));
+
+		for fee in [Permill::zero(), FEE_TIER, Permill::from_percent(3)] {
+			for (reserve_in, reserve_out, amount) in sample_swaps() {
+				if amount > reserve_in.max(reserve_out) {
+					continue
+				}
+
+				if let Ok(amount_out) =
+					AssetConversion::get_amount_out(&amount, &reserve_in, &reserve_out, fee)
+				{
+					if amount_out > 0 {
+						let amount_in = AssetConversion::get_amount_in(
+							&amount_out,
+							&reserve_in,
+							&reserve_out,
+							fee,
+						)
+						.unwrap();
+						assert!(amount_in <= amount, "{amount_in} > {amount}");
+					}
+				}
+
+				if amount < reserve_out {
+					let amount_in =
+						AssetConversion::get_amount_in(&amount, &reserve_in, &reserve_out, fee)
+							.unwrap();
+					let amount_out =
+						AssetConversion::get_amount_out(&amount_in, &reserve_in, &reserve_out, fee)
+							.unwrap();
+					assert!(amount_out >= amount, "{amount_out} < {amount}");
+				}
+			}
+		}
+	});
+}
+
+#[test]
+fn swaps_cannot_move_the_price_beyond_the_cap() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			1000,
+			10000,
+			1000,
+			user,
+		));
+
+		MaxPriceMovePerSwap::set(&Some(Permill::from_percent(10)));
+
+		// selling 2000 into 10000 moves the price by about 30%, even though the trader would
+		// accept any amount out.
+		assert_noop!(
+			AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, token_2],
+				2000,
+				1,
+				user,
+				false,
+			),
+			Error::<Test>::PriceMoveTooLarge
+		);
+		assert_noop!(
+			AssetConversion::swap_tokens_for_exact_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, token_2],
+				166,
+				10000,
+				user,
+				false,
+			),
+			Error::<Test>::PriceMoveTooLarge
+		);
+
+		// small swaps are fine.
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			100,
+			1,
+			user,
+			false,
+		));
+
+		// without a cap the large swap goes through.
+		MaxPriceMovePerSwap::set(&None);
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			2000,
+			1,
+			user,
+			false,
+		));
+	});
+}
+
+#[test]
+fn remove_liquidity_single_cannot_move_the_price_beyond_the_cap() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			1000,
+			10000,
+			1000,
+			user,
+		));
+		let lp_to_burn = pool_balance(user, lp_token) / 2;
+
+		MaxPriceMovePerSwap::set(&Some(Permill::from_percent(10)));
+
+		// the asset side of half the pool is swapped into the other half.
+		assert_noop!(
+			AssetConversion::remove_liquidity_single(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				lp_to_burn,
+				token_1,
+				1,
+				user,
+			),
+			Error::<Test>::PriceMoveTooLarge
+		);
+
+		MaxPriceMovePerSwap::set(&None);
+		assert_ok!(AssetConversion::remove_liquidity_single(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			lp_to_burn,
+			token_1,
+			1,
+			user,
+		));
+	});
+}
+
+#[test]
+fn pool_info_stays_within_its_encoded_len_budget() {
+	use codec::{Encode, MaxEncodedLen};
+
+	// see the docs of `PoolInfo` before raising this.
+	const POOL_INFO_MAX_ENCODED_LEN_BUDGET: usize = 32;
+
+	type MockPoolInfo = PoolInfo<<Test as Config>::PoolAssetId, BlockNumberFor<Test>>;
+	assert!(MockPoolInfo::max_encoded_len() <= POOL_INFO_MAX_ENCODED_LEN_BUDGET);
+
+	// all the fields are fixed-size, so the bound is tight.
+	let pool_info = MockPoolInfo {
+		lp_token: u32::MAX,
+		last_swap_block: u64::MAX,
+		created_at: u64::MAX,
+		fee_tier: Permill::one(),
+	};
+	assert_eq!(pool_info.encode().len(), MockPoolInfo::max_encoded_len());
+}
+
+#[test]
+fn can_swap_in_pool_by_lp_token() {
+	let swap = |by_lp_token: bool| {
+		new_test_ext().execute_with(|| {
+			let user = 1;
+			let token_1 = NativeOrAssetId::Native;
+			let token_2 = NativeOrAssetId::Asset(2);
+
+			create_tokens(user, vec![token_2]);
+			let lp_token = AssetConversion::get_next_pool_asset_id();
+			assert_ok!(AssetConversion::create_pool(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER
+			));
+
+			assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+			assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 2000));
+			assert_ok!(AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				10000,
+				1000,
+				10000,
+				1000,
+				user,
+			));
+
+			let before = balance(user, token_1);
+			if by_lp_token {
+				assert_noop!(
+					AssetConversion::swap_exact_tokens_for_tokens_in_pool(
+						RuntimeOrigin::signed(user),
+						lp_token + 1,
+						token_2,
+						100,
+						1,
+						user,
+						false,
+					),
+					Error::<Test>::PoolNotFound
+				);
+				assert_noop!(
+					AssetConversion::swap_exact_tokens_for_tokens_in_pool(
+						RuntimeOrigin::signed(user),
+						lp_token,
+						NativeOrAssetId::Asset(3),
+						100,
+						1,
+						user,
+						false,
+					),
+					Error::<Test>::AssetNotInPool
+				);
+				assert_ok!(AssetConversion::swap_exact_tokens_for_tokens_in_pool(
+					RuntimeOrigin::signed(user),
+					lp_token,
+					token_2,
+					100,
+					1,
+					user,
+					false,
+				));
+			} else {
+				assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+					RuntimeOrigin::signed(user),
+					bvec![token_2, token_1],
+					100,
+					1,
+					user,
+					false,
+				));
+			}
+			balance(user, token_1) - before
+		})
+	};
+
+	let received = swap(true);
+	assert!(received > 0);
+	assert_eq!(received, swap(false));
+}
+
+#[test]
+fn large_price_impact_is_reported() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			1000,
+			10000,
+			1000,
+			user,
+		));
+
+		ImpactAlertThreshold::set(&Permill::from_percent(10));
+		let alerts = || {
+			events()
+				.into_iter()
+				.filter_map(|e| match e {
+					Event::<Test>::LargePriceImpact { pool_id, impact } => Some((pool_id, impact)),
+					_ => None,
+				})
+				.collect::<Vec<_>>()
+		};
+
+		// a small swap doesn't alert.
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			100,
+			1,
+			user,
+			false,
+		));
+		assert_eq!(alerts(), vec![]);
+
+		// a large one does, but still goes through.
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			2000,
+			1,
+			user,
+			false,
+		));
+		let alerts = alerts();
+		assert_eq!(alerts.len(), 1);
+		assert_eq!(alerts[0].0, pool_id);
+		assert!(alerts[0].1 > Permill::from_percent(10));
+	});
+}
+
+#[test]
+fn rounding_up_and_down_differ_by_at_most_one() {
+	new_test_ext().execute_with(|| {
+		for (reserve_in, reserve_out, amount) in sample_swaps() {
+			if amount >= reserve_out {
+				continue
+			}
+
+			let quote = |rounding| {
+				AssetConversion::quote_with_rounding(&amount, &reserve_in, &reserve_out, rounding)
+					.unwrap()
+			};
+			let amount_out = |rounding| {
+				AssetConversion::get_amount_out_with_rounding(
+					&amount,
+					&reserve_in,
+					&reserve_out,
+					FEE_TIER,
+					rounding,
+				)
+				.unwrap()
+			};
+			let amount_in = |rounding| {
+				AssetConversion::get_amount_in_with_rounding(
+					&amount,
+					&reserve_in,
+					&reserve_out,
+					FEE_TIER,
+					rounding,
+				)
+				.unwrap()
+			};
+
+			for f in [&quote as &dyn Fn(Rounding) -> u128, &amount_out, &amount_in] {
+				let (down, up) = (f(Rounding::Down), f(Rounding::Up));
+				assert!(down <= up && up - down <= 1, "{down} vs {up}");
+				for nearest in [f(Rounding::NearestPrefDown), f(Rounding::NearestPrefUp)] {
+					assert!(down <= nearest && nearest <= up);
+				}
+			}
+		}
+
+		// the defaults round in favour of the pool.
+		assert_eq!(AssetConversion::quote(&1, &3, &2).ok(), Some(0));
+		assert_eq!(
+			AssetConversion::quote_with_rounding(&1, &3, &2, Rounding::NearestPrefDown).ok(),
+			Some(1)
+		);
+		assert_eq!(
+			AssetConversion::get_amount_out(&100, &1000, &1000, FEE_TIER).ok(),
+			AssetConversion::get_amount_out_with_rounding(
+				&100,
+				&1000,
+				&1000,
+				FEE_TIER,
+				Rounding::Down
+			)
+			.ok()
+		);
+		assert_eq!(
+			AssetConversion::get_amount_in(&100, &1000, &1000, FEE_TIER).ok(),
+			AssetConversion::get_amount_in_with_rounding(
+				&100,
+				&1000,
+				&1000,
+				FEE_TIER,
+				Rounding::Up
+			)
+			.ok()
+		);
+	});
+}
+
+#[test]
+fn healthy_dex_reports_all_pools_solvent() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+		let token_4 = NativeOrAssetId::Asset(4);
+
+		create_tokens(user, vec![token_2, token_3, token_4]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_3,
+			FEE_TIER
+		));
+		// never gets any liquidity.
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_4,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 10000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 3, user, 10000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			1000,
+			10000,
+			1000,
+			user,
+		));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_3,
+			FEE_TIER,
+			1000,
+			1000,
+			1000,
+			1000,
+			user,
+		));
+
+		let health = AssetConversion::dex_health(None, 10);
+		assert_eq!(health.pools, 3);
+		assert_eq!(health.solvency.len(), 3);
+		assert!(health.solvency.iter().all(|(_, solvent)| *solvent));
+		// twice 10000 native, plus twice 1000 of asset 2 at 10 native each.
+		assert_eq!(health.total_value_locked, 2 * 10000 + 2 * 10000);
+
+		// the pools can be paged through.
+		let first = AssetConversion::dex_health(None, 1);
+		assert_eq!(first.pools, 1);
+		let start_key = Pools::<Test>::hashed_key_for(&first.solvency[0].0);
+		let rest = AssetConversion::dex_health(Some(start_key), 10);
+		assert_eq!(rest.pools, 2);
+		assert_eq!(first.total_value_locked + rest.total_value_locked, health.total_value_locked);
+	});
+}
+
+#[test]
+fn internal_swaps_take_named_amounts() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		let expected_in = AssetConversion::get_amount_in(&50, &10000, &200, FEE_TIER).unwrap();
+		let exact_out = |amount_in_max| SwapExactOutParams { amount_out: 50, amount_in_max };
+		assert!(matches!(
+			AssetConversion::do_swap_tokens_for_exact_tokens(
+				user,
+				bvec![token_1, token_2],
+				exact_out(Some(expected_in - 1)),
+				user,
+				false,
+			),
+			Err(e) if e == Error::<Test>::ProvidedMaximumNotSufficientForSwap.into()
+		));
+		assert_eq!(
+			AssetConversion::do_swap_tokens_for_exact_tokens(
+				user,
+				bvec![token_1, token_2],
+				exact_out(Some(expected_in)),
+				user,
+				false,
+			),
+			Ok(expected_in)
+		);
+
+		let (reserve_1, reserve_2) =
+			AssetConversion::get_reserves(&token_1, &token_2, FEE_TIER).unwrap();
+		let expected_out =
+			AssetConversion::get_amount_out(&1000, &reserve_1, &reserve_2, FEE_TIER).unwrap();
+		assert_eq!(
+			AssetConversion::do_swap_exact_tokens_for_tokens(
+				user,
+				bvec![token_1, token_2],
+				SwapExactInParams { amount_in: 1000, amount_out_min: None },
+				user,
+				false,
+			),
+			Ok(expected_out)
+		);
+	});
+}
+
+#[test]
+fn native_and_asset_reserves_follow_the_pool_orientation() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+
+		create_tokens(user, vec![token_2, token_3]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		// created with the native asset second, but stored with it first.
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER
+		));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_3,
+			FEE_TIER
+		));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER,
+			200,
+			10000,
+			1,
+			1,
+			user,
+		));
+
+		let pool_id = AssetConversion::get_pool_id(token_2, token_1, FEE_TIER);
+		assert_eq!(AssetConversion::asset_side(&pool_id), Some(2));
+		assert_eq!(AssetConversion::native_reserve(&pool_id), Some(10000));
+		assert_eq!(AssetConversion::asset_reserve(&pool_id), Some(200));
+
+		let pool_id = AssetConversion::get_pool_id(token_2, token_3, FEE_TIER);
+		assert_eq!(AssetConversion::asset_side(&pool_id), None);
+		assert_eq!(AssetConversion::native_reserve(&pool_id), None);
+		assert_eq!(AssetConversion::asset_reserve(&pool_id), None);
+	});
+}
+
+#[test]
+fn locked_liquidity_can_not_be_moved_until_the_lock_ends() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let user2 = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		System::set_block_number(1);
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+		let lp_token = Pools::<Test>::get(&pool_id).unwrap().lp_token;
+		assert_eq!(pool_balance(user, lp_token), 1314);
+
+		assert_noop!(
+			AssetConversion::lock_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				1000,
+				1
+			),
+			Error::<Test>::LockInThePast
+		);
+		assert_noop!(
+			AssetConversion::lock_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				1000,
+				102
+			),
+			Error::<Test>::LockTooLong
+		);
+		assert_noop!(
+			AssetConversion::lock_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				2000,
+				51
+			),
+			Error::<Test>::InsufficientLpTokens
+		);
+		assert_eq!(AssetConversion::boost_multiplier(&user, &pool_id), FixedU128::one());
+
+		assert_ok!(AssetConversion::lock_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1000,
+			51
+		));
+		assert!(events().contains(&Event::<Test>::LiquidityLocked {
+			who: user,
+			pool_id,
+			lp_amount: 1000,
+			lock_until: 51,
+		}));
+		// locked for half the `MaxLockDuration`.
+		assert_eq!(
+			AssetConversion::boost_multiplier(&user, &pool_id),
+			FixedU128::from_rational(3, 2)
+		);
+
+		// only the unlocked lp tokens can be moved.
+		assert!(PoolAssets::transfer(RuntimeOrigin::signed(user), lp_token, user2, 400).is_err());
+		assert!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			400,
+			1,
+			1,
+			user,
+		)
+		.is_err());
+		assert_ok!(PoolAssets::transfer(RuntimeOrigin::signed(user), lp_token, user2, 300));
+
+		System::set_block_number(50);
+		assert_noop!(
+			AssetConversion::unlock_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER
+			),
+			Error::<Test>::LiquidityStillLocked
+		);
+		assert!(PoolAssets::transfer(RuntimeOrigin::signed(user), lp_token, user2, 100).is_err());
+
+		System::set_block_number(51);
+		assert_eq!(AssetConversion::boost_multiplier(&user, &pool_id), FixedU128::one());
+		assert_ok!(AssetConversion::unlock_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert!(events().contains(&Event::<Test>::LiquidityUnlocked {
+			who: user,
+			pool_id,
+			lp_amount: 1000,
+		}));
+		assert_ok!(PoolAssets::transfer(RuntimeOrigin::signed(user), lp_token, user2, 1000));
+		assert_noop!(
+			AssetConversion::unlock_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER
+			),
+			Error::<Test>::NoLockedLiquidity
+		);
+	});
+}
+
+#[test]
+fn amount_out_does_not_overflow_for_large_reserves() {
+	new_test_ext().execute_with(|| {
+		let amount_in = 10u128.pow(20);
+		let reserve_in = 10u128.pow(24);
+		let reserve_out = 10u128.pow(30);
+		// `amount_in * 997 * reserve_out` does not fit in a `u128`, the result does.
+		assert!(amount_in.checked_mul(997).unwrap().checked_mul(reserve_out).is_none());
+
+		assert_eq!(
+			AssetConversion::get_amount_out(&amount_in, &reserve_in, &reserve_out, FEE_TIER).ok(),
+			Some(99690060900928177460707167)
+		);
+	});
+}
+
+#[test]
+fn can_create_pool_with_liquidity() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let user2 = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		// 1414 lp tokens are minted, 100 of which are locked in the pool.
+		assert_noop!(
+			AssetConversion::create_pool_with_liquidity(
+				RuntimeOrigin::signed(user),
+				token_2,
+				token_1,
+				FEE_TIER,
+				200,
+				10000,
+				1315,
+				user2,
+			),
+			Error::<Test>::InsufficientLiquidityMinted
+		);
+		assert_ok!(AssetConversion::create_pool_with_liquidity(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER,
+			200,
+			10000,
+			1314,
+			user2,
+		));
+
+		assert_eq!(pools(), vec![pool_id]);
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		assert_eq!(balance(pool_account, token_1), 10000);
+		assert_eq!(balance(pool_account, token_2), 200);
+		let lp_token = Pools::<Test>::get(&pool_id).unwrap().lp_token;
+		assert_eq!(pool_balance(user2, lp_token), 1314);
+	});
+}
+
+#[test]
+fn swaps_are_rate_limited_by_the_cooldown() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let user2 = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		System::set_block_number(1);
+		SwapCooldown::set(&Some(2));
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user2, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		let swap = |who| {
+			AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(who),
+				bvec![token_1, token_2],
+				1000,
+				1,
+				who,
+				false,
+			)
+		};
+		assert_ok!(swap(user));
+		assert_noop!(swap(user), Error::<Test>::SwapCooldownActive);
+		// the cooldown is per account.
+		assert_ok!(swap(user2));
+
+		System::set_block_number(2);
+		assert_noop!(swap(user), Error::<Test>::SwapCooldownActive);
+		System::set_block_number(3);
+		assert_ok!(swap(user));
+	});
+}
+
+#[test]
+fn locking_fails_if_the_lp_freeze_is_not_enforced() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		System::set_block_number(1);
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		EnforceLpFreezes::set(&false);
+		assert_noop!(
+			AssetConversion::lock_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				1000,
+				51
+			),
+			Error::<Test>::LpFreezeNotEnforced
+		);
+		assert_eq!(AssetConversion::boost_multiplier(&user, &pool_id), FixedU128::one());
+
+		EnforceLpFreezes::set(&true);
+		assert_ok!(AssetConversion::lock_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1000,
+			51
+		));
+	});
+}
+
+#[test]
+fn reserves_can_be_read_at_a_past_block() {
+	let user = 1;
+	let token_1 = NativeOrAssetId::Native;
+	let token_2 = NativeOrAssetId::Asset(2);
+	let pool_id = (token_1, token_2, FEE_TIER);
+
+	let mut ext = new_test_ext();
+	ext.execute_with(|| {
+		System::set_block_number(1);
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+	});
+	// keep the state of block 1 around, as an archive node would.
+	ext.commit_all().unwrap();
+	let (block_1_state, block_1_root) = ext.into_raw_snapshot();
+	let at_block =
+		|state, root| sp_io::TestExternalities::from_raw_snapshot(state, root, Default::default());
+
+	let mut ext = at_block(block_1_state.clone(), block_1_root);
+	ext.execute_with(|| {
+		System::set_block_number(2);
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			1000,
+			1,
+			user,
+			false,
+		));
+		assert_eq!(AssetConversion::pool_reserves(&pool_id), Some((11000, 182)));
+	});
+
+	at_block(block_1_state, block_1_root).execute_with(|| {
+		assert_eq!(AssetConversion::pool_reserves(&pool_id), Some((10000, 200)));
+		assert_eq!(
+			AssetConversion::pool_reserves(&(token_1, NativeOrAssetId::Asset(3), FEE_TIER)),
+			None
+		);
+	});
+}
+
+#[test]
+fn idle_blocks_compound_the_treasury_liquidity() {
+	use frame_support::{
+		traits::Hooks,
+		weights::{RuntimeDbWeight, Weight},
+	};
+
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let treasury: u128 = ProtocolTreasury::get();
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		System::set_block_number(1);
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10_000_000));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), treasury, 1000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1_000_000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1_000_000,
+			100_000,
+			1,
+			1,
+			treasury,
+		));
+		let lp_token = Pools::<Test>::get(&pool_id).unwrap().lp_token;
+		let treasury_lp = pool_balance(treasury, lp_token);
+		System::set_block_number(2);
+
+		// turned off.
+		assert_eq!(AssetConversion::on_idle(2, Weight::MAX), Weight::zero());
+		assert_eq!(CompoundStates::<Test>::get(&pool_id), None);
+
+		AutoCompoundTreasury::set(&Some(treasury));
+		// not enough weight left to visit a pool.
+		AssetConversion::on_idle(
+			2,
+			<<Test as frame_system::Config>::DbWeight as Get<RuntimeDbWeight>>::get()
+				.reads_writes(1, 1),
+		);
+		assert_eq!(CompoundStates::<Test>::get(&pool_id), None);
+
+		// the first visit only records where the compounding starts from.
+		AssetConversion::on_idle(2, Weight::MAX);
+		assert_eq!(CompoundStates::<Test>::get(&pool_id).unwrap().next_at, 12);
+		assert_eq!(pool_balance(treasury, lp_token), treasury_lp);
+
+		// trading accrues fees to the pool.
+		System::set_block_number(3);
+		for path in [bvec![token_1, token_2], bvec![token_2, token_1]] {
+			assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				path,
+				10_000,
+				1,
+				user,
+				false,
+			));
+		}
+		let native_before = balance(treasury, token_1);
+
+		// the `AutoCompoundPeriod` is not over yet.
+		System::set_block_number(11);
+		AssetConversion::on_idle(11, Weight::MAX);
+		assert_eq!(pool_balance(treasury, lp_token), treasury_lp);
+
+		System::set_block_number(12);
+		AssetConversion::on_idle(12, Weight::MAX);
+		let native_after = balance(treasury, token_1);
+		let lp_token_burned = treasury_lp - pool_balance(treasury, lp_token);
+		assert!(lp_token_burned > 0);
+		// only the fees of the two swaps are realized, worth about 330 of the native asset, not a
+		// slice of the liquidity.
+		assert!(native_after > native_before && native_after - native_before < 1_000);
+		assert!(events().contains(&Event::<Test>::FeesCompounded {
+			pool_id,
+			lp_token_burned,
+			native_received: native_after - native_before,
+		}));
+		// the non-native side is swapped into native as well.
+		assert_eq!(balance(treasury, token_2), 0);
+
+		// no fees have accrued since, the swap of the compounding itself included.
+		System::set_block_number(22);
+		AssetConversion::on_idle(22, Weight::MAX);
+		assert_eq!(pool_balance(treasury, lp_token), treasury_lp - lp_token_burned);
+		assert_eq!(balance(treasury, token_1), native_after);
+	});
+}
+
+#[test]
+fn compounding_waits_for_an_unswapped_block_and_bounds_its_swap() {
+	use frame_support::{traits::Hooks, weights::Weight};
+
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let treasury: u128 = ProtocolTreasury::get();
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+		let swap = || {
+			assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_2, token_1],
+				10_000,
+				1,
+				user,
+				false,
+			));
+		};
+
+		System::set_block_number(1);
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10_000_000));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), treasury, 1000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1_000_000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1_000_000,
+			100_000,
+			1,
+			1,
+			treasury,
+		));
+		let lp_token = Pools::<Test>::get(&pool_id).unwrap().lp_token;
+		let treasury_lp = pool_balance(treasury, lp_token);
+		AutoCompoundTreasury::set(&Some(treasury));
+
+		// a swap in the block may have moved the price, so the pool is left alone.
+		System::set_block_number(2);
+		swap();
+		AssetConversion::on_idle(2, Weight::MAX);
+		assert_eq!(CompoundStates::<Test>::get(&pool_id), None);
+
+		System::set_block_number(3);
+		AssetConversion::on_idle(3, Weight::MAX);
+		assert!(CompoundStates::<Test>::get(&pool_id).is_some());
+		System::set_block_number(4);
+		swap();
+
+		// with the fee of the pool, the swap always gets less than at the spot price.
+		System::set_block_number(13);
+		AutoCompoundMaxSlippage::set(&Permill::zero());
+		events();
+		AssetConversion::on_idle(13, Weight::MAX);
+		assert_eq!(pool_balance(treasury, lp_token), treasury_lp);
+		assert_eq!(balance(treasury, token_2), 0);
+		assert_eq!(
+			events(),
+			[Event::<Test>::CompoundFailed {
+				pool_id,
+				error: Error::<Test>::ProvidedMinimumNotSufficientForSwap.into(),
+			}]
+		);
+		let state = CompoundStates::<Test>::get(&pool_id).unwrap();
+		assert_eq!((state.failures, state.next_at), (1, 33));
+
+		// the retries back off, and only the first failure in a row is reported.
+		System::set_block_number(32);
+		AssetConversion::on_idle(32, Weight::MAX);
+		assert_eq!(CompoundStates::<Test>::get(&pool_id).unwrap(), state);
+		System::set_block_number(33);
+		AssetConversion::on_idle(33, Weight::MAX);
+		assert!(events().is_empty());
+		let state = CompoundStates::<Test>::get(&pool_id).unwrap();
+		assert_eq!((state.failures, state.next_at), (2, 73));
+
+		AutoCompoundMaxSlippage::set(&Permill::from_percent(2));
+		System::set_block_number(73);
+		AssetConversion::on_idle(73, Weight::MAX);
+		assert!(pool_balance(treasury, lp_token) < treasury_lp);
+		assert_eq!(balance(treasury, token_2), 0);
+		assert_eq!(CompoundStates::<Test>::get(&pool_id).unwrap().failures, 0);
+	});
+}
+
+#[cfg(feature = "nft-pools")]
+#[test]
+fn nft_instances_are_ordered_after_all_other_assets() {
+	new_test_ext().execute_with(|| {
+		let native = NativeOrAssetId::Native;
+		let asset = NativeOrAssetId::Asset(u32::MAX);
+		let nft = NativeOrAssetId::Class(1, 2);
+
+		assert!(native < nft && asset < nft);
+		assert!(
+			nft < NativeOrAssetId::Class(1, 3) &&
+				NativeOrAssetId::Class(1, 3) < NativeOrAssetId::Class(2, 0)
+		);
+		assert_eq!(AssetConversion::get_pool_id(nft, asset, FEE_TIER), (asset, nft));
+		assert_eq!(AssetConversion::get_pool_id(nft, native, FEE_TIER), (native, nft));
+	});
+}
+
+#[cfg(feature = "nft-pools")]
+#[test]
+fn nft_transfers_are_unsupported_without_a_handler() {
+	new_test_ext().execute_with(|| {
+		let nft = NativeOrAssetId::Class(1, 2);
+		assert_eq!(
+			NativeOrAssetIdConverter::<u32>::try_convert(&nft),
+			MultiAssetIdConversionResult::Unsupported(nft)
+		);
+		assert_eq!(
+			<<Test as Config>::NftHandler as NftHandler<u128, _, u128>>::transfer(&nft, &1, &2, 1),
+			Err(TokenError::Unsupported.into())
+		);
+	});
+}
+
+#[test]
+fn swap_events_report_what_was_paid_and_received() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		let amounts = || {
+			events()
+				.into_iter()
+				.find_map(|e| match e {
+					Event::<Test>::SwapExecuted { amount_in, amount_out, .. } =>
+						Some((amount_in, amount_out)),
+					_ => None,
+				})
+				.unwrap()
+		};
+
+		let native_before = balance(user, token_1);
+		let asset_before = balance(user, token_2);
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			100,
+			1,
+			user,
+			true,
+		));
+		assert_eq!(
+			amounts(),
+			(native_before - balance(user, token_1), balance(user, token_2) - asset_before)
+		);
+
+		let native_before = balance(user, token_1);
+		let asset_before = balance(user, token_2);
+		assert_ok!(AssetConversion::swap_tokens_for_exact_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			1,
+			1000,
+			user,
+			true,
+		));
+		let (amount_in, amount_out) = amounts();
+		assert_eq!(amount_out, 1);
+		assert!(amount_in > amount_out);
+		assert_eq!(
+			(amount_in, amount_out),
+			(native_before - balance(user, token_1), balance(user, token_2) - asset_before)
+		);
+	});
+}
+
+#[test]
+fn lp_balance_changes_are_reported() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let user2 = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000000000));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user2, 1000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1000000000,
+			100000,
+			1,
+			1,
+			user2,
+		));
+		let minted = pool_balance(user2, lp_token);
+		assert_eq!(LpBalanceChanges::get(), vec![(user2, pool_id, BalanceDelta::Increase(minted))]);
+
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user2),
+			token_1,
+			token_2,
+			FEE_TIER,
+			minted / 2,
+			0,
+			0,
+			user,
+		));
+		assert_eq!(
+			LpBalanceChanges::get(),
+			vec![
+				(user2, pool_id, BalanceDelta::Increase(minted)),
+				(user2, pool_id, BalanceDelta::Decrease(minted / 2)),
+			]
+		);
+	});
+}
+
+#[test]
+fn reserve_changes_are_reported() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+		let last_reserves = || {
+			let reserves = AssetConversion::pool_reserves(&pool_id).unwrap();
+			assert_eq!(ReserveChanges::get().last(), Some(&(pool_id, reserves.0, reserves.1)));
+			ReserveChanges::set(&vec![]);
+			reserves
+		};
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert!(ReserveChanges::get().is_empty());
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000000000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1000000000,
+			100000,
+			1,
+			1,
+			user,
+		));
+		assert_eq!(last_reserves(), (1000000000, 100000));
+
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			100000,
+			1,
+			user,
+			false,
+		));
+		let (reserve1, reserve2) = last_reserves();
+		assert_eq!(reserve1, 1000000000 + 100000);
+
+		assert_ok!(AssetConversion::swap_tokens_for_exact_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1],
+			5000,
+			100,
+			user,
+			false,
+		));
+		assert_eq!(last_reserves().0, reserve1 - 5000);
+
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			pool_balance(user, lp_token) / 2,
+			0,
+			0,
+			user,
+		));
+		assert!(last_reserves().1 < reserve2);
+	});
+}
+
+#[test]
+fn quotes_read_the_pool_once_per_dispatch() {
+	use frame_support::{dispatch_context::run_in_context, storage::unhashed};
+
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2, token_3]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		let quote =
+			|| AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_2, 3000, true);
+		let quoted = quote();
+		assert!(quoted.is_some());
+
+		// removing the raw `PoolInfo` shows whether a quote reads it.
+		let pool_key = Pools::<Test>::hashed_key_for(&pool_id);
+		let pool_info = unhashed::get_raw(&pool_key).unwrap();
+
+		// outside of a dispatch, e.g. through the runtime api, every quote reads the pool.
+		unhashed::kill(&pool_key);
+		assert_eq!(quote(), None);
+		unhashed::put_raw(&pool_key, &pool_info);
+
+		// a consumer quoting the pool many times in the same dispatch only reads it once, while
+		// the reserves are still read by every quote.
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		run_in_context(|| {
+			assert_eq!(quote(), quoted);
+			unhashed::kill(&pool_key);
+			for _ in 0..10 {
+				assert_eq!(quote(), quoted);
+			}
+			assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, pool_account, 100));
+			assert!(quote() > quoted);
+		});
+
+		// the next dispatch starts with an empty cache.
+		run_in_context(|| assert_eq!(quote(), None));
+		unhashed::put_raw(&pool_key, &pool_info);
+		let quoted = quote();
+
+		// creating a pool clears the cache.
+		run_in_context(|| {
+			assert_eq!(quote(), quoted);
+			unhashed::kill(&pool_key);
+			assert_eq!(quote(), quoted);
+			assert_ok!(AssetConversion::create_pool(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_3,
+				FEE_TIER
+			));
+			assert_eq!(quote(), None);
+		});
+	});
+}
+
+#[test]
+fn scaling_low_decimal_reserves_does_not_change_swap_amounts() {
+	new_test_ext().execute_with(|| {
+		// 1000 units of a 12 decimal native asset against 1000 units of a 2 decimal asset.
+		let native_reserve = 1_000_000_000_000_000u128;
+		let asset_reserve = 100_000u128;
+		let scale = 10_000_000_000u128;
+
+		for amount_in in [1, 999_999_999, 1_500_000_000_000, 7_777_777_777_777] {
+			let amount_out = AssetConversion::get_amount_out(
+				&amount_in,
+				&native_reserve,
+				&asset_reserve,
+				FEE_TIER,
+			)
+			.unwrap();
+			// the exact output, rounded down once.
+			let exact = amount_in * 997 * asset_reserve / (native_reserve * 1000 + amount_in * 997);
+			assert_eq!(amount_out, exact);
+
+			// tracking the 2 decimal reserve at 12 decimals, and scaling the output back down,
+			// gets the same amount.
+			let scaled_out = AssetConversion::get_amount_out(
+				&amount_in,
+				&native_reserve,
+				&(asset_reserve * scale),
+				FEE_TIER,
+			)
+			.unwrap();
+			assert_eq!(scaled_out / scale, amount_out);
+		}
+	});
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "a pool with lp tokens in circulation must have both reserves")]
+fn pool_invariant_catches_an_emptied_reserve() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+		AssetConversion::ensure_pool_invariant(&pool_id);
+
+		// the asset admin takes the whole reserve out of the pool behind its back.
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		assert_ok!(Assets::burn(RuntimeOrigin::signed(user), 2, pool_account, 200));
+		AssetConversion::ensure_pool_invariant(&pool_id);
+	});
+}
+
+#[test]
+fn add_liquidity_respects_the_lp_mint_cap() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		MaxLpMintPerTx::set(&Some(1000));
+		let add = |amount1, amount2| {
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				amount1,
+				amount2,
+				1,
+				1,
+				user,
+			)
+		};
+
+		// would mint 1314 lp tokens.
+		assert_noop!(add(10000, 200), Error::<Test>::LpMintTooLarge);
+
+		// split over two deposits instead.
+		assert_ok!(add(5000, 100));
+		assert_ok!(add(5000, 100));
+		assert_eq!(pool_balance(user, lp_token), 607 + 707);
+	});
+}
+
+#[test]
+fn can_not_create_pool_with_disallowed_asset() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let banned = NativeOrAssetId::Asset(666);
+
+		create_tokens(user, vec![token_2, banned]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000));
+
+		assert_noop!(
+			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, banned, FEE_TIER),
+			Error::<Test>::AssetNotAllowed
+		);
+		assert_noop!(
+			AssetConversion::create_pool(RuntimeOrigin::signed(user), banned, token_2, FEE_TIER),
+			Error::<Test>::AssetNotAllowed
+		);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+	});
+}
+
+#[test]
+fn can_create_pools_in_a_batch() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+		let token_4 = NativeOrAssetId::Asset(4);
+
+		create_tokens(user, vec![token_2, token_3, token_4]);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000));
+
+		assert_ok!(AssetConversion::create_pools(
+			RuntimeOrigin::signed(user),
+			bvec![
+				(token_1, token_2, FEE_TIER),
+				(token_3, token_1, FEE_TIER),
+				(token_3, token_4, FEE_TIER)
+			],
+			false,
+		));
+		assert!(Pools::<Test>::contains_key((token_1, token_2, FEE_TIER)));
+		assert!(Pools::<Test>::contains_key((token_1, token_3, FEE_TIER)));
+		assert!(Pools::<Test>::contains_key((token_3, token_4, FEE_TIER)));
+
+		// an existing pool fails the batch, unless it's skipped.
+		assert_noop!(
+			AssetConversion::create_pools(
+				RuntimeOrigin::signed(user),
+				bvec![(token_1, token_4, FEE_TIER), (token_2, token_1, FEE_TIER)],
+				false,
+			),
+			Error::<Test>::PoolExists
+		);
+		assert_ok!(AssetConversion::create_pools(
+			RuntimeOrigin::signed(user),
+			bvec![(token_1, token_4, FEE_TIER), (token_2, token_1, FEE_TIER)],
+			true,
+		));
+		assert!(Pools::<Test>::contains_key((token_1, token_4, FEE_TIER)));
+		assert_eq!(Pools::<Test>::iter().count(), 4);
+	});
+}
+
+#[test]
+fn shortfalls_are_reported_before_transferring() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100));
+
+		let add = |amount1, amount2| {
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				amount1,
+				amount2,
+				1,
+				1,
+				user,
+			)
+		};
+		assert_noop!(add(10001, 100), Error::<Test>::InsufficientAssetOneBalance);
+		// keeping the existential deposit is left to the transfer. The deposit the user holds for
+		// the pool's asset account makes it report the native balance as frozen.
+		assert_noop!(add(10000, 100), DispatchError::Token(TokenError::Frozen));
+		assert_noop!(add(5000, 101), Error::<Test>::InsufficientAssetTwoBalance);
+		// given in the other order, the errors still refer to the pool's order.
+		assert_noop!(
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_2,
+				token_1,
+				FEE_TIER,
+				101,
+				5000,
+				1,
+				1,
+				user,
+			),
+			Error::<Test>::InsufficientAssetTwoBalance
+		);
+		assert_ok!(add(5000, 50));
+
+		assert_noop!(
+			AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_2, token_1],
+				51,
+				1,
+				user,
+				false,
+			),
+			Error::<Test>::InsufficientBalance
+		);
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1],
+			50,
+			1,
+			user,
+			false,
+		));
+	});
+}
+
+#[test]
+fn keep_alive_policy_decides_if_asset_accounts_survive() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let user2 = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let ed = get_ed();
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		for who in [user, user2] {
+			assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), who, 10000 + ed));
+			assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, who, 100));
+		}
+		let add = |who, amount2| {
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(who),
+				token_1,
+				token_2,
+				FEE_TIER,
+				10000,
+				amount2,
+				1,
+				1,
+				who,
+			)
+		};
+
+		// all of the asset can be taken, only the native existential deposit stays.
+		assert_ok!(add(user, 100));
+		assert_eq!(balance(user, token_1), ed);
+		assert!(Assets::maybe_balance(2, user).is_none());
+		assert!(System::account_exists(&user));
+
+		// the asset keeps its minimum balance as well.
+		SwapKeepAlivePolicy::set(&KeepAlivePolicy::AllAssets);
+		assert_noop!(add(user2, 100), DispatchError::Token(TokenError::NotExpendable));
+		assert_ok!(add(user2, 99));
+		assert_eq!(balance(user2, token_1), 10000 + ed - 9900);
+		assert_eq!(balance(user2, token_2), 1);
+	});
+}
+
+#[test]
+fn larger_trades_have_a_larger_price_impact() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1000000,
+			1000000,
+			1,
+			1,
+			user,
+		));
+
+		let impact = |amount_in| {
+			AssetConversion::price_impact(token_1, token_2, FEE_TIER, amount_in).unwrap()
+		};
+
+		// a small trade pays little more than the 0.3% fee.
+		assert_eq!(impact(1000), Permill::from_parts(4000));
+		assert!(impact(1000) < impact(10000));
+		assert!(impact(10000) < impact(100000));
+		assert!(impact(100000) < impact(1000000));
+		// half of the output is left when trading as much as the whole reserve.
+		assert_eq!(impact(1000000), Permill::from_parts(500752));
+
+		assert_eq!(AssetConversion::price_impact(token_1, token_2, FEE_TIER, 0), None);
+		assert_eq!(AssetConversion::price_impact(token_1, token_3, FEE_TIER, 1000), None);
+	});
+}
+
+fn setup_permit_pool(owner: u128) -> u32 {
+	let token_1 = NativeOrAssetId::Native;
+	let token_2 = NativeOrAssetId::Asset(2);
+	create_tokens(owner, vec![token_2]);
+	let lp_token = AssetConversion::get_next_pool_asset_id();
+	assert_ok!(AssetConversion::create_pool(
+		RuntimeOrigin::signed(owner),
+		token_1,
+		token_2,
+		FEE_TIER
+	));
+	assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), owner, 100000));
+	assert_ok!(Assets::mint(RuntimeOrigin::signed(owner), 2, owner, 1000));
+	assert_ok!(AssetConversion::add_liquidity(
+		RuntimeOrigin::signed(owner),
+		token_1,
+		token_2,
+		FEE_TIER,
+		10000,
+		200,
+		10000,
+		200,
+		owner,
+	));
+	lp_token
+}
+
+#[test]
+fn can_remove_liquidity_with_a_signed_permit() {
+	new_test_ext().execute_with(|| {
+		let owner = 1;
+		let relayer = 2;
+		let receiver = 3;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let lp_token = setup_permit_pool(owner);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), relayer, 1000));
+		let lp = pool_balance(owner, lp_token);
+
+		let permit = RemoveLiquidityPermit {
+			asset1: token_1,
+			asset2: token_2,
+			fee_tier: FEE_TIER,
+			lp_token_burn: lp / 2,
+			amount1_min_receive: 0,
+			amount2_min_receive: 0,
+			withdraw_to: receiver,
+			deadline: System::block_number() + 10,
+			nonce: 0,
+		};
+		let signature = MockSignature(owner, AssetConversion::permit_payload(&permit));
+		assert_ok!(AssetConversion::remove_liquidity_with_signature(
+			RuntimeOrigin::signed(relayer),
+			owner,
+			permit.clone(),
+			signature.clone(),
+		));
+		assert_eq!(pool_balance(owner, lp_token), lp - lp / 2);
+		assert!(balance(receiver, token_2) > 0);
+		assert_eq!(DexNonces::<Test>::get(owner), 1);
+
+		// the same permit can't be used twice.
+		assert_noop!(
+			AssetConversion::remove_liquidity_with_signature(
+				RuntimeOrigin::signed(relayer),
+				owner,
+				permit.clone(),
+				signature,
+			),
+			Error::<Test>::InvalidNonce
+		);
+
+		// a permit wrapped into `<Bytes>` by the signing UI is accepted too.
+		let permit = RemoveLiquidityPermit { nonce: 1, ..permit };
+		let payload = AssetConversion::permit_payload(&permit);
+		let wrapped = [b"<Bytes>".to_vec(), payload, b"</Bytes>".to_vec()].concat();
+		assert_ok!(AssetConversion::remove_liquidity_with_signature(
+			RuntimeOrigin::signed(relayer),
+			owner,
+			permit,
+			MockSignature(owner, wrapped),
+		));
+		assert_eq!(DexNonces::<Test>::get(owner), 2);
+	});
+}
+
+#[test]
+fn expired_or_forged_permits_are_rejected() {
+	new_test_ext().execute_with(|| {
+		let owner = 1;
+		let relayer = 2;
+		let lp_token = setup_permit_pool(owner);
+		let lp = pool_balance(owner, lp_token);
+
+		let permit = RemoveLiquidityPermit {
+			asset1: NativeOrAssetId::Native,
+			asset2: NativeOrAssetId::Asset(2),
+			fee_tier: FEE_TIER,
+			lp_token_burn: lp,
+			amount1_min_receive: 0,
+			amount2_min_receive: 0,
+			withdraw_to: relayer,
+			deadline: System::block_number() + 1,
+			nonce: 0,
+		};
+
+		// signed by someone other than the owner.
+		assert_noop!(
+			AssetConversion::remove_liquidity_with_signature(
+				RuntimeOrigin::signed(relayer),
+				owner,
+				permit.clone(),
+				MockSignature(relayer, AssetConversion::permit_payload(&permit)),
+			),
+			Error::<Test>::WrongSignature
+		);
+
+		// signed by the owner, but over different terms.
+		let tampered = RemoveLiquidityPermit { withdraw_to: 3, ..permit.clone() };
+		assert_noop!(
+			AssetConversion::remove_liquidity_with_signature(
+				RuntimeOrigin::signed(relayer),
+				owner,
+				permit.clone(),
+				MockSignature(owner, AssetConversion::permit_payload(&tampered)),
+			),
+			Error::<Test>::WrongSignature
+		);
+
+		// signed over the bare permit, or for another chain.
+		assert_noop!(
+			AssetConversion::remove_liquidity_with_signature(
+				RuntimeOrigin::signed(relayer),
+				owner,
+				permit.clone(),
+				MockSignature(owner, permit.encode()),
+			),
+			Error::<Test>::WrongSignature
+		);
+		let other_chain = (PERMIT_DOMAIN, sp_core::H256::repeat_byte(1), &permit).encode();
+		assert_ne!(other_chain, AssetConversion::permit_payload(&permit));
+		assert_noop!(
+			AssetConversion::remove_liquidity_with_signature(
+				RuntimeOrigin::signed(relayer),
+				owner,
+				permit.clone(),
+				MockSignature(owner, other_chain),
+			),
+			Error::<Test>::WrongSignature
+		);
+
+		// past the deadline.
+		System::set_block_number(System::block_number() + 2);
+		assert_noop!(
+			AssetConversion::remove_liquidity_with_signature(
+				RuntimeOrigin::signed(relayer),
+				owner,
+				permit.clone(),
+				MockSignature(owner, AssetConversion::permit_payload(&permit)),
+			),
+			Error::<Test>::DeadlineExpired
+		);
+		assert_eq!(pool_balance(owner, lp_token), lp);
+		assert_eq!(DexNonces::<Test>::get(owner), 0);
+	});
+}
+
+#[test]
+fn the_pallet_account_is_not_a_valid_recipient() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let lp_token = setup_permit_pool(user);
+		let lp = pool_balance(user, lp_token);
+		let pallet_account = AssetConversion::account_id();
+
+		assert_noop!(
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				1000,
+				20,
+				1,
+				1,
+				pallet_account,
+			),
+			Error::<Test>::InvalidRecipient
+		);
+		assert_noop!(
+			AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, token_2],
+				100,
+				1,
+				pallet_account,
+				false,
+			),
+			Error::<Test>::InvalidRecipient
+		);
+		assert_noop!(
+			AssetConversion::swap_tokens_for_exact_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, token_2],
+				1,
+				1000,
+				pallet_account,
+				false,
+			),
+			Error::<Test>::InvalidRecipient
+		);
+		assert_noop!(
+			AssetConversion::remove_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				lp,
+				0,
+				0,
+				pallet_account,
+			),
+			Error::<Test>::InvalidRecipient
+		);
+		assert_noop!(
+			AssetConversion::remove_liquidity_single(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				lp,
+				token_1,
+				0,
+				pallet_account,
+			),
+			Error::<Test>::InvalidRecipient
+		);
+	});
+}
+
+#[test]
+fn event_verbosity_decides_the_detail_of_swap_and_withdrawal_events() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let lp_token = setup_permit_pool(user);
+		let lp = pool_balance(user, lp_token);
+
+		let swap_and_withdraw = || {
+			assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, token_2],
+				100,
+				1,
+				user,
+				false,
+			));
+			assert_ok!(AssetConversion::remove_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				lp / 4,
+				0,
+				0,
+				user,
+			));
+		};
+
+		// the full level includes the reserve changes, fees and limits.
+		System::reset_events();
+		swap_and_withdraw();
+		let full = events();
+		assert!(full
+			.iter()
+			.any(|e| matches!(e, Event::<Test>::SwapExecuted { amount_out_min: Some(1), .. })));
+		assert!(full.iter().any(|e| matches!(
+			e,
+			Event::<Test>::LiquidityRemoved { withdrawal_fee, .. }
+				if *withdrawal_fee == <Test as Config>::LiquidityWithdrawalFee::get()
+		)));
+		assert!(!full.iter().any(|e| matches!(
+			e,
+			Event::<Test>::SwapExecutedCompact { .. } |
+				Event::<Test>::LiquidityRemovedCompact { .. }
+		)));
+
+		// the minimal level omits them.
+		EventVerbosity::set(&EventLevel::Minimal);
+		System::reset_events();
+		swap_and_withdraw();
+		let minimal = events();
+		assert!(minimal
+			.iter()
+			.any(|e| matches!(e, Event::<Test>::SwapExecutedCompact { amount_in: 100, .. })));
+		assert!(minimal.iter().any(|e| matches!(
+			e,
+			Event::<Test>::LiquidityRemovedCompact { lp_token_burned, .. } if *lp_token_burned == lp / 4
+		)));
+		assert!(!minimal.iter().any(|e| matches!(
+			e,
+			Event::<Test>::SwapExecuted { .. } | Event::<Test>::LiquidityRemoved { .. }
+		)));
+	});
+}
+
+#[test]
+fn swap_trait_rejects_paths_longer_than_the_max() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		setup_permit_pool(user);
+		let path = vec![
+			NativeOrAssetId::Native,
+			NativeOrAssetId::Asset(2),
+			NativeOrAssetId::Native,
+			NativeOrAssetId::Asset(2),
+			NativeOrAssetId::Native,
+		];
+		assert!(path.len() > <<Test as Config>::MaxSwapPathLength as Get<u32>>::get() as usize);
+
+		assert_noop!(
+			<AssetConversion as Swap<_, _, _>>::swap_exact_tokens_for_tokens(
+				user,
+				path.clone(),
+				U256::from(100),
+				None,
+				user,
+				false,
+			),
+			Error::<Test>::PathTooLong
+		);
+		assert_noop!(
+			<AssetConversion as Swap<_, _, _>>::swap_tokens_for_exact_tokens(
+				user,
+				path,
+				U256::from(1),
+				None,
+				user,
+				false,
+			),
+			Error::<Test>::PathTooLong
+		);
+	});
+}
+
+#[test]
+fn lp_pnl_reports_the_impermanent_loss_of_a_position() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let trader = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10_000_000));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), trader, 10_000_000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100_000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1_000_000,
+			20_000,
+			1,
+			1,
+			user,
+		));
+		let lp = pool_balance(user, lp_token);
+		assert_eq!(
+			LpPositions::<Test>::get(user, pool_id),
+			Some(LpPosition { lp_amount: lp, amount1: 1_000_000, amount2: 20_000 })
+		);
+		assert_eq!(AssetConversion::lp_pnl(&trader, &pool_id), None);
+
+		// before the price moves, only the locked minimum liquidity separates the two.
+		let pnl = AssetConversion::lp_pnl(&user, &pool_id).unwrap();
+		assert!(pnl.value_if_withdrawn <= pnl.value_if_held);
+		assert!(pnl.value_if_held - pnl.value_if_withdrawn < pnl.value_if_held / 1000);
+
+		// the price of the asset in native roughly quadruples.
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(trader),
+			bvec![token_1, token_2],
+			1_000_000,
+			1,
+			trader,
+			false,
+		));
+
+		// a 4x price move loses 1 - 2 * sqrt(4) / (1 + 4) = 20% against holding.
+		let pnl = AssetConversion::lp_pnl(&user, &pool_id).unwrap();
+		assert!(pnl.value_if_withdrawn < pnl.value_if_held);
+		let loss =
+			Permill::from_rational(pnl.value_if_held - pnl.value_if_withdrawn, pnl.value_if_held);
+		assert!(loss > Permill::from_percent(19) && loss < Permill::from_percent(21));
+
+		// withdrawing half keeps the loss of the rest.
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			lp / 2,
+			1,
+			1,
+			user,
+		));
+		let half = AssetConversion::lp_pnl(&user, &pool_id).unwrap();
+		assert!(half.value_if_withdrawn < half.value_if_held);
+		assert!(half.value_if_held.abs_diff(pnl.value_if_held / 2) < pnl.value_if_held / 10_000);
+
+		// withdrawing the rest closes the position.
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			lp - lp / 2,
+			1,
+			1,
+			user,
+		));
+		assert_eq!(LpPositions::<Test>::get(user, pool_id), None);
+		assert_eq!(AssetConversion::lp_pnl(&user, &pool_id), None);
+	});
+}
+
+#[test]
+fn quotes_for_assets_without_a_pool_are_none() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_3 = NativeOrAssetId::Asset(3);
+		setup_permit_pool(user);
+
+		for id in [3, 1_000_000, u32::MAX] {
+			let token = NativeOrAssetId::Asset(id);
+			for include_fee in [false, true] {
+				assert_eq!(
+					AssetConversion::quote_price_exact_tokens_for_tokens(
+						token_1,
+						token,
+						1000,
+						include_fee
+					),
+					None
+				);
+				assert_eq!(
+					AssetConversion::quote_price_tokens_for_exact_tokens(
+						token,
+						token_1,
+						10,
+						include_fee
+					),
+					None
+				);
+			}
+		}
+
+		// funds sent to the account of a pool that was never created don't make it quotable.
+		create_tokens(user, vec![token_3]);
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 3, user, 1000));
+		let phantom = AssetConversion::get_pool_account(&(token_1, token_3, FEE_TIER));
+		assert_ok!(Balances::transfer_allow_death(RuntimeOrigin::signed(user), phantom, 10000));
+		assert_ok!(Assets::transfer(RuntimeOrigin::signed(user), 3, phantom, 200));
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_3, 1000, false),
+			None
+		);
+		assert_eq!(
+			AssetConversion::quote_price_tokens_for_exact_tokens(token_3, token_1, 10, true),
+			None
+		);
+	});
+}
+
+#[test]
+fn optimal_swap_fraction_balances_a_single_sided_deposit() {
+	new_test_ext().execute_with(|| {
+		let fee = FEE_TIER;
+		for (amount_in, reserve_in, reserve_out) in [
+			(10_000_000_000u128, 1_000_000_000_000u128, 500_000_000_000u128),
+			(1_000_000, 1_000_000, 3_000_000),
+			(123_456_789, 987_654_321, 55_555),
+			(1_000_000_000_000_000_000, 1_000_000_000_000_000, 1_000_000_000_000_000),
+		] {
+			let swap =
+				AssetConversion::optimal_swap_fraction(&amount_in, &reserve_in, fee).unwrap();
+
+			// the rationalized root, which doesn't lose precision to cancellation.
+			let (a, r, g) = (amount_in as f64, reserve_in as f64, 0.997);
+			let reference =
+				2.0 * a * r / ((((1.0 + g) * r).powi(2) + 4.0 * g * a * r).sqrt() + (1.0 + g) * r);
+			assert!((swap as f64 - reference).abs() <= 1.0 + reference / 1e12);
+
+			// what's left of `amount_in` matches the output in the pool's new ratio, up to the
+			// rounding of a single unit of the swap.
+			let out = AssetConversion::get_amount_out(&swap, &reserve_in, &reserve_out, FEE_TIER)
+				.unwrap();
+			let needed = AssetConversion::quote(
+				&(amount_in - swap),
+				&(reserve_in + swap),
+				&(reserve_out - out),
+			)
+			.unwrap();
+			let tolerance = 2 * (reserve_out / reserve_in + 1) + out / 1_000_000;
+			assert!(needed.abs_diff(out) <= tolerance);
+		}
+
+		assert_eq!(AssetConversion::optimal_swap_fraction(&0, &1000, fee).ok(), Some(0));
+		assert!(AssetConversion::optimal_swap_fraction(&1000, &0, fee).is_err());
+	});
+}
+
+#[test]
+fn lopsided_deposits_can_be_rejected() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10_000_000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			1_000_000,
+			100,
+			1,
+			1,
+			user,
+		));
+
+		// 29_999 is matched with 2 of the second asset, rounded down from 2.9999. The native
+		// side is worth 299 lp tokens, but only the 200 of the other side are minted.
+		let add = || {
+			AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token_2,
+				FEE_TIER,
+				29_999,
+				100,
+				1,
+				1,
+				user,
+			)
+		};
+		MaxLpRatioImbalance::set(&Some(Permill::from_percent(1)));
+		assert_noop!(add(), Error::<Test>::RatioImbalance);
+
+		// a deposit matching the ratio exactly passes.
+		let lp_before = pool_balance(user, lp_token);
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			20_000,
+			100,
+			1,
+			1,
+			user,
+		));
+		assert_eq!(pool_balance(user, lp_token), lp_before + 200);
+
+		// without a limit, the lopsided deposit goes through.
+		MaxLpRatioImbalance::set(&None);
+		let lp_before = pool_balance(user, lp_token);
+		assert_ok!(add());
+		assert_eq!(pool_balance(user, lp_token), lp_before + 200);
+	});
+}
+
+#[test]
+fn lp_redemption_value_matches_what_remove_liquidity_pays() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let lp_token = setup_permit_pool(user);
+		let lp = pool_balance(user, lp_token);
+		LiquidityWithdrawalFee::set(&Permill::from_percent(1));
+
+		let burn = lp / 3;
+		let (value2, value1) =
+			AssetConversion::lp_redemption_value(token_2, token_1, FEE_TIER, burn).unwrap();
+		assert!(value1 > 0 && value2 > 0);
+		assert_eq!(
+			AssetConversion::lp_redemption_value(token_1, token_2, FEE_TIER, burn).ok(),
+			Some((value1, value2))
+		);
+
+		let native_before = balance(user, token_1);
+		let asset_before = balance(user, token_2);
+		assert_ok!(AssetConversion::remove_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			burn,
+			0,
+			0,
+			user,
+		));
+		assert_eq!(balance(user, token_1) - native_before, value1);
+		assert_eq!(balance(user, token_2) - asset_before, value2);
+
+		assert!(AssetConversion::lp_redemption_value(
+			token_1,
+			NativeOrAssetId::Asset(3),
+			FEE_TIER,
+			burn
+		)
+		.is_err());
+	});
+}
+
+#[test]
+fn try_swap_skips_legs_of_a_batch_that_cant_fill() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		setup_permit_pool(user);
+		let expected_out =
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_2, 100, true)
+				.unwrap();
+
+		let native_before = balance(user, token_1);
+		let asset_before = balance(user, token_2);
+		System::reset_events();
+
+		// like `batch_all`, the legs run in a storage layer that any failing leg reverts.
+		assert_ok!(frame_support::storage::with_storage_layer(|| -> DispatchResult {
+			// the first leg can't fill its minimum and is skipped.
+			AssetConversion::try_swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, token_2],
+				100,
+				expected_out + 1,
+				user,
+				false,
+			)?;
+			// the second one fills.
+			AssetConversion::try_swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, token_2],
+				100,
+				expected_out,
+				user,
+				false,
+			)
+		}));
+
+		// only the second leg took funds.
+		assert_eq!(balance(user, token_1), native_before - 100);
+		assert_eq!(balance(user, token_2), asset_before + expected_out);
+		let events = events();
+		assert!(events.contains(&Event::<Test>::SwapSkipped {
+			who: user,
+			path: bvec![token_1, token_2],
+			amount_in: 100,
+			amount_out_min: expected_out + 1,
+		}));
+		assert_eq!(
+			events
+				.iter()
+				.filter(|e| matches!(e, Event::<Test>::SwapExecuted { .. }))
+				.count(),
+			1
+		);
+
+		// other errors still fail the leg.
+		assert_noop!(
+			AssetConversion::try_swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, NativeOrAssetId::Asset(3)],
+				100,
+				1,
+				user,
+				false,
+			),
+			Error::<Test>::PoolNotFound
+		);
+	});
+}
+
+#[test]
+fn can_force_close_the_pools_of_an_asset() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let user2 = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
+		let pool_id_2 = (token_1, token_2, FEE_TIER);
+		let pool_id_3 = (token_1, token_3, FEE_TIER);
+		let pool_id_23 = (token_2, token_3, FEE_TIER);
+
+		create_tokens(user, vec![token_2, token_3]);
+		let lp_token_2 = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_3,
+			FEE_TIER
+		));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_3,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 20000 + get_ed()));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user2, 10000 + get_ed()));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 2000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 3, user, 2000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user2, 1000));
+		for (who, asset1, asset2, amount1, amount2) in [
+			(user, token_1, token_2, 10000, 100),
+			(user2, token_1, token_2, 10000, 100),
+			(user, token_1, token_3, 10000, 100),
+			(user, token_2, token_3, 1000, 1000),
+		] {
+			assert_ok!(AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(who),
+				asset1,
+				asset2,
+				FEE_TIER,
+				amount1,
+				amount2,
+				amount1,
+				amount2,
+				who,
+			));
+		}
+		let lp_user = pool_balance(user, lp_token_2);
+		let lp_user2 = pool_balance(user2, lp_token_2);
+
+		assert_noop!(
+			AssetConversion::force_close_asset_pools(RuntimeOrigin::signed(user), token_2, 1, 10),
+			DispatchError::BadOrigin
+		);
+		// the native asset is in every pool.
+		assert_noop!(
+			AssetConversion::force_close_asset_pools(RuntimeOrigin::root(), token_1, 10, 10),
+			Error::<Test>::CannotCloseNativePools
+		);
+
+		// only the pools containing the asset are closed, `limit` at a time.
+		assert_ok!(AssetConversion::force_close_asset_pools(RuntimeOrigin::root(), token_2, 1, 10));
+		assert_eq!(
+			[pool_id_2, pool_id_23]
+				.iter()
+				.filter(|id| Pools::<Test>::contains_key(id))
+				.count(),
+			1
+		);
+		assert_ok!(AssetConversion::force_close_asset_pools(RuntimeOrigin::root(), token_2, 1, 10));
+		assert!(events().contains(&Event::<Test>::PoolForceClosed {
+			pool_id: pool_id_2,
+			lp_token: lp_token_2,
+			amount1: 20000,
+			amount2: 200,
+		}));
+		assert!(Pools::<Test>::get(&pool_id_2).is_none());
+		assert!(Pools::<Test>::get(&pool_id_23).is_none());
+		assert!(Pools::<Test>::get(&pool_id_3).is_some());
+		assert_noop!(
+			AssetConversion::force_close_asset_pools(RuntimeOrigin::root(), token_2, 0, 10),
+			Error::<Test>::ZeroLimit
+		);
+		// the scan continues after the last pool read and starts over once it is done.
+		assert_ok!(AssetConversion::force_close_asset_pools(RuntimeOrigin::root(), token_2, 1, 10));
+		assert!(ForceCloseCursor::<Test>::get().is_none());
+		assert_noop!(
+			AssetConversion::force_close_asset_pools(RuntimeOrigin::root(), token_2, 1, 10),
+			Error::<Test>::PoolNotFound
+		);
+
+		// the locked lp tokens are burned, the rest redeem the reserves pro-rata.
+		assert_eq!(PoolAssets::total_supply(lp_token_2), lp_user + lp_user2);
+		assert_noop!(
+			AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2, FEE_TIER),
+			Error::<Test>::PoolClosed
+		);
+		let native_before = balance(user, token_1);
+		assert_ok!(AssetConversion::redeem_closed_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER,
+			lp_user,
+			user,
+		));
+		let amount1 = 20000 * lp_user / (lp_user + lp_user2);
+		let amount2 = 200 * lp_user / (lp_user + lp_user2);
+		assert_eq!(balance(user, token_1), native_before + amount1);
+		assert!(events().contains(&Event::<Test>::ClosedPoolRedeemed {
+			who: user,
+			withdraw_to: user,
+			pool_id: pool_id_2,
+			lp_token_burned: lp_user,
+			amount1,
+			amount2,
+		}));
+
+		// the last holder gets everything left.
+		let native_before = balance(user2, token_1);
+		assert_ok!(AssetConversion::redeem_closed_pool(
+			RuntimeOrigin::signed(user2),
+			token_1,
+			token_2,
+			FEE_TIER,
+			lp_user2,
+			user2,
+		));
+		assert_eq!(balance(user2, token_1), native_before + 20000 - amount1);
+		assert_eq!(balance(user2, token_2), 1000 - 100 + 200 - amount2);
+		let pool_account = AssetConversion::get_pool_account(&pool_id_2);
+		assert_eq!(balance(pool_account, token_1), 0);
+		assert_eq!(balance(pool_account, token_2), 0);
+		assert!(ClosedPools::<Test>::get(&pool_id_2).is_none());
+
+		// the pool can be created again.
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+	});
+}
+
+#[test]
+fn closed_pool_pays_out_the_native_share_when_the_asset_is_frozen() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let user2 = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		let lp_token = AssetConversion::get_next_pool_asset_id();
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + get_ed()));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user2, 10000 + get_ed()));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user2, 1000));
+		for who in [user, user2] {
+			assert_ok!(AssetConversion::add_liquidity(
+				RuntimeOrigin::signed(who),
+				token_1,
+				token_2,
+				FEE_TIER,
+				10000,
+				100,
+				10000,
+				100,
+				who,
+			));
+		}
+		let lp_user = pool_balance(user, lp_token);
+		let lp_user2 = pool_balance(user2, lp_token);
+
+		assert_ok!(AssetConversion::force_close_asset_pools(RuntimeOrigin::root(), token_2, 1, 10));
+		// the owner of the malicious asset freezes the reserve of the closed pool.
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		assert_ok!(Assets::freeze(RuntimeOrigin::signed(user), 2, pool_account));
+
+		let native_before = balance(user, token_1);
+		let asset_before = balance(user, token_2);
+		assert_ok!(AssetConversion::redeem_closed_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			lp_user,
+			user,
+		));
+		let amount1 = 20000 * lp_user / (lp_user + lp_user2);
+		let amount2 = 200 * lp_user / (lp_user + lp_user2);
+		assert_eq!(balance(user, token_1), native_before + amount1);
+		assert_eq!(balance(user, token_2), asset_before);
+		let redeem_events = events();
+		assert!(redeem_events.iter().any(|event| matches!(
+			event,
+			Event::<Test>::ClosedPoolPayoutFailed { withdraw_to, asset, amount, .. }
+				if *withdraw_to == user && *asset == token_2 && *amount == amount2
+		)));
+		assert!(redeem_events.contains(&Event::<Test>::ClosedPoolRedeemed {
+			who: user,
+			withdraw_to: user,
+			pool_id,
+			lp_token_burned: lp_user,
+			amount1,
+			amount2: 0,
+		}));
+
+		// the share that could not be paid out stays with the remaining holders.
+		assert_ok!(Assets::thaw(RuntimeOrigin::signed(user), 2, pool_account));
+		assert_ok!(AssetConversion::redeem_closed_pool(
+			RuntimeOrigin::signed(user2),
+			token_1,
+			token_2,
+			FEE_TIER,
+			lp_user2,
+			user2,
+		));
+		assert_eq!(balance(user2, token_2), 1000 - 100 + 200);
+		assert!(ClosedPools::<Test>::get(&pool_id).is_none());
+	});
+}
+
+#[test]
+fn force_close_asset_pools_reads_at_most_max_visited_pools() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let tokens = [2, 3, 4, 5].map(NativeOrAssetId::Asset);
+		let pool_id_2 = (token_1, tokens[0], FEE_TIER);
+
+		create_tokens(user, tokens.to_vec());
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		for token in tokens {
+			assert_ok!(AssetConversion::create_pool(
+				RuntimeOrigin::signed(user),
+				token_1,
+				token,
+				FEE_TIER
+			));
+		}
+
+		// one pool is read per call, so the pool is found within as many calls as there are pools.
+		let mut calls = 0;
+		while Pools::<Test>::contains_key(&pool_id_2) {
+			assert_ok!(AssetConversion::force_close_asset_pools(
+				RuntimeOrigin::root(),
+				tokens[0],
+				1,
+				1
+			));
+			calls += 1;
+			assert!(calls <= tokens.len());
+		}
+		assert_eq!(Pools::<Test>::iter_keys().count(), tokens.len() - 1);
+
+		while ForceCloseCursor::<Test>::get().is_some() {
+			assert_ok!(AssetConversion::force_close_asset_pools(
+				RuntimeOrigin::root(),
+				tokens[0],
+				1,
+				1
+			));
+		}
+		assert_eq!(Pools::<Test>::iter_keys().count(), tokens.len() - 1);
+		assert_noop!(
+			AssetConversion::force_close_asset_pools(RuntimeOrigin::root(), tokens[0], 1, 10),
+			Error::<Test>::PoolNotFound
+		);
+		assert_noop!(
+			AssetConversion::force_close_asset_pools(RuntimeOrigin::root(), tokens[0], 1, 0),
+			Error::<Test>::ZeroLimit
+		);
+	});
+}
+
+#[test]
+fn swaps_pay_the_network_fee() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let receiver = 500;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 20000 + get_ed()));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), receiver, get_ed()));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		SwapNetworkFee::set(&10);
+		NetworkFeeReceiver::set(&Some(receiver));
+
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			100,
+			1,
+			user,
+			false,
+		));
+		assert_eq!(balance(receiver, token_1), get_ed() + 10);
+
+		assert_ok!(AssetConversion::swap_tokens_for_exact_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1],
+			100,
+			10,
+			user,
+			false,
+		));
+		assert_eq!(balance(receiver, token_1), get_ed() + 20);
+
+		// the sender must afford both the input and the fee. The deposit the user holds for the
+		// pool's asset account keeps the existential deposit out of reach.
+		let spendable = balance(user, token_1) - get_ed();
+		assert_noop!(
+			AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, token_2],
+				spendable - 5,
+				1,
+				user,
+				false,
+			),
+			DispatchError::Token(TokenError::Frozen)
+		);
+	});
+}
+
+#[test]
+fn pool_created_reports_the_account_holding_the_reserves() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_1,
+			FEE_TIER
+		));
+		let pool_account = events()
+			.into_iter()
+			.find_map(|event| match event {
+				Event::<Test>::PoolCreated { pool_account, .. } => Some(pool_account),
+				_ => None,
+			})
+			.unwrap();
+		// each pool has its own account, distinct from the pallet account.
+		assert_ne!(pool_account, AssetConversion::account_id());
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + get_ed()));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			100,
+			10000,
+			100,
+			user,
+		));
+
+		assert_eq!(
+			AssetConversion::pool_reserves(&pool_id),
+			Some((balance(pool_account, token_1), balance(pool_account, token_2)))
+		);
+		assert_eq!(balance(pool_account, token_2), 100);
+	});
+}
+
+#[test]
+fn can_quote_both_directions() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		let (reserve1, reserve2) =
+			AssetConversion::get_reserves(&token_1, &token_2, FEE_TIER).unwrap();
+		let amount = 100;
+		let (a1_to_a2, a2_to_a1) =
+			AssetConversion::quote_both_directions(token_1, token_2, FEE_TIER, amount).unwrap();
+		assert_eq!(a1_to_a2, amount * reserve2 / reserve1);
+		assert_eq!(a2_to_a1, amount * reserve1 / reserve2);
+		assert_eq!(
+			Some(a1_to_a2),
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_2, amount, false)
+		);
+		assert_eq!(
+			Some(a2_to_a1),
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_2, token_1, amount, false)
+		);
+
+		// swapping the assets swaps the quotes.
+		assert_eq!(
+			AssetConversion::quote_both_directions(token_2, token_1, FEE_TIER, amount),
+			Some((a2_to_a1, a1_to_a2))
+		);
+
+		// no quote for a pool that doesn't exist.
+		assert_eq!(
+			AssetConversion::quote_both_directions(
+				token_1,
+				NativeOrAssetId::Asset(3),
+				FEE_TIER,
+				amount
+			),
+			None
+		);
+	});
+}
+
+#[test]
+fn can_quote_with_slippage() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		let expected =
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_2, 3000, true)
+				.unwrap();
+		for (slippage, min_out) in [
+			(Permill::zero(), expected),
+			(Permill::from_percent(1), expected - (expected + 99) / 100),
+			(Permill::from_percent(10), expected - (expected + 9) / 10),
+			(Permill::from_percent(50), expected / 2),
+			(Permill::one(), 0),
+		] {
+			assert_eq!(
+				AssetConversion::quote_with_slippage(token_1, token_2, 3000, slippage),
+				Some((expected, min_out))
+			);
+		}
+
+		// no quote for a pool that doesn't exist.
+		assert_eq!(
+			AssetConversion::quote_with_slippage(
+				token_1,
+				NativeOrAssetId::Asset(3),
+				3000,
+				Permill::zero()
+			),
+			None
+		);
+	});
+}
+
+#[test]
+fn can_quote_price_while_asset_is_frozen() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
+			1,
+			1,
+			user,
+		));
+
+		let quote =
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_2, 3000, true);
+		assert!(quote.is_some());
+
+		// the transfers of the asset are disabled, so nothing of it can be swapped out.
+		assert_ok!(Assets::freeze_asset(RuntimeOrigin::signed(user), 2));
+		assert_noop!(
+			AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_1, token_2],
+				3000,
+				1,
+				user,
+				false,
+			),
+			Error::<Test>::EmptyPool
+		);
+
+		assert_eq!(
+			AssetConversion::quote_price_exact_tokens_for_tokens(token_1, token_2, 3000, true),
+			quote
+		);
+		assert_eq!(
+			AssetConversion::get_reserves(&token_1, &token_2, FEE_TIER).ok(),
+			Some((10000, 200))
+		);
+	});
+}
+
+#[test]
+fn can_swap_with_native() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+		let pool_id = (token_1, token_2, FEE_TIER);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		let ed = get_ed();
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + ed));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+
+		let liquidity1 = 10000;
+		let liquidity2 = 200;
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			liquidity1,
+			liquidity2,
+			1,
+			1,
+			user,
+		));
+
+		let input_amount = 100;
+		let expect_receive =
+			AssetConversion::get_amount_out(&input_amount, &liquidity2, &liquidity1, FEE_TIER)
+				.ok()
+				.unwrap();
+
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
 			RuntimeOrigin::signed(user),
-			token_1,
-			token_3,
-			10000,
-			10,
-			10000,
-			10,
+			bvec![token_2, token_1],
+			input_amount,
+			1,
 			user,
+			false,
 		));
+
+		let pallet_account = AssetConversion::get_pool_account(&pool_id);
+		assert_eq!(balance(user, token_1), expect_receive + ed);
+		assert_eq!(balance(user, token_2), 1000 - liquidity2 - input_amount);
+		assert_eq!(balance(pallet_account, token_1), liquidity1 - expect_receive);
+		assert_eq!(balance(pallet_account, token_2), liquidity2 + input_amount);
 	});
 }
 
 #[test]
-fn can_remove_liquidity() {
+fn can_swap_with_realistic_values() {
 	new_test_ext().execute_with(|| {
 		let user = 1;
-		let token_1 = NativeOrAssetId::Native;
-		let token_2 = NativeOrAssetId::Asset(2);
-		let pool_id = (token_1, token_2);
+		let dot = NativeOrAssetId::Native;
+		let usd = NativeOrAssetId::Asset(2);
+		create_tokens(user, vec![usd]);
+		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), dot, usd, FEE_TIER));
 
-		create_tokens(user, vec![token_2]);
-		let lp_token = AssetConversion::get_next_pool_asset_id();
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
+		const UNIT: u128 = 1_000_000_000;
 
-		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000000000));
-		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 100000));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 300_000 * UNIT));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1_100_000 * UNIT));
 
+		let liquidity_dot = 200_000 * UNIT; // ratio for a 5$ price
+		let liquidity_usd = 1_000_000 * UNIT;
 		assert_ok!(AssetConversion::add_liquidity(
 			RuntimeOrigin::signed(user),
-			token_1,
-			token_2,
-			1000000000,
-			100000,
-			1000000000,
-			100000,
+			dot,
+			usd,
+			FEE_TIER,
+			liquidity_dot,
+			liquidity_usd,
+			1,
+			1,
 			user,
 		));
 
-		let total_lp_received = pool_balance(user, lp_token);
-		LiquidityWithdrawalFee::set(&Permill::from_percent(10));
+		let input_amount = 10 * UNIT; // usd
 
-		assert_ok!(AssetConversion::remove_liquidity(
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
 			RuntimeOrigin::signed(user),
-			token_1,
-			token_2,
-			total_lp_received,
-			0,
-			0,
+			bvec![usd, dot],
+			input_amount,
+			1,
 			user,
+			false,
 		));
 
-		assert!(events().contains(&Event::<Test>::LiquidityRemoved {
+		assert!(events().contains(&Event::<Test>::SwapExecuted {
 			who: user,
-			withdraw_to: user,
-			pool_id,
-			amount1: 899991000,
-			amount2: 89999,
-			lp_token,
-			lp_token_burned: total_lp_received,
-			withdrawal_fee: <Test as Config>::LiquidityWithdrawalFee::get()
+			send_to: user,
+			path: bvec![usd, dot],
+			amount_in: 10 * UNIT,      // usd
+			amount_out: 1_993_980_120, // About 2 dot after div by UNIT.
+			pool_asset1_delta: BalanceDelta::Decrease(1_993_980_120),
+			pool_asset2_delta: BalanceDelta::Increase(10 * UNIT),
+			amount_out_min: Some(1),
+			amount_in_max: None,
 		}));
-
-		let pool_account = AssetConversion::get_pool_account(&pool_id);
-		assert_eq!(balance(pool_account, token_1), 100009000);
-		assert_eq!(balance(pool_account, token_2), 10001);
-		assert_eq!(pool_balance(pool_account, lp_token), 100);
-
-		assert_eq!(balance(user, token_1), 10000000000 - 1000000000 + 899991000);
-		assert_eq!(balance(user, token_2), 89999);
-		assert_eq!(pool_balance(user, lp_token), 0);
 	});
 }
 
 #[test]
-fn can_not_redeem_more_lp_tokens_than_were_minted() {
+fn swap_events_report_deltas_in_pool_orientation() {
 	new_test_ext().execute_with(|| {
 		let user = 1;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
-		let lp_token = AssetConversion::get_next_pool_asset_id();
 
 		create_tokens(user, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
-		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + get_ed()));
+		let ed = get_ed();
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 20000 + ed));
 		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
-
 		assert_ok!(AssetConversion::add_liquidity(
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
+			FEE_TIER,
 			10000,
-			10,
-			10000,
-			10,
+			200,
+			1,
+			1,
 			user,
 		));
+		System::reset_events();
+
+		let swap_event = || {
+			events()
+				.into_iter()
+				.find_map(|e| match e {
+					Event::<Test>::SwapExecuted {
+						amount_in,
+						amount_out,
+						pool_asset1_delta,
+						pool_asset2_delta,
+						..
+					} => Some((amount_in, amount_out, pool_asset1_delta, pool_asset2_delta)),
+					_ => None,
+				})
+				.unwrap()
+		};
 
-		// Only 216 lp_tokens_minted
-		assert_eq!(pool_balance(user, lp_token), 216);
+		// native in, asset out: the native reserve grows.
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			100,
+			1,
+			user,
+			false,
+		));
+		let (amount_in, amount_out, delta1, delta2) = swap_event();
+		assert_eq!(delta1, BalanceDelta::Increase(amount_in));
+		assert_eq!(delta2, BalanceDelta::Decrease(amount_out));
 
-		assert_noop!(
-			AssetConversion::remove_liquidity(
-				RuntimeOrigin::signed(user),
-				token_1,
-				token_2,
-				216 + 1, // Try and redeem 10 lp tokens while only 9 minted.
-				0,
-				0,
-				user,
-			),
-			DispatchError::Token(TokenError::FundsUnavailable)
-		);
+		// asset in, native out: the same pool orientation, reversed signs.
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1],
+			10,
+			1,
+			user,
+			false,
+		));
+		let (amount_in, amount_out, delta1, delta2) = swap_event();
+		assert_eq!(delta1, BalanceDelta::Decrease(amount_out));
+		assert_eq!(delta2, BalanceDelta::Increase(amount_in));
 	});
 }
 
 #[test]
-fn can_quote_price() {
+fn pools_become_stale_without_swaps() {
 	new_test_ext().execute_with(|| {
 		let user = 1;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
+		let token_3 = NativeOrAssetId::Asset(3);
 
 		create_tokens(user, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_eq!(AssetConversion::is_pool_stale(&token_1, &token_3, FEE_TIER, 0), None);
+		assert_eq!(AssetConversion::is_pool_stale(&token_1, &token_2, FEE_TIER, 0), Some(false));
 
-		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 20000 + get_ed()));
 		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
-
 		assert_ok!(AssetConversion::add_liquidity(
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
+			FEE_TIER,
 			10000,
 			200,
 			1,
@@ -560,137 +5795,253 @@ fn can_quote_price() {
 			user,
 		));
 
-		assert_eq!(
-			AssetConversion::quote_price_exact_tokens_for_tokens(
-				NativeOrAssetId::Native,
-				NativeOrAssetId::Asset(2),
-				3000,
-				false,
-			),
-			Some(60)
-		);
-		// Check it still gives same price:
-		// (if the above accidentally exchanged then it would not give same quote as before)
-		assert_eq!(
-			AssetConversion::quote_price_exact_tokens_for_tokens(
-				NativeOrAssetId::Native,
-				NativeOrAssetId::Asset(2),
-				3000,
-				false,
-			),
-			Some(60)
-		);
+		System::set_block_number(5);
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1],
+			10,
+			1,
+			user,
+			true,
+		));
+		assert_eq!(Pools::<Test>::get((token_1, token_2, FEE_TIER)).unwrap().last_swap_block, 5);
 
-		// Check inverse:
-		assert_eq!(
-			AssetConversion::quote_price_exact_tokens_for_tokens(
-				NativeOrAssetId::Asset(2),
-				NativeOrAssetId::Native,
-				60,
-				false,
-			),
-			Some(3000)
+		System::set_block_number(10);
+		assert_eq!(AssetConversion::is_pool_stale(&token_2, &token_1, FEE_TIER, 5), Some(false));
+		assert_eq!(AssetConversion::is_pool_stale(&token_1, &token_2, FEE_TIER, 4), Some(true));
+	});
+}
+
+#[test]
+fn migration_to_v1_sets_last_swap_block() {
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		let pool_id = (NativeOrAssetId::Native, NativeOrAssetId::Asset(2));
+		let lp_token = 7u32;
+		// a pool stored in the version 0 format, i.e. just the lp token.
+		frame_support::storage::unhashed::put(
+			&migration::v1::Pools::<Test>::hashed_key_for(&pool_id),
+			&lp_token,
 		);
+		StorageVersion::new(0).put::<AssetConversion>();
+
+		System::set_block_number(42);
+		migration::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+
+		let pool = migration::v1::Pools::<Test>::get(&pool_id).unwrap();
+		assert_eq!(pool.lp_token, lp_token);
+		assert_eq!(pool.last_swap_block, 42);
+		assert_eq!(AssetConversion::on_chain_storage_version(), 1);
 	});
 }
 
 #[test]
-fn can_swap_with_native() {
+fn migration_to_v2_takes_existing_pools_as_created_at_genesis() {
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		System::set_block_number(100);
+		let pool_id = (NativeOrAssetId::Native, NativeOrAssetId::Asset(2));
+		let old_pool = migration::v1::PoolInfoV1 { lp_token: 7u32, last_swap_block: 42u64 };
+		migration::v1::Pools::<Test>::insert(&pool_id, old_pool);
+		StorageVersion::new(1).put::<AssetConversion>();
+
+		migration::v2::MigrateToV2::<Test>::on_runtime_upgrade();
+
+		let pool = migration::v4::Pools::<Test>::get(&pool_id).unwrap();
+		assert_eq!(pool.lp_token, 7);
+		assert_eq!(pool.last_swap_block, 42);
+		assert_eq!(pool.created_at, 0);
+		assert_eq!(AssetConversion::on_chain_storage_version(), 2);
+	});
+}
+
+#[test]
+fn migration_to_v3_maps_the_lp_tokens_of_existing_pools() {
+	use frame_support::traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion};
+
+	new_test_ext().execute_with(|| {
+		let pool_id = (NativeOrAssetId::Native, NativeOrAssetId::Asset(2));
+		let pool =
+			migration::v4::PoolInfoV3 { lp_token: 7u32, last_swap_block: 42u64, created_at: 0 };
+		migration::v4::Pools::<Test>::insert(&pool_id, pool);
+		StorageVersion::new(2).put::<AssetConversion>();
+		assert_eq!(migration::v4::LpTokenToPool::<Test>::get(7), None);
+
+		migration::v3::MigrateToV3::<Test>::on_runtime_upgrade();
+
+		assert_eq!(migration::v4::LpTokenToPool::<Test>::get(7), Some(pool_id));
+		assert_eq!(AssetConversion::on_chain_storage_version(), 3);
+	});
+}
+
+#[test]
+fn migration_to_v4_puts_existing_pools_in_the_fee_tier_of_the_lp_fee() {
+	use frame_support::traits::{
+		fungibles::Create, GetStorageVersion, OnRuntimeUpgrade, StorageVersion,
+	};
+
 	new_test_ext().execute_with(|| {
 		let user = 1;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
-		let pool_id = (token_1, token_2);
-
+		let lp_token = 7u32;
 		create_tokens(user, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
 
-		let ed = get_ed();
-		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + ed));
-		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		// a pool stored in the version 3 format, its account derived from the pool id without a
+		// fee tier.
+		let old_id = (token_1, token_2);
+		let old_account =
+			<HashedPoolAccount as PoolAccountDerivation<u128, _>>::pool_account(&old_id);
+		System::inc_providers(&old_account);
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), old_account, 10_000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, old_account, 1_000));
+		assert_ok!(<PoolAssets as Create<u128>>::create(lp_token, old_account, false, 1));
+		assert_ok!(PoolAssets::mint(
+			RuntimeOrigin::signed(old_account),
+			lp_token,
+			old_account,
+			100
+		));
+		assert_ok!(PoolAssets::mint(RuntimeOrigin::signed(old_account), lp_token, user, 3_000));
+		migration::v4::Pools::<Test>::insert(
+			&old_id,
+			migration::v4::PoolInfoV3 { lp_token, last_swap_block: 42u64, created_at: 1 },
+		);
+		migration::v4::LpTokenToPool::<Test>::insert(lp_token, &old_id);
+		StorageVersion::new(3).put::<AssetConversion>();
 
-		let liquidity1 = 10000;
-		let liquidity2 = 200;
+		migration::v4::MigrateToV4::<Test, HashedPoolAccount>::on_runtime_upgrade();
 
-		assert_ok!(AssetConversion::add_liquidity(
+		let pool_id = (token_1, token_2, FEE_TIER);
+		assert_eq!(pools(), vec![pool_id]);
+		let pool = Pools::<Test>::get(&pool_id).unwrap();
+		assert_eq!(pool.lp_token, lp_token);
+		assert_eq!((pool.last_swap_block, pool.created_at), (42, 1));
+		assert_eq!(pool.fee_tier, FEE_TIER);
+		assert_eq!(AssetConversion::pool_of_lp_token(lp_token), Some(pool_id));
+		assert_eq!(AssetConversion::on_chain_storage_version(), 4);
+
+		// the funds of the pool moved to the account of the new pool id.
+		let pool_account = AssetConversion::get_pool_account(&pool_id);
+		assert_eq!(balance(old_account, token_1), 0);
+		assert_eq!(balance(old_account, token_2), 0);
+		assert_eq!(pool_balance(old_account, lp_token), 0);
+		assert_eq!(balance(pool_account, token_1), 10_000);
+		assert_eq!(balance(pool_account, token_2), 1_000);
+		assert_eq!(pool_balance(pool_account, lp_token), 100);
+		assert_eq!(
+			AssetConversion::get_reserves(&token_1, &token_2, FEE_TIER).ok(),
+			Some((10_000, 1_000))
+		);
+
+		// and can be swapped against and withdrawn.
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 1_000));
+		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_1, token_2],
+			100,
+			1,
+			user,
+			false,
+		));
+		assert_ok!(AssetConversion::remove_liquidity(
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
-			liquidity1,
-			liquidity2,
+			FEE_TIER,
+			3_000,
 			1,
 			1,
 			user,
 		));
+	});
+}
 
-		let input_amount = 100;
-		let expect_receive =
-			AssetConversion::get_amount_out(&input_amount, &liquidity2, &liquidity1)
-				.ok()
-				.unwrap();
+#[test]
+fn pools_record_their_creation_block() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
 
-		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+		create_tokens(user, vec![token_2]);
+		assert_eq!(AssetConversion::pool_created_at(&token_1, &token_2, FEE_TIER), None);
+
+		System::set_block_number(7);
+		assert_ok!(AssetConversion::create_pool(
 			RuntimeOrigin::signed(user),
-			bvec![token_2, token_1],
-			input_amount,
-			1,
-			user,
-			false,
+			token_1,
+			token_2,
+			FEE_TIER
 		));
+		System::set_block_number(20);
 
-		let pallet_account = AssetConversion::get_pool_account(&pool_id);
-		assert_eq!(balance(user, token_1), expect_receive + ed);
-		assert_eq!(balance(user, token_2), 1000 - liquidity2 - input_amount);
-		assert_eq!(balance(pallet_account, token_1), liquidity1 - expect_receive);
-		assert_eq!(balance(pallet_account, token_2), liquidity2 + input_amount);
+		assert_eq!(AssetConversion::pool_created_at(&token_1, &token_2, FEE_TIER), Some(7));
+		assert_eq!(AssetConversion::pool_created_at(&token_2, &token_1, FEE_TIER), Some(7));
 	});
 }
 
 #[test]
-fn can_swap_with_realistic_values() {
+fn swap_events_echo_the_callers_limit() {
 	new_test_ext().execute_with(|| {
 		let user = 1;
-		let dot = NativeOrAssetId::Native;
-		let usd = NativeOrAssetId::Asset(2);
-		create_tokens(user, vec![usd]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), dot, usd));
-
-		const UNIT: u128 = 1_000_000_000;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
 
-		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 300_000 * UNIT));
-		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1_100_000 * UNIT));
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
-		let liquidity_dot = 200_000 * UNIT; // ratio for a 5$ price
-		let liquidity_usd = 1_000_000 * UNIT;
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 100000));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
 		assert_ok!(AssetConversion::add_liquidity(
 			RuntimeOrigin::signed(user),
-			dot,
-			usd,
-			liquidity_dot,
-			liquidity_usd,
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			200,
 			1,
 			1,
 			user,
 		));
 
-		let input_amount = 10 * UNIT; // usd
+		let limits = || {
+			events()
+				.into_iter()
+				.find_map(|e| match e {
+					Event::<Test>::SwapExecuted { amount_out_min, amount_in_max, .. } =>
+						Some((amount_out_min, amount_in_max)),
+					_ => None,
+				})
+				.unwrap()
+		};
 
 		assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
 			RuntimeOrigin::signed(user),
-			bvec![usd, dot],
-			input_amount,
+			bvec![token_1, token_2],
+			100,
 			1,
 			user,
-			false,
+			true,
 		));
+		assert_eq!(limits(), (Some(1), None));
 
-		assert!(events().contains(&Event::<Test>::SwapExecuted {
-			who: user,
-			send_to: user,
-			path: bvec![usd, dot],
-			amount_in: 10 * UNIT,      // usd
-			amount_out: 1_993_980_120, // About 2 dot after div by UNIT.
-		}));
+		assert_ok!(AssetConversion::swap_tokens_for_exact_tokens(
+			RuntimeOrigin::signed(user),
+			bvec![token_2, token_1],
+			100,
+			50,
+			user,
+			true,
+		));
+		assert_eq!(limits(), (None, Some(50)));
 	});
 }
 
@@ -702,7 +6053,12 @@ fn can_not_swap_in_pool_with_no_liquidity_added_yet() {
 		let token_2 = NativeOrAssetId::Asset(2);
 
 		create_tokens(user, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
 		// Check can't swap an empty pool
 		assert_noop!(
@@ -714,6 +6070,29 @@ fn can_not_swap_in_pool_with_no_liquidity_added_yet() {
 				user,
 				false,
 			),
+			Error::<Test>::EmptyPool
+		);
+		assert_noop!(
+			AssetConversion::swap_tokens_for_exact_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![token_2, token_1],
+				10,
+				100,
+				user,
+				false,
+			),
+			Error::<Test>::EmptyPool
+		);
+		// a pool that was never created is still reported as such.
+		assert_noop!(
+			AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(user),
+				bvec![NativeOrAssetId::Asset(3), token_1],
+				10,
+				1,
+				user,
+				false,
+			),
 			Error::<Test>::PoolNotFound
 		);
 	});
@@ -725,11 +6104,16 @@ fn check_no_panic_when_try_swap_close_to_empty_pool() {
 		let user = 1;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
-		let pool_id = (token_1, token_2);
+		let pool_id = (token_1, token_2, FEE_TIER);
 		let lp_token = AssetConversion::get_next_pool_asset_id();
 
 		create_tokens(user, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
 		let ed = get_ed();
 		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + ed));
@@ -742,6 +6126,7 @@ fn check_no_panic_when_try_swap_close_to_empty_pool() {
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
+			FEE_TIER,
 			liquidity1,
 			liquidity2,
 			1,
@@ -768,6 +6153,7 @@ fn check_no_panic_when_try_swap_close_to_empty_pool() {
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
+			FEE_TIER,
 			lp_token_minted,
 			1,
 			1,
@@ -807,9 +6193,14 @@ fn check_no_panic_when_try_swap_close_to_empty_pool() {
 
 		// The price for the last tokens should be very high
 		assert_eq!(
-			AssetConversion::get_amount_in(&(token_1_left - 1), &token_2_left, &token_1_left)
-				.ok()
-				.unwrap(),
+			AssetConversion::get_amount_in(
+				&(token_1_left - 1),
+				&token_2_left,
+				&token_1_left,
+				FEE_TIER
+			)
+			.ok()
+			.unwrap(),
 			10625
 		);
 
@@ -848,7 +6239,12 @@ fn swap_should_not_work_if_too_much_slippage() {
 		let token_2 = NativeOrAssetId::Asset(2);
 
 		create_tokens(user, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
 		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + get_ed()));
 		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
@@ -860,6 +6256,7 @@ fn swap_should_not_work_if_too_much_slippage() {
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
+			FEE_TIER,
 			liquidity1,
 			liquidity2,
 			1,
@@ -889,10 +6286,15 @@ fn can_swap_tokens_for_exact_tokens() {
 		let user = 1;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
-		let pool_id = (token_1, token_2);
+		let pool_id = (token_1, token_2, FEE_TIER);
 
 		create_tokens(user, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
 		let ed = get_ed();
 		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 20000 + ed));
@@ -909,6 +6311,7 @@ fn can_swap_tokens_for_exact_tokens() {
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
+			FEE_TIER,
 			liquidity1,
 			liquidity2,
 			1,
@@ -917,9 +6320,10 @@ fn can_swap_tokens_for_exact_tokens() {
 		));
 
 		let exchange_out = 50;
-		let expect_in = AssetConversion::get_amount_in(&exchange_out, &liquidity1, &liquidity2)
-			.ok()
-			.unwrap();
+		let expect_in =
+			AssetConversion::get_amount_in(&exchange_out, &liquidity1, &liquidity2, FEE_TIER)
+				.ok()
+				.unwrap();
 
 		assert_ok!(AssetConversion::swap_tokens_for_exact_tokens(
 			RuntimeOrigin::signed(user),
@@ -943,6 +6347,81 @@ fn can_swap_tokens_for_exact_tokens() {
 	});
 }
 
+#[test]
+fn pay_exact_native_sends_the_exact_amount() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let merchant = 2;
+		let token_1 = NativeOrAssetId::Native;
+		let token_2 = NativeOrAssetId::Asset(2);
+
+		create_tokens(user, vec![token_2]);
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+
+		let ed = get_ed();
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + ed));
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), merchant, ed));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 2000));
+
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER,
+			10000,
+			1000,
+			1,
+			1,
+			user,
+		));
+
+		let native_out = 100;
+		let expect_in = AssetConversion::get_amount_in(&native_out, &1000, &10000, FEE_TIER)
+			.ok()
+			.unwrap();
+
+		assert_noop!(
+			AssetConversion::pay_exact_native(
+				RuntimeOrigin::signed(user),
+				token_2,
+				native_out,
+				expect_in - 1,
+				merchant,
+				true,
+			),
+			Error::<Test>::ProvidedMaximumNotSufficientForSwap
+		);
+		assert_noop!(
+			AssetConversion::pay_exact_native(
+				RuntimeOrigin::signed(user),
+				token_1,
+				native_out,
+				expect_in,
+				merchant,
+				true,
+			),
+			Error::<Test>::EqualAssets
+		);
+
+		assert_ok!(AssetConversion::pay_exact_native(
+			RuntimeOrigin::signed(user),
+			token_2,
+			native_out,
+			expect_in,
+			merchant,
+			true,
+		));
+
+		assert_eq!(balance(merchant, token_1), ed + native_out);
+		assert_eq!(balance(user, token_2), 2000 - 1000 - expect_in);
+	});
+}
+
 #[test]
 fn can_swap_tokens_for_exact_tokens_when_not_liquidity_provider() {
 	new_test_ext().execute_with(|| {
@@ -950,11 +6429,16 @@ fn can_swap_tokens_for_exact_tokens_when_not_liquidity_provider() {
 		let user2 = 2;
 		let token_1 = NativeOrAssetId::Native;
 		let token_2 = NativeOrAssetId::Asset(2);
-		let pool_id = (token_1, token_2);
+		let pool_id = (token_1, token_2, FEE_TIER);
 		let lp_token = AssetConversion::get_next_pool_asset_id();
 
 		create_tokens(user2, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user2), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user2),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
 		let ed = get_ed();
 		let base1 = 10000;
@@ -976,6 +6460,7 @@ fn can_swap_tokens_for_exact_tokens_when_not_liquidity_provider() {
 			RuntimeOrigin::signed(user2),
 			token_1,
 			token_2,
+			FEE_TIER,
 			liquidity1,
 			liquidity2,
 			1,
@@ -987,9 +6472,10 @@ fn can_swap_tokens_for_exact_tokens_when_not_liquidity_provider() {
 		assert_eq!(balance(user, token_2), 0);
 
 		let exchange_out = 50;
-		let expect_in = AssetConversion::get_amount_in(&exchange_out, &liquidity1, &liquidity2)
-			.ok()
-			.unwrap();
+		let expect_in =
+			AssetConversion::get_amount_in(&exchange_out, &liquidity1, &liquidity2, FEE_TIER)
+				.ok()
+				.unwrap();
 
 		assert_ok!(AssetConversion::swap_tokens_for_exact_tokens(
 			RuntimeOrigin::signed(user),
@@ -1024,6 +6510,7 @@ fn can_swap_tokens_for_exact_tokens_when_not_liquidity_provider() {
 			RuntimeOrigin::signed(user2),
 			token_1,
 			token_2,
+			FEE_TIER,
 			lp_token_minted,
 			0,
 			0,
@@ -1041,7 +6528,12 @@ fn swap_when_existential_deposit_would_cause_reaping_but_keep_alive_set() {
 		let token_2 = NativeOrAssetId::Asset(2);
 
 		create_tokens(user2, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user2), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user2),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
 		let ed = get_ed();
 		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 101));
@@ -1052,6 +6544,7 @@ fn swap_when_existential_deposit_would_cause_reaping_but_keep_alive_set() {
 			RuntimeOrigin::signed(user2),
 			token_1,
 			token_2,
+			FEE_TIER,
 			10000,
 			200,
 			1,
@@ -1093,7 +6586,12 @@ fn swap_tokens_for_exact_tokens_should_not_work_if_too_much_slippage() {
 		let token_2 = NativeOrAssetId::Asset(2);
 
 		create_tokens(user, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
 		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 20000 + get_ed()));
 		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
@@ -1105,6 +6603,7 @@ fn swap_tokens_for_exact_tokens_should_not_work_if_too_much_slippage() {
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
+			FEE_TIER,
 			liquidity1,
 			liquidity2,
 			1,
@@ -1137,8 +6636,18 @@ fn swap_exact_tokens_for_tokens_in_multi_hops() {
 		let token_3 = NativeOrAssetId::Asset(3);
 
 		create_tokens(user, vec![token_2, token_3]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_2, token_3));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_3,
+			FEE_TIER
+		));
 
 		let ed = get_ed();
 		let base1 = 10000;
@@ -1155,6 +6664,7 @@ fn swap_exact_tokens_for_tokens_in_multi_hops() {
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
+			FEE_TIER,
 			liquidity1,
 			liquidity2,
 			1,
@@ -1165,6 +6675,7 @@ fn swap_exact_tokens_for_tokens_in_multi_hops() {
 			RuntimeOrigin::signed(user),
 			token_2,
 			token_3,
+			FEE_TIER,
 			liquidity2,
 			liquidity3,
 			1,
@@ -1173,12 +6684,14 @@ fn swap_exact_tokens_for_tokens_in_multi_hops() {
 		));
 
 		let input_amount = 500;
-		let expect_out2 = AssetConversion::get_amount_out(&input_amount, &liquidity1, &liquidity2)
-			.ok()
-			.unwrap();
-		let expect_out3 = AssetConversion::get_amount_out(&expect_out2, &liquidity2, &liquidity3)
-			.ok()
-			.unwrap();
+		let expect_out2 =
+			AssetConversion::get_amount_out(&input_amount, &liquidity1, &liquidity2, FEE_TIER)
+				.ok()
+				.unwrap();
+		let expect_out3 =
+			AssetConversion::get_amount_out(&expect_out2, &liquidity2, &liquidity3, FEE_TIER)
+				.ok()
+				.unwrap();
 
 		assert_noop!(
 			AssetConversion::swap_exact_tokens_for_tokens(
@@ -1213,8 +6726,8 @@ fn swap_exact_tokens_for_tokens_in_multi_hops() {
 			true,
 		));
 
-		let pool_id1 = (token_1, token_2);
-		let pool_id2 = (token_2, token_3);
+		let pool_id1 = (token_1, token_2, FEE_TIER);
+		let pool_id2 = (token_2, token_3, FEE_TIER);
 		let pallet_account1 = AssetConversion::get_pool_account(&pool_id1);
 		let pallet_account2 = AssetConversion::get_pool_account(&pool_id2);
 
@@ -1236,8 +6749,18 @@ fn swap_tokens_for_exact_tokens_in_multi_hops() {
 		let token_3 = NativeOrAssetId::Asset(3);
 
 		create_tokens(user, vec![token_2, token_3]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_2, token_3));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_2,
+			token_3,
+			FEE_TIER
+		));
 
 		let ed = get_ed();
 		let base1 = 10000;
@@ -1254,6 +6777,7 @@ fn swap_tokens_for_exact_tokens_in_multi_hops() {
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
+			FEE_TIER,
 			liquidity1,
 			liquidity2,
 			1,
@@ -1264,6 +6788,7 @@ fn swap_tokens_for_exact_tokens_in_multi_hops() {
 			RuntimeOrigin::signed(user),
 			token_2,
 			token_3,
+			FEE_TIER,
 			liquidity2,
 			liquidity3,
 			1,
@@ -1272,12 +6797,14 @@ fn swap_tokens_for_exact_tokens_in_multi_hops() {
 		));
 
 		let exchange_out3 = 100;
-		let expect_in2 = AssetConversion::get_amount_in(&exchange_out3, &liquidity2, &liquidity3)
-			.ok()
-			.unwrap();
-		let expect_in1 = AssetConversion::get_amount_in(&expect_in2, &liquidity1, &liquidity2)
-			.ok()
-			.unwrap();
+		let expect_in2 =
+			AssetConversion::get_amount_in(&exchange_out3, &liquidity2, &liquidity3, FEE_TIER)
+				.ok()
+				.unwrap();
+		let expect_in1 =
+			AssetConversion::get_amount_in(&expect_in2, &liquidity1, &liquidity2, FEE_TIER)
+				.ok()
+				.unwrap();
 
 		assert_ok!(AssetConversion::swap_tokens_for_exact_tokens(
 			RuntimeOrigin::signed(user),
@@ -1288,8 +6815,8 @@ fn swap_tokens_for_exact_tokens_in_multi_hops() {
 			true,
 		));
 
-		let pool_id1 = (token_1, token_2);
-		let pool_id2 = (token_2, token_3);
+		let pool_id1 = (token_1, token_2, FEE_TIER);
+		let pool_id2 = (token_2, token_3, FEE_TIER);
 		let pallet_account1 = AssetConversion::get_pool_account(&pool_id1);
 		let pallet_account2 = AssetConversion::get_pool_account(&pool_id2);
 
@@ -1318,6 +6845,7 @@ fn can_not_swap_same_asset() {
 				RuntimeOrigin::signed(user),
 				token_1,
 				token_1,
+				FEE_TIER,
 				liquidity1,
 				liquidity2,
 				1,
@@ -1358,10 +6886,22 @@ fn can_not_swap_same_asset() {
 fn validate_pool_id_sorting() {
 	new_test_ext().execute_with(|| {
 		use crate::NativeOrAssetId::{Asset, Native};
-		assert_eq!(AssetConversion::get_pool_id(Native, Asset(2)), (Native, Asset(2)));
-		assert_eq!(AssetConversion::get_pool_id(Asset(2), Native), (Native, Asset(2)));
-		assert_eq!(AssetConversion::get_pool_id(Native, Native), (Native, Native));
-		assert_eq!(AssetConversion::get_pool_id(Asset(2), Asset(1)), (Asset(1), Asset(2)));
+		assert_eq!(
+			AssetConversion::get_pool_id(Native, Asset(2), FEE_TIER),
+			(Native, Asset(2), FEE_TIER)
+		);
+		assert_eq!(
+			AssetConversion::get_pool_id(Asset(2), Native, FEE_TIER),
+			(Native, Asset(2), FEE_TIER)
+		);
+		assert_eq!(
+			AssetConversion::get_pool_id(Native, Native, FEE_TIER),
+			(Native, Native, FEE_TIER)
+		);
+		assert_eq!(
+			AssetConversion::get_pool_id(Asset(2), Asset(1), FEE_TIER),
+			(Asset(1), Asset(2), FEE_TIER)
+		);
 		assert!(Asset(2) > Asset(1));
 		assert!(Asset(1) <= Asset(1));
 		assert_eq!(Asset(1), Asset(1));
@@ -1370,6 +6910,38 @@ fn validate_pool_id_sorting() {
 	});
 }
 
+#[test]
+fn pool_id_with_flag_reports_the_callers_order() {
+	new_test_ext().execute_with(|| {
+		use crate::NativeOrAssetId::{Asset, Native};
+		assert_eq!(
+			AssetConversion::get_pool_id_with_flag(Native, Asset(2), FEE_TIER),
+			((Native, Asset(2), FEE_TIER), true)
+		);
+		assert_eq!(
+			AssetConversion::get_pool_id_with_flag(Asset(2), Native, FEE_TIER),
+			((Native, Asset(2), FEE_TIER), false)
+		);
+		assert_eq!(
+			AssetConversion::get_pool_id_with_flag(Asset(1), Asset(2), FEE_TIER),
+			((Asset(1), Asset(2), FEE_TIER), true)
+		);
+		assert_eq!(
+			AssetConversion::get_pool_id_with_flag(Asset(2), Asset(1), FEE_TIER),
+			((Asset(1), Asset(2), FEE_TIER), false)
+		);
+	});
+}
+
+#[test]
+fn native_or_asset_id_helpers_work() {
+	use crate::NativeOrAssetId::{Asset, Native};
+	assert!(Native::<u32>.is_native());
+	assert!(!Asset(1).is_native());
+	assert_eq!(Native::<u32>.as_asset(), None);
+	assert_eq!(Asset(1).as_asset(), Some(1));
+}
+
 #[test]
 fn cannot_block_pool_creation() {
 	new_test_ext().execute_with(|| {
@@ -1386,8 +6958,9 @@ fn cannot_block_pool_creation() {
 		let token_2 = NativeOrAssetId::Asset(2);
 
 		// Attacker computes the still non-existing pool account for the target pair
-		let pool_account =
-			AssetConversion::get_pool_account(&AssetConversion::get_pool_id(token_2, token_1));
+		let pool_account = AssetConversion::get_pool_account(&AssetConversion::get_pool_id(
+			token_2, token_1, FEE_TIER,
+		));
 		// And transfers the ED to that pool account
 		assert_ok!(Balances::transfer(RuntimeOrigin::signed(attacker), pool_account, ed));
 		// Then, the attacker creates 14 tokens and sends one of each to the pool account
@@ -1399,7 +6972,12 @@ fn cannot_block_pool_creation() {
 
 		// User can still create the pool
 		create_tokens(user, vec![token_2]);
-		assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(user), token_1, token_2));
+		assert_ok!(AssetConversion::create_pool(
+			RuntimeOrigin::signed(user),
+			token_1,
+			token_2,
+			FEE_TIER
+		));
 
 		// User has to transfer one Asset(2) token to the pool account (otherwise add_liquidity will
 		// fail with `AssetTwoDepositDidNotMeetMinimum`)
@@ -1412,6 +6990,7 @@ fn cannot_block_pool_creation() {
 			RuntimeOrigin::signed(user),
 			token_1,
 			token_2,
+			FEE_TIER,
 			10000,
 			100,
 			10000,
@@ -1420,3 +6999,66 @@ fn cannot_block_pool_creation() {
 		));
 	});
 }
+
+#[test]
+fn can_list_pools_in_pages() {
+	new_test_ext().execute_with(|| {
+		let user = 1;
+		let token_1 = NativeOrAssetId::Native;
+		let tokens: Vec<_> = (2..7).map(NativeOrAssetId::Asset).collect();
+
+		create_tokens(user, tokens.clone());
+		for token in tokens.iter() {
+			assert_ok!(AssetConversion::create_pool(
+				RuntimeOrigin::signed(user),
+				token_1,
+				*token,
+				FEE_TIER
+			));
+		}
+
+		let ed = get_ed();
+		assert_ok!(Balances::force_set_balance(RuntimeOrigin::root(), user, 10000 + ed));
+		assert_ok!(Assets::mint(RuntimeOrigin::signed(user), 2, user, 1000));
+		assert_ok!(AssetConversion::add_liquidity(
+			RuntimeOrigin::signed(user),
+			token_1,
+			tokens[0],
+			FEE_TIER,
+			10000,
+			10,
+			10000,
+			10,
+			user,
+		));
+
+		let mut listed = vec![];
+		let mut start_key = None;
+		loop {
+			let page = AssetConversion::list_pools(start_key, 2);
+			assert!(page.len() <= 2);
+			let Some((last_pool_id, _)) = page.last() else { break };
+			start_key = Some(Pools::<Test>::hashed_key_for(last_pool_id));
+			listed.extend(page);
+		}
+
+		assert_eq!(listed.len(), tokens.len());
+		let mut listed_ids: Vec<_> = listed.iter().map(|(pool_id, _)| *pool_id).collect();
+		listed_ids.sort();
+		assert_eq!(listed_ids, pools());
+
+		let pool_id = (token_1, tokens[0], FEE_TIER);
+		let (_, summary) = listed.iter().find(|(id, _)| *id == pool_id).unwrap();
+		assert_eq!(
+			*summary,
+			PoolInfoSummary {
+				owner: AssetConversion::get_pool_account(&pool_id),
+				lp_token: Pools::<Test>::get(pool_id).unwrap().lp_token,
+				reserve1: 10000,
+				reserve2: 10,
+			}
+		);
+
+		assert!(AssetConversion::list_pools(None, 0).is_empty());
+	});
+}
@@ -18,20 +18,257 @@
 use super::*;
 
 use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{traits::Get, weights::Weight, PalletId};
 use scale_info::TypeInfo;
+use sp_arithmetic::{FixedU128, Permill};
+use sp_runtime::{traits::AccountIdConversion, DispatchResult, RuntimeDebug};
 use sp_std::{cmp::Ordering, marker::PhantomData};
 
-/// Pool ID.
+/// Pool ID: the two assets of the pool and its fee tier, one of the `FeeTiers`.
 ///
 /// The pool's `AccountId` is derived from this type. Any changes to the type may necessitate a
 /// migration.
-pub(super) type PoolIdOf<T> = (<T as Config>::MultiAssetId, <T as Config>::MultiAssetId);
+pub(super) type PoolIdOf<T> = (<T as Config>::MultiAssetId, <T as Config>::MultiAssetId, Permill);
+
+/// A [`RemoveLiquidityPermit`] with the types of the runtime.
+pub(super) type RemoveLiquidityPermitOf<T> = RemoveLiquidityPermit<
+	<T as Config>::MultiAssetId,
+	<T as Config>::AssetBalance,
+	<T as frame_system::Config>::AccountId,
+	BlockNumberFor<T>,
+>;
 
 /// Stores the lp_token asset id a particular pool has been assigned.
+///
+/// A pool's info is read by every swap through it and so ends up in the storage proofs of
+/// parachains. Only add fixed-size fields, keeping its `MaxEncodedLen` within 32 bytes for the
+/// mock runtime's types, which the tests enforce.
 #[derive(Decode, Encode, Default, PartialEq, Eq, MaxEncodedLen, TypeInfo)]
-pub struct PoolInfo<PoolAssetId> {
+pub struct PoolInfo<PoolAssetId, BlockNumber> {
 	/// Liquidity pool asset
 	pub lp_token: PoolAssetId,
+	/// The block of the last swap through the pool, or of its creation if there was none yet.
+	pub last_swap_block: BlockNumber,
+	/// The block the pool was created in. `0` for pools created before storage version 2.
+	pub created_at: BlockNumber,
+	/// The share of the input of every swap through the pool taken by its liquidity providers,
+	/// unless a [`GlobalFee`] is set. Starts as the fee tier of the pool id and can be
+	/// changed by [`Pallet::set_pool_fee`].
+	pub fee_tier: Permill,
+}
+
+/// Lifetime statistics of a pool, as returned by [`Pallet::pool_stats`].
+#[derive(Decode, Encode, Clone, Default, PartialEq, Eq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub struct PoolStatistics<Balance> {
+	/// The total amount of lp tokens ever minted to liquidity providers. Unlike the lp token
+	/// supply, this is never decreased by removals.
+	pub lifetime_lp_minted: Balance,
+}
+
+/// A summary of a pool's state, as returned by [`Pallet::list_pools`].
+#[derive(Decode, Encode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct PoolInfoSummary<AccountId, PoolAssetId, Balance> {
+	/// The account holding the pool's reserves.
+	pub owner: AccountId,
+	/// Liquidity pool asset.
+	pub lp_token: PoolAssetId,
+	/// The reserve of the first asset of the pool id.
+	pub reserve1: Balance,
+	/// The reserve of the second asset of the pool id.
+	pub reserve2: Balance,
+}
+
+/// The tag every [`RemoveLiquidityPermit`] is signed with, see [`Pallet::permit_payload`].
+pub const PERMIT_DOMAIN: &[u8] = b"asset-conversion:remove-liquidity-permit";
+
+/// A withdrawal of liquidity signed off-chain by the owner of the lp tokens, to be submitted by
+/// anyone through [`Pallet::remove_liquidity_with_signature`].
+///
+/// The owner signs [`Pallet::permit_payload`] of it rather than the permit itself, so that it
+/// can't be replayed on another chain.
+#[derive(Decode, Encode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct RemoveLiquidityPermit<MultiAssetId, Balance, AccountId, BlockNumber> {
+	/// The first asset of the pool.
+	pub asset1: MultiAssetId,
+	/// The second asset of the pool.
+	pub asset2: MultiAssetId,
+	/// The fee tier of the pool.
+	pub fee_tier: Permill,
+	/// The lp tokens of the owner to burn.
+	pub lp_token_burn: Balance,
+	/// The least of `asset1` the owner is happy to receive.
+	pub amount1_min_receive: Balance,
+	/// The least of `asset2` the owner is happy to receive.
+	pub amount2_min_receive: Balance,
+	/// The account receiving the withdrawn assets.
+	pub withdraw_to: AccountId,
+	/// The last block the permit can be used in.
+	pub deadline: BlockNumber,
+	/// The owner's `DexNonces` at the time of signing. Each nonce can only be used once.
+	pub nonce: u64,
+}
+
+/// The lp tokens an account was minted in a pool and what it deposited for them, see
+/// [`LpPositions`].
+#[derive(Decode, Encode, Clone, Default, PartialEq, Eq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub struct LpPosition<Balance> {
+	/// The lp tokens minted to the account and not burned since.
+	pub lp_amount: Balance,
+	/// The amount of the first asset of the pool deposited for the `lp_amount`.
+	pub amount1: Balance,
+	/// The amount of the second asset of the pool deposited for the `lp_amount`.
+	pub amount2: Balance,
+}
+
+/// The profit and loss of a liquidity position, in the native asset, as returned by
+/// [`Pallet::lp_pnl`].
+///
+/// The impermanent loss of the position is `value_if_held - value_if_withdrawn`, offset by the
+/// fees the position has earned.
+#[derive(Decode, Encode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct LpPnl<Balance> {
+	/// The current value of the assets deposited, had they been held instead.
+	pub value_if_held: Balance,
+	/// The current value of the assets the lp tokens would withdraw, before the withdrawal fee.
+	pub value_if_withdrawn: Balance,
+}
+
+/// The state of a page of pools, as returned by [`Pallet::dex_health`].
+#[derive(Decode, Encode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub struct DexHealth<PoolId, Balance> {
+	/// The number of pools that were checked.
+	pub pools: u32,
+	/// The value locked in the checked pools, in the native asset. Pools that neither contain
+	/// the native asset nor have a native pool for their first asset are not valued.
+	pub total_value_locked: Balance,
+	/// Whether each of the checked pools is solvent, see [`Pallet::is_pool_solvent`].
+	pub solvency: Vec<(PoolId, bool)>,
+}
+
+/// The amounts of a swap of an exact amount in, see [`Pallet::do_swap_exact_tokens_for_tokens`].
+///
+/// The amounts are named so that they can not be passed in the wrong order:
+///
+/// ```compile_fail
+/// use pallet_asset_conversion::{SwapExactInParams, SwapExactOutParams};
+///
+/// fn swap_exact_in(_: SwapExactInParams<u128>) {}
+/// swap_exact_in(SwapExactOutParams { amount_out: 10, amount_in_max: Some(20) });
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct SwapExactInParams<Balance> {
+	/// The amount to swap.
+	pub amount_in: Balance,
+	/// The least amount to receive, if any.
+	pub amount_out_min: Option<Balance>,
+}
+
+/// The amounts of a swap for an exact amount out, see
+/// [`Pallet::do_swap_tokens_for_exact_tokens`].
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub struct SwapExactOutParams<Balance> {
+	/// The amount to receive.
+	pub amount_out: Balance,
+	/// The most amount to swap, if any.
+	pub amount_in_max: Option<Balance>,
+}
+
+/// Which of the assets taken from a sender the `keep_alive` flag of the calls applies to.
+#[derive(Decode, Encode, Clone, Copy, PartialEq, Eq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub enum KeepAlivePolicy {
+	/// Only the native currency keeps its existential deposit. Any other asset can be taken in
+	/// full, removing the sender's account of it.
+	NativeOnly,
+	/// Every asset keeps its minimum balance, so none of the sender's accounts is removed.
+	AllAssets,
+}
+
+/// How much detail the swap and liquidity events of the pallet carry.
+#[derive(Decode, Encode, Clone, Copy, PartialEq, Eq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub enum EventLevel {
+	/// Swaps and withdrawals emit [`Event::SwapExecutedCompact`] and
+	/// [`Event::LiquidityRemovedCompact`], leaving out the reserve changes, fees and limits.
+	Minimal,
+	/// Swaps and withdrawals emit [`Event::SwapExecuted`] and [`Event::LiquidityRemoved`] with
+	/// all their fields.
+	Full,
+}
+
+/// The reasons for which the pallet freezes lp tokens.
+#[derive(Decode, Encode, Clone, Copy, PartialEq, Eq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub enum FreezeReason {
+	/// The lp tokens back a [`LockedPosition`].
+	LiquidityLock,
+}
+
+/// Lp tokens of a pool locked by an account, see [`Pallet::lock_liquidity`].
+#[derive(Decode, Encode, Clone, PartialEq, Eq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub struct LockedPosition<Balance, BlockNumber> {
+	/// The amount of locked lp tokens.
+	pub amount: Balance,
+	/// The block from which the lp tokens can be unlocked.
+	pub lock_until: BlockNumber,
+	/// The boost multiplier earned by the lock, see [`Pallet::boost_multiplier`].
+	pub boost: FixedU128,
+}
+
+/// Where the compounding of a pool by [`Pallet::auto_compound`] left off.
+#[derive(Decode, Encode, Clone, PartialEq, Eq, MaxEncodedLen, RuntimeDebug, TypeInfo)]
+pub struct CompoundState<BlockNumber> {
+	/// The square root of the product of the reserves per lp token at the last compounding.
+	/// Only fees grow it, so its growth since is the share of the liquidity left to realize.
+	pub root_k_per_lp_token: FixedU128,
+	/// The block from which the pool is compounded again.
+	pub next_at: BlockNumber,
+	/// The number of compoundings of the pool that failed in a row.
+	pub failures: u32,
+}
+
+/// A signed change of a balance.
+#[derive(Decode, Encode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum BalanceDelta<Balance> {
+	/// The balance increased by the given amount.
+	Increase(Balance),
+	/// The balance decreased by the given amount.
+	Decrease(Balance),
+}
+
+/// Derives the account holding the reserves of a pool from its id.
+pub trait PoolAccountDerivation<AccountId, PoolId> {
+	/// Returns the account of the pool with the id `pool_id`.
+	fn pool_account(pool_id: &PoolId) -> AccountId;
+}
+
+/// Derives the pool account from the `blake2_256` hash of the encoded pool id. No part of the
+/// pool id is truncated, so distinct pool ids only collide if their hashes do.
+pub struct HashedPoolAccount;
+
+impl<AccountId: Decode, PoolId: Encode> PoolAccountDerivation<AccountId, PoolId>
+	for HashedPoolAccount
+{
+	fn pool_account(pool_id: &PoolId) -> AccountId {
+		let encoded_pool_id = sp_io::hashing::blake2_256(&Encode::encode(pool_id)[..]);
+
+		Decode::decode(&mut TrailingZeroInput::new(encoded_pool_id.as_ref()))
+			.expect("infinite length input; no invalid inputs for type; qed")
+	}
+}
+
+/// Derives the pool account as a sub-account of the pallet id given by `P`.
+///
+/// Note that the encoded pool id is truncated if it does not fit into the account id together
+/// with the pallet id, in which case distinct pool ids may derive the same account.
+pub struct TruncatedSubAccount<P>(PhantomData<P>);
+
+impl<AccountId, PoolId, P> PoolAccountDerivation<AccountId, PoolId> for TruncatedSubAccount<P>
+where
+	AccountId: Encode + Decode,
+	PoolId: Encode,
+	P: Get<PalletId>,
+{
+	fn pool_account(pool_id: &PoolId) -> AccountId {
+		P::get().into_sub_account_truncating(pool_id)
+	}
 }
 
 /// A trait that converts between a MultiAssetId and either the native currency or an AssetId.
@@ -60,16 +297,29 @@ pub enum MultiAssetIdConversionResult<MultiAssetId, AssetId> {
 
 /// Benchmark Helper
 #[cfg(feature = "runtime-benchmarks")]
-pub trait BenchmarkHelper<AssetId, MultiAssetId> {
+pub trait BenchmarkHelper<AssetId, MultiAssetId, Public, AccountId, Signature> {
 	/// Returns an `AssetId` from a given integer.
 	fn asset_id(asset_id: u32) -> AssetId;
 
 	/// Returns a `MultiAssetId` from a given integer.
 	fn multiasset_id(asset_id: u32) -> MultiAssetId;
+
+	/// Returns a public key that [`Self::sign`] can sign with, and the account it identifies as.
+	fn signer() -> (Public, AccountId);
+
+	/// Signs `message` with the key of `signer`.
+	fn sign(signer: &Public, message: &[u8]) -> Signature;
 }
 
 #[cfg(feature = "runtime-benchmarks")]
-impl<AssetId, MultiAssetId> BenchmarkHelper<AssetId, MultiAssetId> for ()
+impl<AssetId, MultiAssetId>
+	BenchmarkHelper<
+		AssetId,
+		MultiAssetId,
+		sp_runtime::MultiSigner,
+		sp_runtime::AccountId32,
+		sp_runtime::MultiSignature,
+	> for ()
 where
 	AssetId: From<u32>,
 	MultiAssetId: From<u32>,
@@ -81,6 +331,21 @@ where
 	fn multiasset_id(asset_id: u32) -> MultiAssetId {
 		asset_id.into()
 	}
+
+	fn signer() -> (sp_runtime::MultiSigner, sp_runtime::AccountId32) {
+		use sp_runtime::traits::IdentifyAccount;
+		let public = sp_io::crypto::sr25519_generate(0.into(), None);
+		let signer = sp_runtime::MultiSigner::Sr25519(public);
+		(signer.clone(), signer.into_account())
+	}
+
+	fn sign(signer: &sp_runtime::MultiSigner, message: &[u8]) -> sp_runtime::MultiSignature {
+		let public = signer.clone().try_into().expect("`signer` returns sr25519 keys; qed");
+		sp_runtime::MultiSignature::Sr25519(
+			sp_io::crypto::sr25519_sign(0.into(), &public, message)
+				.expect("the key is in the keystore; qed"),
+		)
+	}
 }
 
 /// Trait for providing methods to swap between the various asset classes.
@@ -120,9 +385,101 @@ pub trait Swap<AccountId, Balance, MultiAssetId> {
 	) -> Result<Balance, DispatchError>;
 }
 
-/// An implementation of MultiAssetId that can be either Native or an asset.
+/// Notified of the lp tokens the pallet mints to or burns from accounts, e.g. to track lp tokens
+/// used as collateral elsewhere without polling.
+pub trait OnLpBalanceChange<AccountId, PoolId, Balance> {
+	/// The lp token balance of `who` in the pool `pool_id` changed by `delta`.
+	fn on_lp_balance_change(who: &AccountId, pool_id: &PoolId, delta: BalanceDelta<Balance>);
+}
+
+impl<AccountId, PoolId, Balance> OnLpBalanceChange<AccountId, PoolId, Balance> for () {
+	fn on_lp_balance_change(_: &AccountId, _: &PoolId, _: BalanceDelta<Balance>) {}
+}
+
+/// Notified of the reserves of a pool whenever they change, e.g. to mirror them to another system
+/// synchronously rather than by following events.
+pub trait OnReserveChange<PoolId, Balance> {
+	/// The pool `pool_id` now holds `reserve1` of its first asset and `reserve2` of its second.
+	fn on_reserve_change(pool_id: &PoolId, reserve1: Balance, reserve2: Balance);
+
+	/// The weight of [`Self::on_reserve_change`].
+	fn weight() -> Weight;
+}
+
+impl<PoolId, Balance> OnReserveChange<PoolId, Balance> for () {
+	fn on_reserve_change(_: &PoolId, _: Balance, _: Balance) {}
+
+	fn weight() -> Weight {
+		Weight::zero()
+	}
+}
+
+/// Receives the funds of a flash loan, see [`Pallet::flash_loan`].
+pub trait FlashBorrower<AccountId, MultiAssetId, Balance> {
+	/// Called once `amount` of `asset` has been lent from `pool_account` to `borrower`.
+	///
+	/// `amount` plus `fee` of `asset` must have been paid back to `pool_account` by the time
+	/// this returns, otherwise the loan is reverted. `data` is passed through from the caller.
+	fn on_flash_loan(
+		borrower: &AccountId,
+		pool_account: &AccountId,
+		asset: &MultiAssetId,
+		amount: Balance,
+		fee: Balance,
+		data: &[u8],
+	) -> DispatchResult;
+
+	/// The weight of [`Self::on_flash_loan`] when called with `data`.
+	fn weight(data: &[u8]) -> Weight;
+}
+
+/// Never repays, so no flash loan can succeed.
+impl<AccountId, MultiAssetId, Balance> FlashBorrower<AccountId, MultiAssetId, Balance> for () {
+	fn on_flash_loan(
+		_borrower: &AccountId,
+		_pool_account: &AccountId,
+		_asset: &MultiAssetId,
+		_amount: Balance,
+		_fee: Balance,
+		_data: &[u8],
+	) -> DispatchResult {
+		Ok(())
+	}
+
+	fn weight(_data: &[u8]) -> Weight {
+		Weight::zero()
+	}
+}
+
+/// Moves the assets the `MultiAssetIdConverter` does not support, such as the nft instances of
+/// [`NativeOrAssetId::Class`].
+#[cfg(feature = "nft-pools")]
+pub trait NftHandler<AccountId, MultiAssetId, Balance> {
+	/// Transfer `amount` of `asset` from `from` to `to`.
+	fn transfer(
+		asset: &MultiAssetId,
+		from: &AccountId,
+		to: &AccountId,
+		amount: Balance,
+	) -> DispatchResult;
+}
+
+/// Supports no assets.
+#[cfg(feature = "nft-pools")]
+impl<AccountId, MultiAssetId, Balance> NftHandler<AccountId, MultiAssetId, Balance> for () {
+	fn transfer(_: &MultiAssetId, _: &AccountId, _: &AccountId, _: Balance) -> DispatchResult {
+		Err(sp_runtime::TokenError::Unsupported.into())
+	}
+}
+
+/// An implementation of MultiAssetId that can be either Native or an asset, or an nft instance
+/// with the `nft-pools` feature.
+///
+/// `InstanceId` identifies the items of an nft collection and defaults to `AssetId`. It only
+/// exists with the `nft-pools` feature, so that the type, its encoding and its metadata are
+/// unchanged without it.
 #[derive(Decode, Encode, Default, MaxEncodedLen, TypeInfo, Clone, Copy, Debug)]
-pub enum NativeOrAssetId<AssetId>
+pub enum NativeOrAssetId<AssetId, #[cfg(feature = "nft-pools")] InstanceId: Ord = AssetId>
 where
 	AssetId: Ord,
 {
@@ -131,58 +488,117 @@ where
 	Native,
 	/// A non-native asset id.
 	Asset(AssetId),
+	/// An instance of an nft collection, as `Class(collection, item)`. Experimental: the
+	/// `NativeOrAssetIdConverter` does not support it, its transfers go to the `NftHandler`.
+	#[cfg(feature = "nft-pools")]
+	Class(AssetId, InstanceId),
 }
 
-impl<AssetId: Ord> From<AssetId> for NativeOrAssetId<AssetId> {
+/// [`NativeOrAssetId`] of `$asset`, with `$instance` as its `InstanceId` if the `nft-pools`
+/// feature is on, so that its impls are written once for both.
+#[cfg(feature = "nft-pools")]
+macro_rules! native_or_asset_id {
+	($asset:ty, $instance:ty) => { NativeOrAssetId<$asset, $instance> };
+}
+#[cfg(not(feature = "nft-pools"))]
+macro_rules! native_or_asset_id {
+	($asset:ty, $instance:ty) => { NativeOrAssetId<$asset> };
+}
+
+impl<AssetId: Ord + Clone, #[cfg(feature = "nft-pools")] InstanceId: Ord>
+	native_or_asset_id!(AssetId, InstanceId)
+{
+	/// Returns true if this is the native asset.
+	pub fn is_native(&self) -> bool {
+		matches!(self, Self::Native)
+	}
+
+	/// Returns the non-native asset id, or `None` if this is the native asset.
+	pub fn as_asset(&self) -> Option<AssetId> {
+		match self {
+			Self::Native => None,
+			Self::Asset(asset) => Some(asset.clone()),
+			#[cfg(feature = "nft-pools")]
+			Self::Class(..) => None,
+		}
+	}
+}
+
+impl<AssetId: Ord, #[cfg(feature = "nft-pools")] InstanceId: Ord> From<AssetId>
+	for native_or_asset_id!(AssetId, InstanceId)
+{
 	fn from(asset: AssetId) -> Self {
 		Self::Asset(asset)
 	}
 }
 
-impl<AssetId: Ord> Ord for NativeOrAssetId<AssetId> {
+impl<AssetId: Ord, #[cfg(feature = "nft-pools")] InstanceId: Ord> Ord
+	for native_or_asset_id!(AssetId, InstanceId)
+{
 	fn cmp(&self, other: &Self) -> Ordering {
 		match (self, other) {
 			(Self::Native, Self::Native) => Ordering::Equal,
 			(Self::Native, Self::Asset(_)) => Ordering::Less,
 			(Self::Asset(_), Self::Native) => Ordering::Greater,
 			(Self::Asset(id1), Self::Asset(id2)) => <AssetId as Ord>::cmp(id1, id2),
+			// nft instances come after all other assets.
+			#[cfg(feature = "nft-pools")]
+			(Self::Class(..), Self::Native | Self::Asset(_)) => Ordering::Greater,
+			#[cfg(feature = "nft-pools")]
+			(Self::Native | Self::Asset(_), Self::Class(..)) => Ordering::Less,
+			#[cfg(feature = "nft-pools")]
+			(Self::Class(collection1, item1), Self::Class(collection2, item2)) =>
+				(collection1, item1).cmp(&(collection2, item2)),
 		}
 	}
 }
-impl<AssetId: Ord> PartialOrd for NativeOrAssetId<AssetId> {
+impl<AssetId: Ord, #[cfg(feature = "nft-pools")] InstanceId: Ord> PartialOrd
+	for native_or_asset_id!(AssetId, InstanceId)
+{
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
 		Some(<Self as Ord>::cmp(self, other))
 	}
 }
-impl<AssetId: Ord> PartialEq for NativeOrAssetId<AssetId> {
+impl<AssetId: Ord, #[cfg(feature = "nft-pools")] InstanceId: Ord> PartialEq
+	for native_or_asset_id!(AssetId, InstanceId)
+{
 	fn eq(&self, other: &Self) -> bool {
 		self.cmp(other) == Ordering::Equal
 	}
 }
-impl<AssetId: Ord> Eq for NativeOrAssetId<AssetId> {}
+impl<AssetId: Ord, #[cfg(feature = "nft-pools")] InstanceId: Ord> Eq
+	for native_or_asset_id!(AssetId, InstanceId)
+{
+}
 
 /// Converts between a MultiAssetId and an AssetId (or the native currency).
-pub struct NativeOrAssetIdConverter<AssetId> {
-	_phantom: PhantomData<AssetId>,
+///
+/// `InstanceId` is the one of the converted [`NativeOrAssetId`] with the `nft-pools` feature,
+/// and unused without it.
+pub struct NativeOrAssetIdConverter<AssetId, InstanceId = AssetId> {
+	_phantom: PhantomData<(AssetId, InstanceId)>,
 }
 
-impl<AssetId: Ord + Clone> MultiAssetIdConverter<NativeOrAssetId<AssetId>, AssetId>
-	for NativeOrAssetIdConverter<AssetId>
+impl<AssetId: Ord + Clone, InstanceId: Ord + Clone>
+	MultiAssetIdConverter<native_or_asset_id!(AssetId, InstanceId), AssetId>
+	for NativeOrAssetIdConverter<AssetId, InstanceId>
 {
-	fn get_native() -> NativeOrAssetId<AssetId> {
+	fn get_native() -> native_or_asset_id!(AssetId, InstanceId) {
 		NativeOrAssetId::Native
 	}
 
-	fn is_native(asset: &NativeOrAssetId<AssetId>) -> bool {
-		*asset == Self::get_native()
+	fn is_native(asset: &native_or_asset_id!(AssetId, InstanceId)) -> bool {
+		asset.is_native()
 	}
 
 	fn try_convert(
-		asset: &NativeOrAssetId<AssetId>,
-	) -> MultiAssetIdConversionResult<NativeOrAssetId<AssetId>, AssetId> {
+		asset: &native_or_asset_id!(AssetId, InstanceId),
+	) -> MultiAssetIdConversionResult<native_or_asset_id!(AssetId, InstanceId), AssetId> {
 		match asset {
-			NativeOrAssetId::Asset(asset) => MultiAssetIdConversionResult::Converted(asset.clone()),
 			NativeOrAssetId::Native => MultiAssetIdConversionResult::Native,
+			NativeOrAssetId::Asset(asset) => MultiAssetIdConversionResult::Converted(asset.clone()),
+			#[cfg(feature = "nft-pools")]
+			NativeOrAssetId::Class(..) => MultiAssetIdConversionResult::Unsupported(asset.clone()),
 		}
 	}
 }
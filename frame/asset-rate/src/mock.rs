@@ -74,6 +74,7 @@ impl pallet_balances::Config for Test {
 	type ReserveIdentifier = [u8; 8];
 	type RuntimeHoldReason = RuntimeHoldReason;
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxHolds = ();
 	type MaxFreezes = ();
 }
@@ -84,6 +84,7 @@ impl pallet_balances::Config for Test {
 	type ReserveIdentifier = [u8; 8];
 	type RuntimeHoldReason = ();
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxHolds = ();
 	type MaxFreezes = ();
 }
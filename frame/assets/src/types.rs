@@ -20,7 +20,7 @@
 use super::*;
 use frame_support::{
 	pallet_prelude::*,
-	traits::{fungible, tokens::ConversionToAssetBalance},
+	traits::{fungible, fungibles, tokens::ConversionToAssetBalance},
 };
 use sp_runtime::{traits::Convert, FixedPointNumber, FixedU128};
 
@@ -232,6 +232,22 @@ impl<AssetId, AccountId, Balance> FrozenBalance<AssetId, AccountId, Balance> for
 	fn died(_: AssetId, _: &AccountId) {}
 }
 
+/// Enforces the freezes kept by `F`, an implementation of the `fungibles` freeze traits for the
+/// same assets.
+pub struct FrozenBalanceOf<F>(PhantomData<F>);
+impl<AssetId, AccountId, Balance, F> FrozenBalance<AssetId, AccountId, Balance>
+	for FrozenBalanceOf<F>
+where
+	Balance: Zero,
+	F: fungibles::InspectFreeze<AccountId, AssetId = AssetId, Balance = Balance>,
+{
+	fn frozen_balance(asset: AssetId, who: &AccountId) -> Option<Balance> {
+		let frozen = F::balance_frozen_total(asset, who);
+		(!frozen.is_zero()).then_some(frozen)
+	}
+	fn died(_: AssetId, _: &AccountId) {}
+}
+
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub(super) struct TransferFlags {
 	/// The debited account must stay alive at the end of the operation; an error is returned if
@@ -58,6 +58,7 @@ impl pallet_balances::Config for Test {
 	type AccountStore = System;
 	type WeightInfo = ();
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 	type RuntimeHoldReason = ();
 	type MaxHolds = ();
@@ -423,26 +423,37 @@ where
 			return Ok(NegativeImbalance::zero())
 		}
 
-		Self::try_mutate_account_handling_dust(
-			who,
-			|account, _| -> Result<Self::NegativeImbalance, DispatchError> {
-				let new_free_account =
-					account.free.checked_sub(&value).ok_or(Error::<T, I>::InsufficientBalance)?;
-
-				// bail if we need to keep the account alive and this would kill it.
-				let ed = T::ExistentialDeposit::get();
-				let would_be_dead = new_free_account < ed;
-				let would_kill = would_be_dead && account.free >= ed;
-				ensure!(liveness == AllowDeath || !would_kill, Error::<T, I>::Expendability);
-
-				Self::ensure_can_withdraw(who, value, reasons, new_free_account)?;
-
-				account.free = new_free_account;
-
-				Self::deposit_event(Event::Withdraw { who: who.clone(), amount: value });
-				Ok(NegativeImbalance::new(value))
-			},
-		)
+		let withdraw = || {
+			Self::try_mutate_account_handling_dust(
+				who,
+				|account, _| -> Result<Self::NegativeImbalance, DispatchError> {
+					let new_free_account = account
+						.free
+						.checked_sub(&value)
+						.ok_or(Error::<T, I>::InsufficientBalance)?;
+
+					// bail if we need to keep the account alive and this would kill it.
+					let ed = T::ExistentialDeposit::get();
+					let would_be_dead = new_free_account < ed;
+					let would_kill = would_be_dead && account.free >= ed;
+					ensure!(liveness == AllowDeath || !would_kill, Error::<T, I>::Expendability);
+
+					Self::ensure_can_withdraw(who, value, reasons, new_free_account)?;
+
+					account.free = new_free_account;
+
+					Self::deposit_event(Event::Withdraw { who: who.clone(), amount: value });
+					Ok(NegativeImbalance::new(value))
+				},
+			)
+		};
+		withdraw().or_else(|e| {
+			if Self::notify_liquidity_restriction(who, value, &e) {
+				withdraw()
+			} else {
+				Err(e)
+			}
+		})
 	}
 
 	/// Force the new free balance of a target account `who` to some new value `balance`.
@@ -509,12 +520,21 @@ where
 			return Ok(())
 		}
 
-		Self::try_mutate_account_handling_dust(who, |account, _| -> DispatchResult {
-			account.free =
-				account.free.checked_sub(&value).ok_or(Error::<T, I>::InsufficientBalance)?;
-			account.reserved =
-				account.reserved.checked_add(&value).ok_or(ArithmeticError::Overflow)?;
-			Self::ensure_can_withdraw(&who, value, WithdrawReasons::RESERVE, account.free)
+		let reserve = || {
+			Self::try_mutate_account_handling_dust(who, |account, _| -> DispatchResult {
+				account.free =
+					account.free.checked_sub(&value).ok_or(Error::<T, I>::InsufficientBalance)?;
+				account.reserved =
+					account.reserved.checked_add(&value).ok_or(ArithmeticError::Overflow)?;
+				Self::ensure_can_withdraw(&who, value, WithdrawReasons::RESERVE, account.free)
+			})
+		};
+		reserve().or_else(|e| {
+			if Self::notify_liquidity_restriction(who, value, &e) {
+				reserve()
+			} else {
+				Err(e)
+			}
 		})?;
 
 		Self::deposit_event(Event::Reserved { who: who.clone(), amount: value });
@@ -173,6 +173,24 @@ impl<T: Config<I>, I: 'static> fungible::Unbalanced<T::AccountId> for Pallet<T,
 		TotalIssuance::<T, I>::mutate(|t| *t = amount);
 	}
 
+	fn notify_freezers(
+		who: &T::AccountId,
+		amount: Self::Balance,
+		preservation: Preservation,
+		force: Fortitude,
+	) -> bool {
+		// Forced reductions ignore freezes, and past the forced limit nothing can help.
+		if force == Force ||
+			amount > <Self as fungible::Inspect<_>>::reducible_balance(who, preservation, Force)
+		{
+			return false
+		}
+		// Freezes apply to the total balance, inclusive of any funds on hold.
+		let a = Self::account(who);
+		let remaining = a.free.saturating_sub(amount).saturating_add(a.reserved);
+		Self::notify_freeze_violation(who, amount, remaining)
+	}
+
 	fn deactivate(amount: Self::Balance) {
 		InactiveIssuance::<T, I>::mutate(|b| b.saturating_accrue(amount));
 	}
@@ -297,6 +315,11 @@ impl<T: Config<I>, I: 'static> fungible::InspectFreeze<T::AccountId> for Pallet<
 		let l = Freezes::<T, I>::get(who);
 		!l.is_full() || l.iter().any(|x| &x.id == id)
 	}
+
+	fn balance_frozen_total(who: &T::AccountId) -> Self::Balance {
+		// `frozen` also covers the locks, so only look at the freezes.
+		Freezes::<T, I>::get(who).iter().map(|f| f.amount).max().unwrap_or_default()
+	}
 }
 
 impl<T: Config<I>, I: 'static> fungible::MutateFreeze<T::AccountId> for Pallet<T, I> {
@@ -207,7 +207,10 @@ pub mod pallet {
 	use super::*;
 	use frame_support::{
 		pallet_prelude::*,
-		traits::{fungible::Credit, tokens::Precision},
+		traits::{
+			fungible::{Credit, OnFreezeViolation},
+			tokens::Precision,
+		},
 	};
 	use frame_system::pallet_prelude::*;
 
@@ -232,6 +235,7 @@ pub mod pallet {
 
 			type ReserveIdentifier = ();
 			type FreezeIdentifier = ();
+			type OnFreezeViolation = ();
 
 			type MaxLocks = ();
 			type MaxReserves = ();
@@ -297,6 +301,15 @@ pub mod pallet {
 		/// The ID type for freezes.
 		type FreezeIdentifier: Parameter + Member + MaxEncodedLen + Ord + Copy;
 
+		/// Handler told of the freezes refusing a reduction of an account's balance, see
+		/// [`fungible::OnFreezeViolation`].
+		#[pallet::no_default_bounds]
+		type OnFreezeViolation: fungible::OnFreezeViolation<
+			Self::FreezeIdentifier,
+			Self::AccountId,
+			Self::Balance,
+		>;
+
 		/// The maximum number of locks that should exist on an account.
 		/// Not strictly enforced, but used for weight estimation.
 		#[pallet::constant]
@@ -1094,6 +1107,44 @@ pub mod pallet {
 			}
 		}
 
+		/// Tell the `OnFreezeViolation` handler of every freeze of `who` which refuses to take
+		/// `amount` out of its balance, leaving `remaining` of it. Nothing is told if a lock
+		/// refuses it as well, since lifting freezes would not help.
+		///
+		/// Returns `true` if the handler was told of any freeze.
+		pub(crate) fn notify_freeze_violation(
+			who: &T::AccountId,
+			amount: T::Balance,
+			remaining: T::Balance,
+		) -> bool {
+			if Locks::<T, I>::get(who).iter().any(|l| l.amount > remaining) {
+				return false
+			}
+			let mut notified = false;
+			for l in Freezes::<T, I>::get(who).iter().filter(|l| l.amount > remaining) {
+				T::OnFreezeViolation::on_freeze_violation(&l.id, who, amount, l.amount);
+				notified = true;
+			}
+			notified
+		}
+
+		/// If `e` is the `LiquidityRestrictions` refusing to take `amount` out of the free balance
+		/// of `who`, tell the `OnFreezeViolation` handler of the freezes behind it. Returns `true`
+		/// if the handler was told of any freeze.
+		pub(crate) fn notify_liquidity_restriction(
+			who: &T::AccountId,
+			amount: T::Balance,
+			e: &DispatchError,
+		) -> bool {
+			// `Currency` holds the free balance alone against the locks and freezes.
+			*e == Error::<T, I>::LiquidityRestrictions.into() &&
+				Self::notify_freeze_violation(
+					who,
+					amount,
+					Self::free_balance(who).saturating_sub(amount),
+				)
+		}
+
 		/// Update the account entry for `who`, given the locks.
 		pub(crate) fn update_freezes(
 			who: &T::AccountId,
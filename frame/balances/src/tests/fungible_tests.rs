@@ -25,6 +25,7 @@ use frame_support::traits::tokens::{
 	Restriction::Free,
 };
 use fungible::{Inspect, InspectFreeze, InspectHold, Mutate, MutateFreeze, MutateHold, Unbalanced};
+use sp_runtime::Permill;
 
 #[test]
 fn inspect_trait_reducible_balance_basic_works() {
@@ -288,6 +289,88 @@ fn partial_freezing_should_work() {
 		});
 }
 
+#[test]
+fn freeze_violation_is_reported_to_the_freezer() {
+	use frame_support::traits::{Currency, ExistenceRequirement::AllowDeath, WithdrawReasons};
+	ExtBuilder::default()
+		.existential_deposit(1)
+		.monied(true)
+		.build_and_execute_with(|| {
+			assert_ok!(Balances::set_freeze(&TestId::Foo, &1, 5));
+			assert_ok!(<Balances as fungible::Mutate<_>>::transfer(&1, &2, 5, Expendable));
+			assert!(FreezeViolations::get().is_empty());
+
+			assert_noop!(
+				<Balances as fungible::Mutate<_>>::transfer(&1, &2, 1, Expendable),
+				TokenError::Frozen
+			);
+			assert_noop!(Balances::burn_from(&1, 1, Exact, Polite), TokenError::FundsUnavailable);
+			assert_eq!(Balances::decrease_balance(&1, 1, BestEffort, Expendable, Polite), Ok(0));
+			assert_noop!(
+				<Balances as Currency<_>>::withdraw(&1, 1, WithdrawReasons::TRANSFER, AllowDeath),
+				Error::<Test>::LiquidityRestrictions
+			);
+			assert_eq!(FreezeViolations::take(), vec![(TestId::Foo, 1, 1, 5); 4]);
+
+			// Forced reductions ignore freezes, so there is nothing to report.
+			assert_ok!(Balances::burn_from(&1, 1, Exact, Force));
+			assert!(FreezeViolations::get().is_empty());
+		});
+}
+
+#[test]
+fn freeze_violation_is_not_reported_when_a_lock_refuses_as_well() {
+	use frame_support::traits::{
+		Currency, ExistenceRequirement::AllowDeath, LockableCurrency, WithdrawReasons,
+	};
+	ExtBuilder::default()
+		.existential_deposit(1)
+		.monied(true)
+		.build_and_execute_with(|| {
+			assert_ok!(Balances::set_freeze(&TestId::Foo, &1, 5));
+			Balances::set_lock(*b"LOCK_000", &1, 8, WithdrawReasons::all());
+
+			// the lock alone refuses these, lifting the freeze would not help.
+			assert_noop!(
+				<Balances as fungible::Mutate<_>>::transfer(&1, &2, 3, Expendable),
+				TokenError::Frozen
+			);
+			assert_noop!(
+				<Balances as Currency<_>>::withdraw(&1, 3, WithdrawReasons::TRANSFER, AllowDeath),
+				Error::<Test>::LiquidityRestrictions
+			);
+			assert!(FreezeViolations::get().is_empty());
+
+			// only the freezes above what the lock allows are reported.
+			assert_ok!(Balances::set_freeze(&TestId::Bar, &1, 9));
+			assert_noop!(
+				<Balances as fungible::Mutate<_>>::transfer(&1, &2, 2, Expendable),
+				TokenError::Frozen
+			);
+			assert_eq!(FreezeViolations::take(), vec![(TestId::Bar, 1, 2, 9)]);
+		});
+}
+
+#[test]
+fn freezer_can_lift_its_freeze_when_told_of_a_violation() {
+	ExtBuilder::default()
+		.existential_deposit(1)
+		.monied(true)
+		.build_and_execute_with(|| {
+			ThawOnViolation::set(true);
+			assert_ok!(Balances::set_freeze(&TestId::Foo, &1, 10));
+			assert_ok!(<Balances as fungible::Mutate<_>>::transfer(&1, &2, 1, Expendable));
+			assert_eq!(FreezeViolations::take(), vec![(TestId::Foo, 1, 1, 10)]);
+			// The housekeeping of the freezer persists along with the transfer.
+			assert_eq!(Balances::balance_frozen(&TestId::Foo, &1), 0);
+			assert_eq!(Balances::free_balance(1), 9);
+			System::assert_has_event(RuntimeEvent::Balances(crate::Event::Thawed {
+				who: 1,
+				amount: 10,
+			}));
+		});
+}
+
 #[test]
 fn thaw_should_work() {
 	ExtBuilder::default()
@@ -303,6 +386,75 @@ fn thaw_should_work() {
 		});
 }
 
+#[test]
+fn migrate_freeze_should_work() {
+	ExtBuilder::default()
+		.existential_deposit(1)
+		.monied(true)
+		.build_and_execute_with(|| {
+			assert_ok!(Balances::set_freeze(&TestId::Foo, &1, 5));
+			assert_ok!(Balances::migrate_freeze(&TestId::Foo, &TestId::Bar, &1));
+			assert_eq!(Balances::balance_frozen(&TestId::Foo, &1), 0);
+			assert_eq!(Balances::balance_frozen(&TestId::Bar, &1), 5);
+			assert_eq!(Balances::account(&1).frozen, 5);
+
+			// migrating onto itself keeps the freeze.
+			assert_ok!(Balances::migrate_freeze(&TestId::Bar, &TestId::Bar, &1));
+			assert_eq!(Balances::balance_frozen(&TestId::Bar, &1), 5);
+		});
+}
+
+#[test]
+fn set_freeze_percent_freezes_a_snapshot_of_the_balance() {
+	ExtBuilder::default()
+		.existential_deposit(1)
+		.monied(true)
+		.build_and_execute_with(|| {
+			let freezable = Balances::balance_freezable(&1);
+			assert_eq!(
+				Balances::set_freeze_percent(&TestId::Foo, &1, Permill::from_percent(50)),
+				Ok(freezable / 2)
+			);
+			assert_eq!(Balances::balance_frozen(&TestId::Foo, &1), freezable / 2);
+			assert_eq!(Balances::account(&1).frozen, freezable / 2);
+
+			// the freeze doesn't follow the balance.
+			assert_ok!(Balances::mint_into(&1, freezable));
+			assert_eq!(Balances::balance_frozen(&TestId::Foo, &1), freezable / 2);
+
+			// setting it again replaces it with the share of the new balance.
+			assert_eq!(
+				Balances::set_freeze_percent(&TestId::Foo, &1, Permill::from_percent(25)),
+				Ok(freezable / 2)
+			);
+			assert_eq!(Balances::set_freeze_percent(&TestId::Foo, &1, Permill::zero()), Ok(0));
+			assert_eq!(Balances::balance_frozen(&TestId::Foo, &1), 0);
+		});
+}
+
+#[test]
+fn migrate_freeze_combines_via_max() {
+	ExtBuilder::default()
+		.existential_deposit(1)
+		.monied(true)
+		.build_and_execute_with(|| {
+			assert_ok!(Balances::set_freeze(&TestId::Foo, &1, 8));
+			assert_ok!(Balances::set_freeze(&TestId::Bar, &1, 5));
+			assert_ok!(Balances::migrate_freeze(&TestId::Foo, &TestId::Bar, &1));
+			assert_eq!(Balances::balance_frozen(&TestId::Foo, &1), 0);
+			assert_eq!(Balances::balance_frozen(&TestId::Bar, &1), 8);
+
+			assert_ok!(Balances::set_freeze(&TestId::Foo, &1, 3));
+			assert_ok!(Balances::migrate_freeze(&TestId::Foo, &TestId::Bar, &1));
+			assert_eq!(Balances::balance_frozen(&TestId::Bar, &1), 8);
+			assert_eq!(Balances::account(&1).frozen, 8);
+			assert_noop!(
+				<Balances as fungible::Mutate<_>>::transfer(&1, &2, 3, Expendable),
+				TokenError::Frozen
+			);
+		});
+}
+
 #[test]
 fn set_freeze_zero_should_work() {
 	ExtBuilder::default()
@@ -368,6 +520,41 @@ fn double_freezing_should_work() {
 		});
 }
 
+#[test]
+fn spendable_after_freezes_uses_largest_freeze() {
+	use frame_support::traits::{LockableCurrency, WithdrawReasons};
+	ExtBuilder::default()
+		.existential_deposit(1)
+		.monied(false)
+		.build_and_execute_with(|| {
+			<Balances as fungible::Mutate<_>>::set_balance(&1, 100);
+			assert_eq!(Balances::spendable_after_freezes(&1), 100);
+
+			// locks are not freezes.
+			Balances::set_lock(*b"LOCK_000", &1, 50, WithdrawReasons::all());
+			assert_eq!(Balances::balance_frozen_total(&1), 0);
+			assert_eq!(Balances::spendable_after_freezes(&1), 100);
+			Balances::remove_lock(*b"LOCK_000", &1);
+
+			assert_ok!(Balances::set_freeze(&TestId::Foo, &1, 30));
+			assert_ok!(Balances::set_freeze(&TestId::Bar, &1, 20));
+			assert_eq!(Balances::balance_frozen_total(&1), 30);
+			assert_eq!(Balances::spendable_after_freezes(&1), 70);
+
+			assert_ok!(Balances::extend_freeze(&TestId::Bar, &1, 40));
+			assert_eq!(Balances::balance_frozen_total(&1), 40);
+			assert_eq!(Balances::spendable_after_freezes(&1), 60);
+
+			// freezing more than the total balance saturates at zero.
+			assert_ok!(Balances::set_freeze(&TestId::Foo, &1, 150));
+			assert_eq!(Balances::spendable_after_freezes(&1), 0);
+
+			assert_ok!(Balances::thaw(&TestId::Foo, &1));
+			assert_ok!(Balances::thaw(&TestId::Bar, &1));
+			assert_eq!(Balances::spendable_after_freezes(&1), 100);
+		});
+}
+
 #[test]
 fn can_hold_entire_balance_when_second_provider() {
 	ExtBuilder::default()
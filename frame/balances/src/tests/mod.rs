@@ -133,6 +133,7 @@ impl Config for Test {
 	type WeightInfo = ();
 	type RuntimeHoldReason = TestId;
 	type FreezeIdentifier = TestId;
+	type OnFreezeViolation = TestFreezeViolation;
 	type MaxFreezes = ConstU32<2>;
 	type MaxHolds = ConstU32<2>;
 }
@@ -218,6 +219,23 @@ impl OnUnbalanced<CreditOf<Test, ()>> for DustTrap {
 	}
 }
 
+parameter_types! {
+	pub static FreezeViolations: Vec<(TestId, u64, u64, u64)> = vec![];
+	pub static ThawOnViolation: bool = false;
+}
+
+/// Records every `(id, who, amount, frozen)` it is told of and lifts the freeze if
+/// `ThawOnViolation` is set.
+pub struct TestFreezeViolation;
+impl fungible::OnFreezeViolation<TestId, u64, u64> for TestFreezeViolation {
+	fn on_freeze_violation(id: &TestId, who: &u64, amount: u64, frozen: u64) {
+		FreezeViolations::mutate(|v| v.push((*id, *who, amount, frozen)));
+		if ThawOnViolation::get() {
+			let _ = <Balances as fungible::MutateFreeze<_>>::thaw(id, who);
+		}
+	}
+}
+
 parameter_types! {
 	pub static UseSystem: bool = false;
 }
@@ -161,6 +161,7 @@ impl pallet_balances::Config for Test {
 	type RuntimeHoldReason = ();
 	type MaxHolds = ();
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 }
 
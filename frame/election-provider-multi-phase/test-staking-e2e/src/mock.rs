@@ -121,6 +121,7 @@ impl pallet_balances::Config for Runtime {
 	type MaxFreezes = traits::ConstU32<1>;
 	type RuntimeHoldReason = RuntimeHoldReason;
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type WeightInfo = ();
 }
 
@@ -1355,6 +1355,7 @@ mod tests {
 		type ReserveIdentifier = [u8; 8];
 		type WeightInfo = ();
 		type FreezeIdentifier = ();
+		type OnFreezeViolation = ();
 		type MaxFreezes = ();
 		type RuntimeHoldReason = ();
 		type MaxHolds = ();
@@ -902,6 +902,7 @@ mod tests {
 		type ReserveIdentifier = [u8; 8];
 		type WeightInfo = ();
 		type FreezeIdentifier = ();
+		type OnFreezeViolation = ();
 		type MaxFreezes = ConstU32<1>;
 		type RuntimeHoldReason = ();
 		type MaxHolds = ConstU32<1>;
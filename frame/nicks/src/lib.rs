@@ -311,6 +311,7 @@ mod tests {
 		type AccountStore = System;
 		type WeightInfo = ();
 		type FreezeIdentifier = ();
+		type OnFreezeViolation = ();
 		type MaxFreezes = ();
 		type RuntimeHoldReason = ();
 		type MaxHolds = ();
@@ -87,6 +87,7 @@ impl pallet_balances::Config<Instance1> for Test {
 	type MaxReserves = ConstU32<1>;
 	type ReserveIdentifier = [u8; 8];
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 	type RuntimeHoldReason = RuntimeHoldReason;
 	type MaxHolds = ConstU32<1>;
@@ -107,6 +108,7 @@ impl pallet_balances::Config<Instance2> for Test {
 	type MaxReserves = ();
 	type ReserveIdentifier = [u8; 8];
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 	type RuntimeHoldReason = ();
 	type MaxHolds = ();
@@ -26,6 +26,7 @@ pub fn default_reward_account() -> AccountId {
 parameter_types! {
 	pub static MinJoinBondConfig: Balance = 2;
 	pub static CurrentEra: EraIndex = 0;
+	pub static ActiveEra: EraIndex = 0;
 	pub static BondingDuration: EraIndex = 3;
 	pub storage BondedBalanceMap: BTreeMap<AccountId, Balance> = Default::default();
 	pub storage UnbondingBalanceMap: BTreeMap<AccountId, Balance> = Default::default();
@@ -33,6 +34,7 @@ parameter_types! {
 	pub static MaxUnbonding: u32 = 8;
 	pub static StakingMinBond: Balance = 10;
 	pub storage Nominations: Option<Vec<AccountId>> = None;
+	pub storage Payees: BTreeMap<AccountId, AccountId> = Default::default();
 }
 
 pub struct StakingMock;
@@ -55,6 +57,9 @@ impl sp_staking::StakingInterface for StakingMock {
 	fn minimum_validator_bond() -> Self::Balance {
 		StakingMinBond::get()
 	}
+	fn minimum_active_stake() -> Self::Balance {
+		unimplemented!("method currently not used in testing")
+	}
 
 	fn desired_validator_count() -> u32 {
 		unimplemented!("method currently not used in testing")
@@ -64,10 +69,18 @@ impl sp_staking::StakingInterface for StakingMock {
 		CurrentEra::get()
 	}
 
+	fn active_era() -> EraIndex {
+		ActiveEra::get()
+	}
+
 	fn bonding_duration() -> EraIndex {
 		BondingDuration::get()
 	}
 
+	fn total_staked() -> Self::Balance {
+		BondedBalanceMap::get().values().sum()
+	}
+
 	fn status(
 		_: &Self::AccountId,
 	) -> Result<sp_staking::StakerStatus<Self::AccountId>, DispatchError> {
@@ -97,6 +110,18 @@ impl sp_staking::StakingInterface for StakingMock {
 		Ok(())
 	}
 
+	fn validators_paged(_: Option<Vec<u8>>, _: u32) -> (Vec<Self::AccountId>, Option<Vec<u8>>) {
+		(Vec::new(), None)
+	}
+
+	fn chill_other(who: &Self::AccountId) -> sp_runtime::DispatchResult {
+		frame_support::ensure!(
+			Self::active_stake(who)? < Self::minimum_nominator_bond(),
+			DispatchError::Other("bonded enough to nominate")
+		);
+		Ok(())
+	}
+
 	fn withdraw_unbonded(who: Self::AccountId, _: u32) -> Result<bool, DispatchError> {
 		// Simulates removing unlocking chunks and only having the bonded balance locked
 		let mut x = UnbondingBalanceMap::get();
@@ -106,11 +131,22 @@ impl sp_staking::StakingInterface for StakingMock {
 		Ok(UnbondingBalanceMap::get().is_empty() && BondedBalanceMap::get().is_empty())
 	}
 
-	fn bond(stash: &Self::AccountId, value: Self::Balance, _: &Self::AccountId) -> DispatchResult {
+	fn bond(
+		stash: &Self::AccountId,
+		value: Self::Balance,
+		payee: &Self::AccountId,
+	) -> DispatchResult {
 		StakingMock::set_bonded_balance(*stash, value);
+		let mut x = Payees::get();
+		x.insert(*stash, *payee);
+		Payees::set(&x);
 		Ok(())
 	}
 
+	fn payee(who: &Self::AccountId) -> Result<Self::AccountId, DispatchError> {
+		Payees::get().get(who).copied().ok_or(DispatchError::Other("payee not found"))
+	}
+
 	fn nominate(_: &Self::AccountId, nominations: Vec<Self::AccountId>) -> DispatchResult {
 		Nominations::set(&Some(nominations));
 		Ok(())
@@ -137,6 +173,10 @@ impl sp_staking::StakingInterface for StakingMock {
 		}
 	}
 
+	fn unlocking(_who: &Self::AccountId) -> Result<Vec<(EraIndex, Balance)>, DispatchError> {
+		unimplemented!("method currently not used in testing")
+	}
+
 	fn election_ongoing() -> bool {
 		unimplemented!("method currently not used in testing")
 	}
@@ -205,6 +245,7 @@ impl pallet_balances::Config for Runtime {
 	type AccountStore = System;
 	type WeightInfo = ();
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 	type RuntimeHoldReason = ();
 	type MaxHolds = ();
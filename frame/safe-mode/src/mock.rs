@@ -80,6 +80,7 @@ impl pallet_balances::Config for Test {
 	type ReserveIdentifier = [u8; 8];
 	type RuntimeHoldReason = RuntimeHoldReason;
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxHolds = ConstU32<10>;
 	type MaxFreezes = ConstU32<0>;
 }
@@ -326,7 +326,7 @@ benchmarks! {
 		let ed = T::Currency::minimum_balance();
 		let mut ledger = Ledger::<T>::get(&controller).unwrap();
 		ledger.active = ed - One::one();
-		Ledger::<T>::insert(&controller, ledger);
+		Staking::<T>::update_ledger(&controller, &ledger);
 		CurrentEra::<T>::put(EraIndex::max_value());
 
 		whitelist_account!(controller);
@@ -660,7 +660,7 @@ benchmarks! {
 		for _ in 0 .. l {
 			staking_ledger.unlocking.try_push(unlock_chunk.clone()).unwrap()
 		}
-		Ledger::<T>::insert(controller.clone(), staking_ledger.clone());
+		Staking::<T>::update_ledger(&controller, &staking_ledger);
 		let original_bonded: BalanceOf<T> = staking_ledger.active;
 
 		whitelist_account!(controller);
@@ -692,7 +692,7 @@ benchmarks! {
 			unlocking: Default::default(),
 			claimed_rewards: Default::default(),
 		};
-		Ledger::<T>::insert(&controller, l);
+		Staking::<T>::update_ledger(&controller, &l);
 
 		assert!(Bonded::<T>::contains_key(&stash));
 		assert!(T::VoterList::contains(&stash));
@@ -785,7 +785,7 @@ benchmarks! {
 		for _ in 0 .. l {
 			staking_ledger.unlocking.try_push(unlock_chunk.clone()).unwrap();
 		}
-		Ledger::<T>::insert(controller, staking_ledger);
+		Staking::<T>::update_ledger(&controller, &staking_ledger);
 		let slash_amount = T::Currency::minimum_balance() * 10u32.into();
 		let balance_before = T::Currency::free_balance(&stash);
 	}: {
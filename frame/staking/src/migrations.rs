@@ -57,6 +57,69 @@ impl Default for ObsoleteReleases {
 #[storage_alias]
 type StorageVersion<T: Config> = StorageValue<Pallet<T>, ObsoleteReleases, ValueQuery>;
 
+pub mod v14 {
+	use super::*;
+
+	/// Sets [`TotalActiveStake`] to the sum of the active stake of all ledgers.
+	///
+	/// This is not a multi-block migration: it reads every [`Ledger`] in the block it runs in,
+	/// one read per bonded controller, and reports that weight. The proof size grows by the
+	/// same amount. Chains with more than a few tens of thousands of ledgers should check with
+	/// `try-runtime` that the upgrade block stays within the weight and proof size limits before
+	/// including it.
+	pub struct MigrateToV14<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV14<T> {
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, TryRuntimeError> {
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 13,
+				"Required v13 before upgrading to v14"
+			);
+
+			Ok(Default::default())
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			let current = Pallet::<T>::current_storage_version();
+			let onchain = Pallet::<T>::on_chain_storage_version();
+
+			if current == 14 && onchain == 13 {
+				let mut ledgers = 0u64;
+				let mut total: BalanceOf<T> = Zero::zero();
+				for ledger in Ledger::<T>::iter_values() {
+					ledgers.saturating_inc();
+					total = total.saturating_add(ledger.active);
+				}
+				TotalActiveStake::<T>::put(total);
+				current.put::<Pallet<T>>();
+
+				log!(info, "v14 applied successfully");
+				T::DbWeight::get().reads_writes(ledgers.saturating_add(1), 2)
+			} else {
+				log!(warn, "Skipping v14, should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 14,
+				"v14 not applied"
+			);
+
+			let total = Ledger::<T>::iter_values()
+				.fold(Zero::zero(), |acc: BalanceOf<T>, l| acc.saturating_add(l.active));
+			frame_support::ensure!(
+				TotalActiveStake::<T>::get() == total,
+				"TotalActiveStake not migrated correctly"
+			);
+
+			Ok(())
+		}
+	}
+}
+
 pub mod v13 {
 	use super::*;
 
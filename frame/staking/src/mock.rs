@@ -157,6 +157,7 @@ impl pallet_balances::Config for Test {
 	type AccountStore = System;
 	type WeightInfo = ();
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 	type RuntimeHoldReason = ();
 	type MaxHolds = ();
@@ -270,6 +271,9 @@ parameter_types! {
 	pub static LedgerSlashPerEra:
 		(BalanceOf<Test>, BTreeMap<EraIndex, BalanceOf<Test>>) =
 		(Zero::zero(), BTreeMap::new());
+	pub static RewardsReceived: Vec<(AccountId, Balance)> = vec![];
+	pub static StakeUpdates: Vec<(AccountId, Option<sp_staking::Stake<Balance>>)> = vec![];
+	pub static BondedExtra: Vec<(AccountId, Balance)> = vec![];
 }
 
 pub struct EventListenerMock;
@@ -281,6 +285,16 @@ impl OnStakingUpdate<AccountId, Balance> for EventListenerMock {
 	) {
 		LedgerSlashPerEra::set((slashed_bonded, slashed_chunks.clone()));
 	}
+
+	fn on_reward(who: &AccountId, amount: Balance) {
+		RewardsReceived::mutate(|r| r.push((*who, amount)));
+	}
+	fn on_stake_update(who: &AccountId, prev_stake: Option<sp_staking::Stake<Balance>>) {
+		StakeUpdates::mutate(|u| u.push((*who, prev_stake)));
+	}
+	fn on_bond_extra(who: &AccountId, amount: Balance) {
+		BondedExtra::mutate(|b| b.push((*who, amount)));
+	}
 }
 
 impl crate::pallet::pallet::Config for Test {
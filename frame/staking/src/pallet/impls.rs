@@ -41,7 +41,7 @@ use sp_runtime::{
 use sp_staking::{
 	currency_to_vote::CurrencyToVote,
 	offence::{DisableStrategy, OffenceDetails, OnOffenceHandler},
-	EraIndex, SessionIndex, Stake, StakingInterface,
+	EraIndex, OnStakingUpdate, SessionIndex, Stake, StakingInterface,
 };
 use sp_std::prelude::*;
 
@@ -239,6 +239,7 @@ impl<T: Config> Pallet<T> {
 		if let Some(imbalance) =
 			Self::make_payout(&ledger.stash, validator_staking_payout + validator_commission_payout)
 		{
+			T::EventListeners::on_reward(&ledger.stash, imbalance.peek());
 			Self::deposit_event(Event::<T>::Rewarded {
 				stash: ledger.stash,
 				amount: imbalance.peek(),
@@ -262,6 +263,7 @@ impl<T: Config> Pallet<T> {
 			if let Some(imbalance) = Self::make_payout(&nominator.who, nominator_reward) {
 				// Note: this logic does not count payouts for `RewardDestination::None`.
 				nominator_payout_count += 1;
+				T::EventListeners::on_reward(&nominator.who, imbalance.peek());
 				let e =
 					Event::<T>::Rewarded { stash: nominator.who.clone(), amount: imbalance.peek() };
 				Self::deposit_event(e);
@@ -276,10 +278,51 @@ impl<T: Config> Pallet<T> {
 
 	/// Update the ledger for a controller.
 	///
-	/// This will also update the stash lock.
+	/// This will also update the stash lock and the [`TotalActiveStake`].
 	pub(crate) fn update_ledger(controller: &T::AccountId, ledger: &StakingLedger<T>) {
+		let prev_active = Self::ledger(controller).map_or(Zero::zero(), |l| l.active);
 		T::Currency::set_lock(STAKING_ID, &ledger.stash, ledger.total, WithdrawReasons::all());
 		<Ledger<T>>::insert(controller, ledger);
+		TotalActiveStake::<T>::mutate(|total| {
+			*total = total.saturating_sub(prev_active).saturating_add(ledger.active);
+		});
+	}
+
+	/// Ensure that `stash`, with `active` bonded, may be chilled by someone other than its
+	/// controller. See [`Call::chill_other`] for the conditions.
+	pub(crate) fn ensure_can_chill_other(
+		stash: &T::AccountId,
+		active: BalanceOf<T>,
+	) -> DispatchResult {
+		if Nominators::<T>::contains_key(stash) && Nominators::<T>::get(stash).is_none() {
+			return Ok(())
+		}
+
+		let threshold = ChillThreshold::<T>::get().ok_or(Error::<T>::CannotChillOther)?;
+		let min_active_bond = if Nominators::<T>::contains_key(stash) {
+			let max_nominator_count =
+				MaxNominatorsCount::<T>::get().ok_or(Error::<T>::CannotChillOther)?;
+			let current_nominator_count = Nominators::<T>::count();
+			ensure!(
+				threshold * max_nominator_count < current_nominator_count,
+				Error::<T>::CannotChillOther
+			);
+			MinNominatorBond::<T>::get()
+		} else if Validators::<T>::contains_key(stash) {
+			let max_validator_count =
+				MaxValidatorsCount::<T>::get().ok_or(Error::<T>::CannotChillOther)?;
+			let current_validator_count = Validators::<T>::count();
+			ensure!(
+				threshold * max_validator_count < current_validator_count,
+				Error::<T>::CannotChillOther
+			);
+			MinValidatorBond::<T>::get()
+		} else {
+			Zero::zero()
+		};
+
+		ensure!(active < min_active_bond, Error::<T>::CannotChillOther);
+		Ok(())
 	}
 
 	/// Chill a stash account.
@@ -664,7 +707,9 @@ impl<T: Config> Pallet<T> {
 		slashing::clear_stash_metadata::<T>(stash, num_slashing_spans)?;
 
 		<Bonded<T>>::remove(stash);
-		<Ledger<T>>::remove(&controller);
+		if let Some(ledger) = <Ledger<T>>::take(&controller) {
+			TotalActiveStake::<T>::mutate(|total| *total = total.saturating_sub(ledger.active));
+		}
 
 		<Payee<T>>::remove(stash);
 		Self::do_remove_validator(stash);
@@ -1124,6 +1169,7 @@ impl<T: Config> ElectionDataProvider for Pallet<T> {
 				claimed_rewards: Default::default(),
 			},
 		);
+		TotalActiveStake::<T>::mutate(|total| *total = total.saturating_add(stake));
 
 		Self::do_add_nominator(&voter, Nominations { targets, submitted_in: 0, suppressed: false });
 	}
@@ -1142,6 +1188,7 @@ impl<T: Config> ElectionDataProvider for Pallet<T> {
 				claimed_rewards: Default::default(),
 			},
 		);
+		TotalActiveStake::<T>::mutate(|total| *total = total.saturating_add(stake));
 		Self::do_add_validator(
 			&target,
 			ValidatorPrefs { commission: Perbill::zero(), blocked: false },
@@ -1154,6 +1201,7 @@ impl<T: Config> ElectionDataProvider for Pallet<T> {
 		<Bonded<T>>::remove_all(None);
 		#[allow(deprecated)]
 		<Ledger<T>>::remove_all(None);
+		TotalActiveStake::<T>::kill();
 		#[allow(deprecated)]
 		<Validators<T>>::remove_all();
 		#[allow(deprecated)]
@@ -1183,6 +1231,7 @@ impl<T: Config> ElectionDataProvider for Pallet<T> {
 					claimed_rewards: Default::default(),
 				},
 			);
+			TotalActiveStake::<T>::mutate(|total| *total = total.saturating_add(stake));
 			Self::do_add_validator(
 				&v,
 				ValidatorPrefs { commission: Perbill::zero(), blocked: false },
@@ -1204,6 +1253,7 @@ impl<T: Config> ElectionDataProvider for Pallet<T> {
 					claimed_rewards: Default::default(),
 				},
 			);
+			TotalActiveStake::<T>::mutate(|total| *total = total.saturating_add(stake));
 			Self::do_add_nominator(
 				&v,
 				Nominations { targets: t, submitted_in: 0, suppressed: false },
@@ -1408,7 +1458,7 @@ where
 					// Apply right away.
 					slashing::apply_slash::<T>(unapplied, slash_era);
 					{
-						let slash_cost = (6, 5);
+						let slash_cost = (7, 6);
 						let reward_cost = (2, 2);
 						add_db_reads_writes(
 							(1 + nominators_len) * slash_cost.0 + reward_cost.0 * reporters_len,
@@ -1456,9 +1506,12 @@ impl<T: Config> ScoreProvider<T::AccountId> for Pallet<T> {
 			None => StakingLedger::default_from(who.clone()),
 			Some(l) => l,
 		};
-		ledger.active = active;
+		let prev_active = sp_std::mem::replace(&mut ledger.active, active);
 
 		<Ledger<T>>::insert(who, ledger);
+		TotalActiveStake::<T>::mutate(|total| {
+			*total = total.saturating_sub(prev_active).saturating_add(active);
+		});
 		<Bonded<T>>::insert(who, who);
 
 		// also, we play a trick to make sure that a issuance based-`CurrencyToVote` behaves well:
@@ -1631,6 +1684,10 @@ impl<T: Config> StakingInterface for Pallet<T> {
 		MinValidatorBond::<T>::get()
 	}
 
+	fn minimum_active_stake() -> Self::Balance {
+		MinimumActiveStake::<T>::get()
+	}
+
 	fn desired_validator_count() -> u32 {
 		ValidatorCount::<T>::get()
 	}
@@ -1664,6 +1721,14 @@ impl<T: Config> StakingInterface for Pallet<T> {
 		Self::current_era().unwrap_or(Zero::zero())
 	}
 
+	fn active_era() -> EraIndex {
+		Self::active_era().map(|era| era.index).unwrap_or(Zero::zero())
+	}
+
+	fn total_staked() -> Self::Balance {
+		TotalActiveStake::<T>::get()
+	}
+
 	fn stake(who: &Self::AccountId) -> Result<Stake<BalanceOf<T>>, DispatchError> {
 		Self::bonded(who)
 			.and_then(|c| Self::ledger(c))
@@ -1671,6 +1736,13 @@ impl<T: Config> StakingInterface for Pallet<T> {
 			.ok_or(Error::<T>::NotStash.into())
 	}
 
+	fn unlocking(who: &Self::AccountId) -> Result<Vec<(EraIndex, Self::Balance)>, DispatchError> {
+		Self::bonded(who)
+			.and_then(|c| Self::ledger(c))
+			.map(|l| l.unlocking.iter().map(|chunk| (chunk.era, chunk.value)).collect())
+			.ok_or(Error::<T>::NotStash.into())
+	}
+
 	fn bond_extra(who: &Self::AccountId, extra: Self::Balance) -> DispatchResult {
 		Self::bond_extra(RawOrigin::Signed(who.clone()).into(), extra)
 	}
@@ -1689,6 +1761,32 @@ impl<T: Config> StakingInterface for Pallet<T> {
 		Self::chill(RawOrigin::Signed(ctrl).into())
 	}
 
+	fn validators_paged(
+		start_key: Option<Vec<u8>>,
+		limit: u32,
+	) -> (Vec<Self::AccountId>, Option<Vec<u8>>) {
+		let iter = match start_key {
+			Some(key) => Validators::<T>::iter_from(key),
+			None => Validators::<T>::iter(),
+		};
+		let page: Vec<_> = iter.take(limit as usize).map(|(stash, _)| stash).collect();
+		let next_key = match page.last() {
+			Some(last) if page.len() == limit as usize =>
+				Some(Validators::<T>::hashed_key_for(last)),
+			_ => None,
+		};
+		(page, next_key)
+	}
+
+	fn chill_other(who: &Self::AccountId) -> DispatchResult {
+		let ctrl = Self::bonded(who).ok_or(Error::<T>::NotStash)?;
+		let ledger = Self::ledger(&ctrl).ok_or(Error::<T>::NotController)?;
+		Self::ensure_can_chill_other(who, ledger.active)?;
+
+		Self::chill_stash(who);
+		Ok(())
+	}
+
 	fn withdraw_unbonded(
 		who: Self::AccountId,
 		num_slashing_spans: u32,
@@ -1711,6 +1809,16 @@ impl<T: Config> StakingInterface for Pallet<T> {
 		)
 	}
 
+	fn payee(who: &Self::AccountId) -> Result<Self::AccountId, DispatchError> {
+		let controller = Self::bonded(who).ok_or(Error::<T>::NotStash)?;
+		match Payee::<T>::get(who) {
+			RewardDestination::Staked | RewardDestination::Stash => Ok(who.clone()),
+			RewardDestination::Controller => Ok(controller),
+			RewardDestination::Account(account) => Ok(account),
+			RewardDestination::None => Err(Error::<T>::NoRewardDestination.into()),
+		}
+	}
+
 	fn nominate(who: &Self::AccountId, targets: Vec<Self::AccountId>) -> DispatchResult {
 		let ctrl = Self::bonded(who).ok_or(Error::<T>::NotStash)?;
 		let targets = targets.into_iter().map(T::Lookup::unlookup).collect::<Vec<_>>();
@@ -1778,6 +1886,7 @@ impl<T: Config> Pallet<T> {
 		Self::check_nominators()?;
 		Self::check_exposures()?;
 		Self::check_ledgers()?;
+		Self::check_total_active_stake()?;
 		Self::check_count()
 	}
 
@@ -1806,6 +1915,13 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	fn check_total_active_stake() -> Result<(), TryRuntimeError> {
+		let total = Ledger::<T>::iter_values()
+			.fold(Zero::zero(), |acc: BalanceOf<T>, l| acc.saturating_add(l.active));
+		ensure!(TotalActiveStake::<T>::get() == total, "wrong total active stake");
+		Ok(())
+	}
+
 	fn check_exposures() -> Result<(), TryRuntimeError> {
 		// a check per validator to ensure the exposure struct is always sane.
 		let era = Self::active_era().unwrap().index;
@@ -36,7 +36,7 @@ use sp_runtime::{
 	traits::{CheckedSub, SaturatedConversion, StaticLookup, Zero},
 	ArithmeticError, Perbill, Percent,
 };
-use sp_staking::{EraIndex, SessionIndex};
+use sp_staking::{EraIndex, OnStakingUpdate, SessionIndex, Stake};
 use sp_std::prelude::*;
 
 mod impls;
@@ -65,7 +65,7 @@ pub mod pallet {
 	use super::*;
 
 	/// The current storage version.
-	const STORAGE_VERSION: StorageVersion = StorageVersion::new(13);
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(14);
 
 	#[pallet::pallet]
 	#[pallet::storage_version(STORAGE_VERSION)]
@@ -310,6 +310,10 @@ pub mod pallet {
 	#[pallet::storage]
 	pub type MinimumActiveStake<T> = StorageValue<_, BalanceOf<T>, ValueQuery>;
 
+	/// The sum of the active stake of all ledgers, i.e. excluding any funds being unbonded.
+	#[pallet::storage]
+	pub type TotalActiveStake<T> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+
 	/// The minimum amount of commission that validators can set.
 	///
 	/// If set to `0`, no limit exists.
@@ -760,6 +764,8 @@ pub mod pallet {
 		CommissionTooLow,
 		/// Some bound is not met.
 		BoundNotMet,
+		/// The stash receives no rewards.
+		NoRewardDestination,
 	}
 
 	#[pallet::hooks]
@@ -879,6 +885,7 @@ pub mod pallet {
 					.defensive_map_err(|_| Error::<T>::BoundNotMet)?,
 			};
 			Self::update_ledger(&controller_to_be_deprecated, &item);
+			T::EventListeners::on_stake_update(&stash, None);
 			Ok(())
 		}
 
@@ -910,6 +917,7 @@ pub mod pallet {
 			let stash_balance = T::Currency::free_balance(&stash);
 			if let Some(extra) = stash_balance.checked_sub(&ledger.total) {
 				let extra = extra.min(max_additional);
+				let prev_stake = Stake { total: ledger.total, active: ledger.active };
 				ledger.total += extra;
 				ledger.active += extra;
 				// Last check: the new active amount of ledger must be more than ED.
@@ -926,6 +934,9 @@ pub mod pallet {
 						T::VoterList::on_update(&stash, Self::weight_of(&ledger.stash)).defensive();
 				}
 
+				T::EventListeners::on_stake_update(&stash, Some(prev_stake));
+				T::EventListeners::on_bond_extra(&stash, extra);
+
 				Self::deposit_event(Event::<T>::Bonded { stash, amount: extra });
 			}
 			Ok(())
@@ -1706,36 +1717,8 @@ pub mod pallet {
 			//
 			// Otherwise, if caller is the same as the controller, this is just like `chill`.
 
-			if Nominators::<T>::contains_key(&stash) && Nominators::<T>::get(&stash).is_none() {
-				Self::chill_stash(&stash);
-				return Ok(())
-			}
-
 			if caller != controller {
-				let threshold = ChillThreshold::<T>::get().ok_or(Error::<T>::CannotChillOther)?;
-				let min_active_bond = if Nominators::<T>::contains_key(&stash) {
-					let max_nominator_count =
-						MaxNominatorsCount::<T>::get().ok_or(Error::<T>::CannotChillOther)?;
-					let current_nominator_count = Nominators::<T>::count();
-					ensure!(
-						threshold * max_nominator_count < current_nominator_count,
-						Error::<T>::CannotChillOther
-					);
-					MinNominatorBond::<T>::get()
-				} else if Validators::<T>::contains_key(&stash) {
-					let max_validator_count =
-						MaxValidatorsCount::<T>::get().ok_or(Error::<T>::CannotChillOther)?;
-					let current_validator_count = Validators::<T>::count();
-					ensure!(
-						threshold * max_validator_count < current_validator_count,
-						Error::<T>::CannotChillOther
-					);
-					MinValidatorBond::<T>::get()
-				} else {
-					Zero::zero()
-				};
-
-				ensure!(ledger.active < min_active_bond, Error::<T>::CannotChillOther);
+				Self::ensure_can_chill_other(&stash, ledger.active)?;
 			}
 
 			Self::chill_stash(&stash);
@@ -20,7 +20,7 @@
 use super::{ConfigOp, Event, *};
 use frame_election_provider_support::{
 	bounds::{DataProviderBounds, ElectionBoundsBuilder},
-	ElectionProvider, SortedListProvider, Support,
+	ElectionDataProvider, ElectionProvider, SortedListProvider, Support,
 };
 use frame_support::{
 	assert_noop, assert_ok, assert_storage_noop,
@@ -37,7 +37,7 @@ use sp_runtime::{
 };
 use sp_staking::{
 	offence::{DisableStrategy, OffenceDetails, OnOffenceHandler},
-	SessionIndex,
+	SessionIndex, Stake,
 };
 use sp_std::prelude::*;
 use substrate_test_utils::assert_eq_uvec;
@@ -1724,6 +1724,71 @@ fn rebond_emits_right_value_in_event() {
 	});
 }
 
+#[test]
+fn on_stake_update_is_fired_from_the_bond_paths() {
+	ExtBuilder::default().build_and_execute(|| {
+		let _ = Balances::make_free_balance_be(&1, 1000);
+		// ignore the updates of the genesis bonds.
+		StakeUpdates::take();
+
+		// a new bond has no previous stake.
+		assert_ok!(Staking::bond(RuntimeOrigin::signed(1), 500, RewardDestination::Stash));
+		assert_eq!(StakeUpdates::take(), vec![(1, None)]);
+
+		// bonding more reports the stake before the extra funds.
+		assert_ok!(Staking::bond_extra(RuntimeOrigin::signed(1), 200));
+		assert_eq!(StakeUpdates::take(), vec![(1, Some(Stake { total: 500, active: 500 }))]);
+
+		// other ledger updates are not reported.
+		assert_ok!(Staking::unbond(RuntimeOrigin::signed(1), 100));
+		assert_ok!(Staking::rebond(RuntimeOrigin::signed(1), 100));
+		assert!(StakeUpdates::get().is_empty());
+	});
+}
+
+#[test]
+fn on_bond_extra_is_fired_along_with_on_stake_update() {
+	ExtBuilder::default().build_and_execute(|| {
+		let _ = Balances::make_free_balance_be(&11, 1000000);
+		// ignore the updates of the genesis bonds.
+		StakeUpdates::take();
+
+		assert_ok!(Staking::bond_extra(RuntimeOrigin::signed(11), 100));
+
+		assert_eq!(BondedExtra::get(), vec![(11, 100)]);
+		let prev_stake = Stake { total: 1000, active: 1000 };
+		assert_eq!(StakeUpdates::get(), vec![(11, Some(prev_stake))]);
+		// the delta seen by `on_bond_extra` is the change of the stake.
+		let ledger = Staking::ledger(&11).unwrap();
+		assert_eq!(ledger.total - prev_stake.total, 100);
+		assert_eq!(ledger.active - prev_stake.active, 100);
+	});
+}
+
+#[test]
+fn on_reward_is_fired_for_every_payout() {
+	ExtBuilder::default().nominate(true).build_and_execute(|| {
+		Pallet::<Test>::reward_by_ids(vec![(11, 1)]);
+		Pallet::<Test>::reward_by_ids(vec![(21, 1)]);
+
+		mock::start_active_era(1);
+		let _ = staking_events_since_last_call();
+		assert!(RewardsReceived::get().is_empty());
+
+		mock::make_all_reward_payment(0);
+		let rewarded: Vec<_> = staking_events_since_last_call()
+			.into_iter()
+			.filter_map(|e| match e {
+				Event::Rewarded { stash, amount } => Some((stash, amount)),
+				_ => None,
+			})
+			.collect();
+
+		assert!(!rewarded.is_empty());
+		assert_eq!(RewardsReceived::get(), rewarded);
+	});
+}
+
 #[test]
 fn reward_to_stake_works() {
 	ExtBuilder::default()
@@ -1745,9 +1810,9 @@ fn reward_to_stake_works() {
 
 			// Bypass logic and change current exposure
 			ErasStakers::<Test>::insert(0, 21, Exposure { total: 69, own: 69, others: vec![] });
-			<Ledger<Test>>::insert(
+			Staking::update_ledger(
 				&20,
-				StakingLedger {
+				&StakingLedger {
 					stash: 21,
 					total: 69,
 					active: 69,
@@ -1806,9 +1871,9 @@ fn reap_stash_works() {
 
 			// no easy way to cause an account to go below ED, we tweak their staking ledger
 			// instead.
-			Ledger::<Test>::insert(
-				11,
-				StakingLedger {
+			Staking::update_ledger(
+				&11,
+				&StakingLedger {
 					stash: 11,
 					total: 5,
 					active: 5,
@@ -4156,9 +4221,9 @@ fn offences_weight_calculated_correctly() {
 		 +
 			<Test as frame_system::Config>::DbWeight::get().reads_writes(rw, rw)
 			// One `slash_cost`
-			+ <Test as frame_system::Config>::DbWeight::get().reads_writes(6, 5)
+			+ <Test as frame_system::Config>::DbWeight::get().reads_writes(7, 6)
 			// `slash_cost` * nominators (1)
-			+ <Test as frame_system::Config>::DbWeight::get().reads_writes(6, 5)
+			+ <Test as frame_system::Config>::DbWeight::get().reads_writes(7, 6)
 			// `reward_cost` * reporters (1)
 			+ <Test as frame_system::Config>::DbWeight::get().reads_writes(2, 2)
 		;
@@ -6052,11 +6117,99 @@ fn set_min_commission_works_with_admin_origin() {
 }
 
 mod staking_interface {
-	use frame_support::storage::with_storage_layer;
-	use sp_staking::StakingInterface;
+	use frame_support::{parameter_types, storage::with_storage_layer};
+	use sp_staking::{offence::apply_offence_slash, OnStakingUpdate, StakingInterface};
 
 	use super::*;
 
+	parameter_types! {
+		static SlashesReceived: Vec<(AccountId, Balance, BTreeMap<EraIndex, Balance>)> = vec![];
+	}
+
+	struct SlashRecorder;
+	impl OnStakingUpdate<AccountId, Balance> for SlashRecorder {
+		fn on_slash(
+			stash: &AccountId,
+			slashed_active: Balance,
+			slashed_unlocking: &BTreeMap<EraIndex, Balance>,
+		) {
+			SlashesReceived::mutate(|s| {
+				s.push((*stash, slashed_active, slashed_unlocking.clone()))
+			});
+		}
+	}
+
+	#[test]
+	fn apply_offence_slash_reports_post_slash_balances() {
+		ExtBuilder::default().build_and_execute(|| {
+			let era = active_era();
+			assert_ok!(<Staking as StakingInterface>::unbond(&11, 100));
+			start_active_era(era + 1);
+			assert_ok!(<Staking as StakingInterface>::unbond(&11, 100));
+			assert_ok!(<Staking as StakingInterface>::unbond(&21, 200));
+			assert_eq!(
+				<Staking as StakingInterface>::unlocking(&11).unwrap(),
+				vec![(era + 3, 100), (era + 4, 100)]
+			);
+
+			let offenders = [11, 21, 1337].map(|who| OffenceDetails {
+				offender: (who, ()),
+				reporters: Vec::<AccountId>::new(),
+			});
+			let fractions = [Perbill::from_percent(10), Perbill::from_percent(50), Perbill::one()];
+
+			// an offence in the active era.
+			let slashed = apply_offence_slash::<Staking, SlashRecorder, _, _>(
+				&offenders,
+				&fractions,
+				era + 1,
+			);
+
+			// 1337 is not staking and is skipped.
+			assert_eq!(slashed, vec![(11, 90), (21, 500)]);
+			// the chunk unbonded before the offence era is not exposed and left untouched.
+			assert_eq!(
+				SlashesReceived::take(),
+				vec![
+					(11, 720, BTreeMap::from([(era + 4, 90)])),
+					(21, 400, BTreeMap::from([(era + 4, 100)])),
+				]
+			);
+
+			// an offence in the previous era, when both chunks of 11 were still exposed.
+			let slashed = apply_offence_slash::<Staking, SlashRecorder, _, _>(
+				&offenders[..1],
+				&fractions[..1],
+				era,
+			);
+			assert_eq!(slashed, vec![(11, 100)]);
+			assert_eq!(
+				SlashesReceived::take(),
+				vec![(11, 720, BTreeMap::from([(era + 3, 90), (era + 4, 90)]))]
+			);
+		});
+	}
+
+	#[test]
+	fn payee_is_the_reward_destination() {
+		ExtBuilder::default().build_and_execute(|| {
+			// 11 is bonded with rewards paid into the stash.
+			assert_eq!(Staking::payee(&11), RewardDestination::Staked);
+			assert_eq!(<Staking as StakingInterface>::payee(&11), Ok(11));
+
+			let _ = Balances::make_free_balance_be(&61, 1000);
+			assert_ok!(<Staking as StakingInterface>::bond(&61, 500, &71));
+			assert_eq!(<Staking as StakingInterface>::payee(&61), Ok(71));
+
+			assert_ok!(Staking::set_payee(RuntimeOrigin::signed(61), RewardDestination::None));
+			assert_noop!(
+				<Staking as StakingInterface>::payee(&61),
+				Error::<Test>::NoRewardDestination
+			);
+			assert_noop!(<Staking as StakingInterface>::payee(&71), Error::<Test>::NotStash);
+		});
+	}
+
 	#[test]
 	fn force_unstake_with_slash_works() {
 		ExtBuilder::default().build_and_execute(|| {
@@ -6123,4 +6276,221 @@ mod staking_interface {
 			assert!(Staking::status(&42).is_err());
 		})
 	}
+
+	#[test]
+	fn chill_other_checks_like_the_extrinsic() {
+		ExtBuilder::default().build_and_execute(|| {
+			// nominator 101 and validator 11 are bonded enough.
+			assert_noop!(
+				<Staking as StakingInterface>::chill_other(&101),
+				Error::<Test>::CannotChillOther
+			);
+			assert_noop!(
+				<Staking as StakingInterface>::chill_other(&11),
+				Error::<Test>::CannotChillOther
+			);
+			// chilled accounts have no minimum to fall below.
+			assert_noop!(
+				<Staking as StakingInterface>::chill_other(&41),
+				Error::<Test>::CannotChillOther
+			);
+
+			// raising the minimum leaves 101 under-bonded, 11 is still bonded enough.
+			MinNominatorBond::<Test>::put(1_000);
+			MinValidatorBond::<Test>::put(1_000);
+			// but no one can be chilled by others until the chill threshold is reached.
+			assert_noop!(
+				<Staking as StakingInterface>::chill_other(&101),
+				Error::<Test>::CannotChillOther
+			);
+			ChillThreshold::<Test>::put(Percent::from_percent(10));
+			assert_noop!(
+				<Staking as StakingInterface>::chill_other(&101),
+				Error::<Test>::CannotChillOther
+			);
+			MaxNominatorsCount::<Test>::put(10);
+			assert_noop!(
+				<Staking as StakingInterface>::chill_other(&101),
+				Error::<Test>::CannotChillOther
+			);
+
+			MaxNominatorsCount::<Test>::put(1);
+			MaxValidatorsCount::<Test>::put(1);
+			assert_ok!(<Staking as StakingInterface>::chill_other(&101));
+			assert_eq!(Staking::status(&101).unwrap(), StakerStatus::Idle);
+			assert_noop!(
+				<Staking as StakingInterface>::chill_other(&11),
+				Error::<Test>::CannotChillOther
+			);
+		})
+	}
+
+	#[test]
+	fn active_era_lags_the_planned_era() {
+		ExtBuilder::default().period(1).build_and_execute(|| {
+			assert_eq!(<Staking as StakingInterface>::current_era(), 0);
+			assert_eq!(<Staking as StakingInterface>::active_era(), 0);
+
+			// the next era is planned a session before it starts.
+			start_session(2);
+			assert_eq!(<Staking as StakingInterface>::current_era(), 1);
+			assert_eq!(<Staking as StakingInterface>::active_era(), 0);
+
+			start_session(3);
+			assert_eq!(<Staking as StakingInterface>::current_era(), 1);
+			assert_eq!(<Staking as StakingInterface>::active_era(), 1);
+		})
+	}
+
+	#[test]
+	fn validators_paged_enumerates_the_candidates() {
+		ExtBuilder::default().build_and_execute(|| {
+			bond_validator(51, 1000);
+			bond_validator(61, 1000);
+
+			let (all, next_key) = <Staking as StakingInterface>::validators_paged(None, 10);
+			assert!(next_key.is_none());
+			let mut sorted = all.clone();
+			sorted.sort();
+			// 41 is chilled, nominators are not validators.
+			assert_eq!(sorted, vec![11, 21, 31, 51, 61]);
+
+			// pages follow the same order, each starting after the last one.
+			let mut pages = vec![];
+			let mut start_key = None;
+			loop {
+				let (page, next_key) =
+					<Staking as StakingInterface>::validators_paged(start_key, 2);
+				pages.extend(page);
+				match next_key {
+					Some(key) => start_key = Some(key),
+					None => break,
+				}
+			}
+			assert_eq!(pages, all);
+			assert_eq!(<Staking as StakingInterface>::validators_paged(None, 0), (vec![], None));
+		})
+	}
+
+	#[test]
+	fn minimum_active_stake_follows_elections() {
+		ExtBuilder::default()
+			.nominate(false)
+			.add_staker(61, 61, 2_000, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.add_staker(71, 71, 10, StakerStatus::<AccountId>::Nominator(vec![21]))
+			.build_and_execute(|| {
+				assert_ok!(<Staking as ElectionDataProvider>::electing_voters(
+					DataProviderBounds::default()
+				));
+				assert_eq!(<Staking as StakingInterface>::minimum_active_stake(), 10);
+
+				// bonding more isn't reflected until the next election.
+				assert_ok!(Staking::bond_extra(RuntimeOrigin::signed(71), 90));
+				assert_eq!(<Staking as StakingInterface>::minimum_active_stake(), 10);
+				assert_ok!(<Staking as ElectionDataProvider>::electing_voters(
+					DataProviderBounds::default()
+				));
+				assert_eq!(<Staking as StakingInterface>::minimum_active_stake(), 100);
+			});
+	}
+
+	#[test]
+	fn exposed_eras_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			// 42 nominates 11 in eras 2 and 4 only.
+			let exposure = |era: EraIndex| {
+				let mut exposure = Staking::eras_stakers(era, 11);
+				exposure.others.push(IndividualExposure { who: 42, value: 100 });
+				exposure
+			};
+			ErasStakers::<Test>::insert(2, 11, exposure(2));
+			ErasStakers::<Test>::insert(4, 11, exposure(4));
+
+			assert_eq!(
+				<Staking as StakingInterface>::exposed_eras(&42, &[0, 1, 2, 3, 4, 5]),
+				vec![2, 4]
+			);
+			assert_eq!(<Staking as StakingInterface>::exposed_eras(&42, &[0, 1, 3]), Vec::<EraIndex>::new());
+			assert_eq!(<Staking as StakingInterface>::exposed_eras(&42, &[]), Vec::<EraIndex>::new());
+
+			// the validator itself is exposed in every era it was elected in.
+			assert_eq!(
+				<Staking as StakingInterface>::exposed_eras(&11, &[0, 2, 4, 5]),
+				vec![0, 2, 4]
+			);
+		})
+	}
+
+	#[test]
+	fn total_staked_is_sum_of_active_stake() {
+		ExtBuilder::default().build_and_execute(|| {
+			let sum_of_active = || Ledger::<Test>::iter_values().map(|l| l.active).sum::<Balance>();
+			assert!(<Staking as StakingInterface>::total_staked() > 0);
+			assert_eq!(<Staking as StakingInterface>::total_staked(), sum_of_active());
+
+			// a new staker adds to the total.
+			let before = <Staking as StakingInterface>::total_staked();
+			bond_nominator(61, 500, vec![11]);
+			assert_eq!(<Staking as StakingInterface>::total_staked(), before + 500);
+
+			// unbonding funds are not included.
+			assert_ok!(<Staking as StakingInterface>::unbond(&61, 200));
+			assert_eq!(<Staking as StakingInterface>::total_staked(), before + 300);
+			assert_eq!(<Staking as StakingInterface>::total_staked(), sum_of_active());
+
+			// slashed funds are not either.
+			on_offence_now(
+				&[OffenceDetails {
+					offender: (11, Staking::eras_stakers(active_era(), 11)),
+					reporters: vec![],
+				}],
+				&[Perbill::from_percent(10)],
+			);
+			assert!(<Staking as StakingInterface>::total_staked() < before + 300);
+			assert_eq!(<Staking as StakingInterface>::total_staked(), sum_of_active());
+
+			// a stash that leaves takes its stake with it.
+			assert_ok!(<Staking as StakingInterface>::chill(&61));
+			assert_ok!(<Staking as StakingInterface>::unbond(&61, 300));
+			start_active_era(active_era() + BondingDuration::get());
+			assert_ok!(<Staking as StakingInterface>::withdraw_unbonded(61, 0), true);
+			assert_eq!(<Staking as StakingInterface>::total_staked(), sum_of_active());
+		})
+	}
+
+	#[test]
+	fn eras_to_next_withdraw_works() {
+		ExtBuilder::default().build_and_execute(|| {
+			let bonding_duration = <Staking as StakingInterface>::bonding_duration();
+
+			// not a staker.
+			assert!(<Staking as StakingInterface>::eras_to_next_withdraw(&42).is_err());
+			// nothing is unbonding.
+			assert_eq!(<Staking as StakingInterface>::eras_to_next_withdraw(&11), Ok(None));
+
+			assert_ok!(<Staking as StakingInterface>::unbond(&11, 100));
+			assert_eq!(
+				<Staking as StakingInterface>::eras_to_next_withdraw(&11),
+				Ok(Some(bonding_duration))
+			);
+
+			// a later chunk does not change the countdown to the earliest one.
+			start_active_era(1);
+			assert_ok!(<Staking as StakingInterface>::unbond(&11, 100));
+			assert_eq!(
+				<Staking as StakingInterface>::unlocking(&11),
+				Ok(vec![(bonding_duration, 100), (bonding_duration + 1, 100)])
+			);
+			assert_eq!(
+				<Staking as StakingInterface>::eras_to_next_withdraw(&11),
+				Ok(Some(bonding_duration - 1))
+			);
+
+			// the first chunk is withdrawable, the second one is next.
+			start_active_era(bonding_duration);
+			assert_eq!(<Staking as StakingInterface>::eras_to_next_withdraw(&11), Ok(Some(0)));
+			assert_ok!(<Staking as StakingInterface>::withdraw_unbonded(11, 0));
+			assert_eq!(<Staking as StakingInterface>::eras_to_next_withdraw(&11), Ok(Some(1)));
+		})
+	}
 }
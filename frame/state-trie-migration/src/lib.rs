@@ -1124,6 +1124,7 @@ mod mock {
 		type ReserveIdentifier = [u8; 8];
 		type WeightInfo = ();
 		type FreezeIdentifier = ();
+		type OnFreezeViolation = ();
 		type MaxFreezes = ();
 		type RuntimeHoldReason = ();
 		type MaxHolds = ();
@@ -17,8 +17,13 @@
 
 //! The traits for putting freezes within a single fungible token class.
 
+use crate::traits::tokens::{
+	Fortitude::{Force, Polite},
+	Preservation::Expendable,
+};
+use codec::Encode;
 use scale_info::TypeInfo;
-use sp_runtime::DispatchResult;
+use sp_runtime::{DispatchError, DispatchResult, Permill, Saturating};
 
 /// Trait for inspecting a fungible asset which can be frozen. Freezing is essentially setting a
 /// minimum balance bellow which the total balance (inclusive of any funds placed on hold) may not
@@ -41,6 +46,26 @@ pub trait Inspect<AccountId>: super::Inspect<AccountId> {
 	/// account of `who`. This will be true as long as the implementor supports as many
 	/// concurrent freeze locks as there are possible values of `id`.
 	fn can_freeze(id: &Self::Id, who: &AccountId) -> bool;
+
+	/// The total amount of the balance of `who` which is frozen.
+	///
+	/// Freezes overlap rather than stack, so this is the largest of the individual freezes rather
+	/// than their sum.
+	///
+	/// Defaults to the part of the balance that only the freezes keep from being withdrawn. This
+	/// is lower than the largest freeze where it overlaps funds on hold or the existential
+	/// deposit, so implementors keeping track of their freezes should override it.
+	fn balance_frozen_total(who: &AccountId) -> Self::Balance {
+		let force = Self::reducible_balance(who, Expendable, Force);
+		force.saturating_sub(Self::reducible_balance(who, Expendable, Polite))
+	}
+
+	/// The amount of the total balance of `who` which is not frozen, saturating at zero.
+	///
+	/// Note that funds on hold are part of the total balance, so they are included in the result.
+	fn spendable_after_freezes(who: &AccountId) -> Self::Balance {
+		Self::total_balance(who).saturating_sub(Self::balance_frozen_total(who))
+	}
 }
 
 /// Trait for introducing, altering and removing locks to freeze an account's funds so they never
@@ -65,4 +90,54 @@ pub trait Mutate<AccountId>: Inspect<AccountId> {
 
 	/// Remove an existing lock.
 	fn thaw(id: &Self::Id, who: &AccountId) -> DispatchResult;
+
+	/// Like `set_freeze`, freezing `percent` of the `balance_freezable` of `who`. Returns the
+	/// amount frozen.
+	///
+	/// The amount is taken once, when this is called: the freeze does not follow later changes of
+	/// the balance of `who`.
+	fn set_freeze_percent(
+		id: &Self::Id,
+		who: &AccountId,
+		percent: Permill,
+	) -> Result<Self::Balance, DispatchError> {
+		let amount = percent * Self::balance_freezable(who);
+		Self::set_freeze(id, who, amount)?;
+		Ok(amount)
+	}
+
+	/// Move the freeze of `who` under `old_id` to `new_id`, thawing `old_id`. If there already is
+	/// a freeze under `new_id`, the larger of the two amounts is kept.
+	///
+	/// The new freeze is in place before the old one is removed, so the funds of `who` are never
+	/// left unfrozen in between. Should the thaw fail, the new freeze is not rolled back, so this
+	/// is best called from within a storage layer.
+	fn migrate_freeze(old_id: &Self::Id, new_id: &Self::Id, who: &AccountId) -> DispatchResult {
+		if old_id.encode() == new_id.encode() {
+			return Ok(())
+		}
+		Self::extend_freeze(new_id, who, Self::balance_frozen(old_id, who))?;
+		Self::thaw(old_id, who)
+	}
+}
+
+/// Hook for an attempt to reduce the balance of an account below what one of its freezes holds.
+///
+/// This is how freezers "provide an update function": the implementor of the freeze traits calls
+/// it for every freeze of `who` that stands in the way of a polite reduction, once the reduction
+/// has been refused and only if no lock would refuse it as well. It then checks the reduction
+/// again. A freezer which no longer needs its funds frozen may therefore thaw or reduce its freeze
+/// from the hook and let the reduction go ahead, in which case its storage changes persist along
+/// with it.
+///
+/// If the reduction is refused anyway, any storage changes made by the hook are reverted with it
+/// whenever it runs inside a storage layer, as all dispatchables do. The hook is never called
+/// while the balance of `who` is being changed, so it may thaw or adjust freezes of `who`, but it
+/// must not itself move funds of `who` in the same fungible.
+pub trait OnFreezeViolation<Id, AccountId, Balance> {
+	/// The reduction of the balance of `who` by `amount` was refused because `frozen` of it is
+	/// frozen under `id`.
+	fn on_freeze_violation(_id: &Id, _who: &AccountId, _amount: Balance, _frozen: Balance) {}
 }
+
+impl<Id, AccountId, Balance> OnFreezeViolation<Id, AccountId, Balance> for () {}
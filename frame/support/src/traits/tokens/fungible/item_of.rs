@@ -120,6 +120,9 @@ impl<
 	fn can_freeze(id: &Self::Id, who: &AccountId) -> bool {
 		<F as fungibles::InspectFreeze<AccountId>>::can_freeze(A::get(), id, who)
 	}
+	fn balance_frozen_total(who: &AccountId) -> Self::Balance {
+		<F as fungibles::InspectFreeze<AccountId>>::balance_frozen_total(A::get(), who)
+	}
 }
 
 impl<
@@ -45,7 +45,7 @@ mod imbalance;
 mod item_of;
 mod regular;
 
-pub use freeze::{Inspect as InspectFreeze, Mutate as MutateFreeze};
+pub use freeze::{Inspect as InspectFreeze, Mutate as MutateFreeze, OnFreezeViolation};
 pub use hold::{
 	Balanced as BalancedHold, Inspect as InspectHold, Mutate as MutateHold,
 	Unbalanced as UnbalancedHold,
@@ -180,8 +180,11 @@ pub trait Unbalanced<AccountId>: Inspect<AccountId> {
 		preservation: Preservation,
 		force: Fortitude,
 	) -> Result<Self::Balance, DispatchError> {
+		let mut free = Self::reducible_balance(who, preservation, force);
+		if free < amount && Self::notify_freezers(who, amount, preservation, force) {
+			free = Self::reducible_balance(who, preservation, force);
+		}
 		let old_balance = Self::balance(who);
-		let free = Self::reducible_balance(who, preservation, force);
 		if let BestEffort = precision {
 			amount = amount.min(free);
 		}
@@ -228,6 +231,20 @@ pub trait Unbalanced<AccountId>: Inspect<AccountId> {
 		}
 	}
 
+	/// Called when taking `amount` out of the balance of `who` is about to be refused. If the
+	/// freezes of `who` are what refuses it, the implementor gives their freezers a chance to lift
+	/// them, see [`super::freeze::OnFreezeViolation`].
+	///
+	/// Returns `true` if any freezer was told, in which case the reduction is checked again.
+	fn notify_freezers(
+		_who: &AccountId,
+		_amount: Self::Balance,
+		_preservation: Preservation,
+		_force: Fortitude,
+	) -> bool {
+		false
+	}
+
 	/// Reduce the active issuance by some amount.
 	fn deactivate(_: Self::Balance) {}
 
@@ -256,7 +273,11 @@ pub trait Mutate<AccountId>: Inspect<AccountId> + Unbalanced<AccountId> {
 		precision: Precision,
 		force: Fortitude,
 	) -> Result<Self::Balance, DispatchError> {
-		let actual = Self::reducible_balance(who, Expendable, force).min(amount);
+		let mut reducible = Self::reducible_balance(who, Expendable, force);
+		if reducible < amount && Self::notify_freezers(who, amount, Expendable, force) {
+			reducible = Self::reducible_balance(who, Expendable, force);
+		}
+		let actual = reducible.min(amount);
 		ensure!(actual == amount || precision == BestEffort, TokenError::FundsUnavailable);
 		Self::total_issuance().checked_sub(&actual).ok_or(ArithmeticError::Overflow)?;
 		let actual = Self::decrease_balance(who, actual, BestEffort, Expendable, force)?;
@@ -276,7 +297,11 @@ pub trait Mutate<AccountId>: Inspect<AccountId> + Unbalanced<AccountId> {
 	/// Because of this expectation, any metadata associated with the asset is expected to survive
 	/// the suspect-resume cycle.
 	fn shelve(who: &AccountId, amount: Self::Balance) -> Result<Self::Balance, DispatchError> {
-		let actual = Self::reducible_balance(who, Expendable, Polite).min(amount);
+		let mut reducible = Self::reducible_balance(who, Expendable, Polite);
+		if reducible < amount && Self::notify_freezers(who, amount, Expendable, Polite) {
+			reducible = Self::reducible_balance(who, Expendable, Polite);
+		}
+		let actual = reducible.min(amount);
 		ensure!(actual == amount, TokenError::FundsUnavailable);
 		Self::total_issuance().checked_sub(&actual).ok_or(ArithmeticError::Overflow)?;
 		let actual = Self::decrease_balance(who, actual, BestEffort, Expendable, Polite)?;
@@ -310,7 +335,13 @@ pub trait Mutate<AccountId>: Inspect<AccountId> + Unbalanced<AccountId> {
 		amount: Self::Balance,
 		preservation: Preservation,
 	) -> Result<Self::Balance, DispatchError> {
-		let _extra = Self::can_withdraw(source, amount).into_result(preservation != Expendable)?;
+		let mut consequence = Self::can_withdraw(source, amount);
+		if consequence == WithdrawConsequence::Frozen &&
+			Self::notify_freezers(source, amount, preservation, Polite)
+		{
+			consequence = Self::can_withdraw(source, amount);
+		}
+		let _extra = consequence.into_result(preservation != Expendable)?;
 		Self::can_deposit(dest, amount, Extant).into_result()?;
 		Self::decrease_balance(source, amount, BestEffort, preservation, Polite)?;
 		// This should never fail as we checked `can_deposit` earlier. But we do a best-effort
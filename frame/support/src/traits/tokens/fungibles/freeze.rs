@@ -17,8 +17,13 @@
 
 //! The traits for putting freezes within a single fungible token class.
 
+use crate::traits::tokens::{
+	Fortitude::{Force, Polite},
+	Preservation::Expendable,
+};
+use codec::Encode;
 use scale_info::TypeInfo;
-use sp_runtime::DispatchResult;
+use sp_runtime::{DispatchError, DispatchResult, Permill, Saturating};
 
 /// Trait for inspecting a fungible asset which can be frozen. Freezing is essentially setting a
 /// minimum balance below which the total balance (inclusive of any funds placed on hold) may not
@@ -41,6 +46,19 @@ pub trait Inspect<AccountId>: super::Inspect<AccountId> {
 	/// account of `who`. This will be true as long as the implementor supports as many
 	/// concurrent freeze locks as there are possible values of `id`.
 	fn can_freeze(asset: Self::AssetId, id: &Self::Id, who: &AccountId) -> bool;
+
+	/// The total amount of the balance of `who` which is frozen.
+	///
+	/// Freezes overlap rather than stack, so this is the largest of the individual freezes rather
+	/// than their sum.
+	///
+	/// Defaults to the part of the balance that only the freezes keep from being withdrawn. This
+	/// is lower than the largest freeze where it overlaps funds on hold or the existential
+	/// deposit, so implementors keeping track of their freezes should override it.
+	fn balance_frozen_total(asset: Self::AssetId, who: &AccountId) -> Self::Balance {
+		let force = Self::reducible_balance(asset.clone(), who, Expendable, Force);
+		force.saturating_sub(Self::reducible_balance(asset, who, Expendable, Polite))
+	}
 }
 
 /// Trait for introducing, altering and removing locks to freeze an account's funds so they never
@@ -75,4 +93,40 @@ pub trait Mutate<AccountId>: Inspect<AccountId> {
 
 	/// Remove an existing lock.
 	fn thaw(asset: Self::AssetId, id: &Self::Id, who: &AccountId) -> DispatchResult;
+
+	/// Like `set_freeze`, freezing `percent` of the `balance_freezable` of `who`. Returns the
+	/// amount frozen.
+	///
+	/// The amount is taken once, when this is called: the freeze does not follow later changes of
+	/// the balance of `who`.
+	fn set_freeze_percent(
+		asset: Self::AssetId,
+		id: &Self::Id,
+		who: &AccountId,
+		percent: Permill,
+	) -> Result<Self::Balance, DispatchError> {
+		let amount = percent * Self::balance_freezable(asset.clone(), who);
+		Self::set_freeze(asset, id, who, amount)?;
+		Ok(amount)
+	}
+
+	/// Move the freeze of `who` under `old_id` to `new_id`, thawing `old_id`. If there already is
+	/// a freeze under `new_id`, the larger of the two amounts is kept.
+	///
+	/// The new freeze is in place before the old one is removed, so the funds of `who` are never
+	/// left unfrozen in between. Should the thaw fail, the new freeze is not rolled back, so this
+	/// is best called from within a storage layer.
+	fn migrate_freeze(
+		asset: Self::AssetId,
+		old_id: &Self::Id,
+		new_id: &Self::Id,
+		who: &AccountId,
+	) -> DispatchResult {
+		if old_id.encode() == new_id.encode() {
+			return Ok(())
+		}
+		let amount = Self::balance_frozen(asset.clone(), old_id, who);
+		Self::extend_freeze(asset.clone(), new_id, who, amount)?;
+		Self::thaw(asset, old_id, who)
+	}
 }
@@ -23,18 +23,24 @@ use frame_support::{
 	ord_parameter_types,
 	pallet_prelude::*,
 	parameter_types,
-	traits::{AsEnsureOriginWithArg, ConstU32, ConstU64, ConstU8, Imbalance, OnUnbalanced},
+	traits::{
+		AsEnsureOriginWithArg, ConstBool, ConstU32, ConstU64, ConstU8, Imbalance, OnUnbalanced,
+	},
 	weights::{Weight, WeightToFee as WeightToFeeT},
 	PalletId,
 };
 use frame_system as system;
 use frame_system::{EnsureRoot, EnsureSignedBy};
-use pallet_asset_conversion::{NativeOrAssetId, NativeOrAssetIdConverter};
+use pallet_asset_conversion::{
+	EventLevel, KeepAlivePolicy, NativeOrAssetId, NativeOrAssetIdConverter,
+};
 use pallet_transaction_payment::CurrencyAdapter;
 use sp_core::H256;
 use sp_runtime::{
+	bounded_vec,
+	testing::{TestSignature, UintAuthorityId},
 	traits::{AccountIdConversion, BlakeTwo256, IdentityLookup, SaturatedConversion},
-	Permill,
+	BoundedVec, Permill,
 };
 
 type Block = frame_system::mocking::MockBlock<Runtime>;
@@ -119,6 +125,7 @@ impl pallet_balances::Config for Runtime {
 	type MaxReserves = ConstU32<50>;
 	type ReserveIdentifier = [u8; 8];
 	type FreezeIdentifier = ();
+	type OnFreezeViolation = ();
 	type MaxFreezes = ();
 	type RuntimeHoldReason = ();
 	type MaxHolds = ();
@@ -211,7 +218,7 @@ impl pallet_assets::Config<Instance2> for Runtime {
 	type MetadataDepositPerByte = ConstU64<0>;
 	type ApprovalDeposit = ConstU64<0>;
 	type StringLimit = ConstU32<50>;
-	type Freezer = ();
+	type Freezer = pallet_assets::FrozenBalanceOf<AssetConversion>;
 	type Extra = ();
 	type WeightInfo = ();
 	type CallbackHandle = ();
@@ -225,7 +232,17 @@ parameter_types! {
 	pub storage AllowMultiAssetPools: bool = false;
 	// should be non-zero if AllowMultiAssetPools is true, otherwise can be zero
 	pub storage LiquidityWithdrawalFee: Permill = Permill::from_percent(0);
+	pub const SwapKeepAlivePolicy: KeepAlivePolicy = KeepAlivePolicy::NativeOnly;
+	pub const EventVerbosity: EventLevel = EventLevel::Full;
 	pub const MaxSwapPathLength: u32 = 4;
+	pub FeeTiers: BoundedVec<Permill, ConstU32<1>> = bounded_vec![Permill::from_parts(3_000)];
+	pub const FlashFee: Permill = Permill::from_percent(0);
+	pub const ImpactAlertThreshold: Permill = Permill::one();
+	pub const AutoCompoundPeriod: u64 = 10;
+	pub const AutoCompoundMaxSlippage: Permill = Permill::from_percent(1);
+	pub storage SwapCooldown: Option<u64> = None;
+	pub storage SwapNetworkFee: u64 = 0;
+	pub storage NetworkFeeReceiver: Option<u64> = None;
 }
 
 ord_parameter_types! {
@@ -241,14 +258,45 @@ impl pallet_asset_conversion::Config for Runtime {
 	type Assets = Assets;
 	type PoolAssets = PoolAssets;
 	type PalletId = AssetConversionPalletId;
+	type OffchainSignature = TestSignature;
+	type OffchainPublic = UintAuthorityId;
 	type WeightInfo = ();
+	type FeeTiers = FeeTiers;
+	type MaxFeeTiers = ConstU32<1>;
 	type LPFee = ConstU32<3>; // means 0.3%
 	type PoolSetupFee = ConstU64<100>; // should be more or equal to the existential deposit
 	type PoolSetupFeeReceiver = AssetConversionOrigin;
+	type SwapNetworkFee = SwapNetworkFee;
+	type NetworkFeeReceiver = NetworkFeeReceiver;
 	type LiquidityWithdrawalFee = LiquidityWithdrawalFee;
+	type AllowSingleSidedDust = ConstBool<false>;
+	type SwapKeepAlivePolicy = SwapKeepAlivePolicy;
+	type EventVerbosity = EventVerbosity;
 	type AllowMultiAssetPools = AllowMultiAssetPools;
 	type MaxSwapPathLength = MaxSwapPathLength;
 	type MintMinLiquidity = ConstU64<100>; // 100 is good enough when the main currency has 12 decimals.
+	type LpTransferPolicy = frame_support::traits::Everything;
+	type AssetAllowList = frame_support::traits::Everything;
+	type MaxReserve = ConstU64<{ u64::MAX }>;
+	type AccountDerivation = pallet_asset_conversion::HashedPoolAccount;
+	type GovernanceOrigin = frame_system::EnsureRoot<u64>;
+	type ProtocolTreasury = ConstU64<1000>;
+	type LpTokenDecimals = ConstU8<12>;
+	type OnLpBalanceChange = ();
+	type OnReserveChange = ();
+	type FlashBorrower = ();
+	type FlashFee = FlashFee;
+	type MaxPriceMovePerSwap = ();
+	type ImpactAlertThreshold = ImpactAlertThreshold;
+	type QuoteCacheSize = ConstU32<0>;
+	type MaxLockDuration = ConstU64<100>;
+	type SwapCooldown = SwapCooldown;
+	type AutoCompoundTreasury = ();
+	type AutoCompoundPeriod = AutoCompoundPeriod;
+	type AutoCompoundMaxSlippage = AutoCompoundMaxSlippage;
+	type MaxLpMintPerTx = ();
+	type MaxLpRatioImbalance = ();
+	type MaxPoolsPerBatch = ConstU32<5>;
 
 	type Balance = u64;
 	type HigherPrecisionBalance = u128;
@@ -257,7 +305,37 @@ impl pallet_asset_conversion::Config for Runtime {
 	type MultiAssetIdConverter = NativeOrAssetIdConverter<u32>;
 
 	pallet_asset_conversion::runtime_benchmarks_enabled! {
-		type BenchmarkHelper = ();
+		type BenchmarkHelper = AssetConversionBenchmarkHelper;
+	}
+}
+
+pallet_asset_conversion::runtime_benchmarks_enabled! {
+	/// Signs permits with `TestSignature`s in the benchmarks of the asset conversion pallet.
+	pub struct AssetConversionBenchmarkHelper;
+	impl
+		pallet_asset_conversion::BenchmarkHelper<
+			u32,
+			NativeOrAssetId<u32>,
+			UintAuthorityId,
+			AccountId,
+			TestSignature,
+		> for AssetConversionBenchmarkHelper
+	{
+		fn asset_id(asset_id: u32) -> u32 {
+			asset_id
+		}
+
+		fn multiasset_id(asset_id: u32) -> NativeOrAssetId<u32> {
+			NativeOrAssetId::Asset(asset_id)
+		}
+
+		fn signer() -> (UintAuthorityId, AccountId) {
+			(UintAuthorityId(7), 7)
+		}
+
+		fn sign(signer: &UintAuthorityId, message: &[u8]) -> TestSignature {
+			TestSignature(signer.0, message.to_vec())
+		}
 	}
 }
 
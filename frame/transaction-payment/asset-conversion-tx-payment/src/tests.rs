@@ -120,12 +120,19 @@ fn setup_lp(asset_id: u32, balance_factor: u64) {
 
 	let token_1 = NativeOrAssetId::Native;
 	let token_2 = NativeOrAssetId::Asset(asset_id);
-	assert_ok!(AssetConversion::create_pool(RuntimeOrigin::signed(lp_provider), token_1, token_2));
+	let fee_tier = FeeTiers::get()[0];
+	assert_ok!(AssetConversion::create_pool(
+		RuntimeOrigin::signed(lp_provider),
+		token_1,
+		token_2,
+		fee_tier
+	));
 
 	assert_ok!(AssetConversion::add_liquidity(
 		RuntimeOrigin::signed(lp_provider),
 		token_1,
 		token_2,
+		fee_tier,
 		1_000 * balance_factor,  // 1 desired
 		10_000 * balance_factor, // 2 desired
 		1,                       // 1 min
@@ -442,6 +449,70 @@ fn asset_transaction_payment_with_tip_and_refund() {
 		});
 }
 
+#[test]
+fn asset_transaction_payment_is_exempt_from_the_swap_cooldown() {
+	let base_weight = 5;
+	let balance_factor = 100;
+	ExtBuilder::default()
+		.balance_factor(balance_factor)
+		.base_weight(Weight::from_parts(base_weight, 0))
+		.build()
+		.execute_with(|| {
+			// create the asset
+			let asset_id = 1;
+			let min_balance = 2;
+			assert_ok!(Assets::force_create(
+				RuntimeOrigin::root(),
+				asset_id.into(),
+				42,   /* owner */
+				true, /* is_sufficient */
+				min_balance,
+			));
+
+			setup_lp(asset_id, balance_factor);
+			SwapCooldown::set(&Some(10));
+
+			// mint into the caller account
+			let caller = 2;
+			let beneficiary = <Runtime as system::Config>::Lookup::unlookup(caller);
+			let balance = 10000;
+			assert_ok!(Assets::mint_into(asset_id.into(), &beneficiary, balance));
+
+			let len = 10;
+			let pre = ChargeAssetTxPayment::<Runtime>::from(0, Some(asset_id))
+				.pre_dispatch(&caller, CALL, &info_from_weight(WEIGHT_100), len)
+				.unwrap();
+
+			// the fee swap does not start a cooldown for the swap of the transaction itself.
+			assert_ok!(AssetConversion::swap_exact_tokens_for_tokens(
+				RuntimeOrigin::signed(caller),
+				vec![NativeOrAssetId::Native, NativeOrAssetId::Asset(asset_id)].try_into().unwrap(),
+				100,
+				1,
+				caller,
+				true,
+			));
+
+			// and the refund is still swapped back into the asset.
+			let asset_balance = Assets::balance(asset_id, caller);
+			let expected_token_refund = AssetConversion::quote_price_exact_tokens_for_tokens(
+				NativeOrAssetId::Native,
+				NativeOrAssetId::Asset(asset_id),
+				50, // the weight left unused
+				true,
+			)
+			.unwrap();
+			assert_ok!(ChargeAssetTxPayment::<Runtime>::post_dispatch(
+				Some(pre),
+				&info_from_weight(WEIGHT_100),
+				&post_info_from_weight(WEIGHT_50),
+				len,
+				&Ok(())
+			));
+			assert_eq!(Assets::balance(asset_id, caller), asset_balance + expected_token_refund);
+		});
+}
+
 #[test]
 fn payment_from_account_with_only_assets() {
 	let base_weight = 5;
@@ -518,6 +589,67 @@ fn payment_from_account_with_only_assets() {
 		});
 }
 
+#[test]
+fn payment_from_account_with_only_assets_is_not_charged_the_swap_network_fee() {
+	let base_weight = 5;
+	let balance_factor = 100;
+	ExtBuilder::default()
+		.balance_factor(balance_factor)
+		.base_weight(Weight::from_parts(base_weight, 0))
+		.build()
+		.execute_with(|| {
+			// create the asset
+			let asset_id = 1;
+			let min_balance = 2;
+			assert_ok!(Assets::force_create(
+				RuntimeOrigin::root(),
+				asset_id.into(),
+				42,   /* owner */
+				true, /* is_sufficient */
+				min_balance,
+			));
+
+			setup_lp(asset_id, balance_factor);
+			let receiver = 6;
+			SwapNetworkFee::set(&50);
+			NetworkFeeReceiver::set(&Some(receiver));
+
+			// mint into the caller account
+			let caller = 333;
+			let beneficiary = <Runtime as system::Config>::Lookup::unlookup(caller);
+			let balance = 1000;
+			assert_ok!(Assets::mint_into(asset_id.into(), &beneficiary, balance));
+			assert_eq!(Balances::free_balance(caller), 0);
+
+			let len = 10;
+			let fee_in_native = base_weight + 5 + len as u64;
+			let ed = Balances::minimum_balance();
+			let fee_in_asset = AssetConversion::quote_price_tokens_for_exact_tokens(
+				NativeOrAssetId::Asset(asset_id),
+				NativeOrAssetId::Native,
+				fee_in_native + ed,
+				true,
+			)
+			.unwrap();
+
+			let pre = ChargeAssetTxPayment::<Runtime>::from(0, Some(asset_id))
+				.pre_dispatch(&caller, CALL, &info_from_weight(WEIGHT_5), len)
+				.unwrap();
+			assert_eq!(Assets::balance(asset_id, caller), balance - fee_in_asset);
+			assert_ok!(ChargeAssetTxPayment::<Runtime>::post_dispatch(
+				Some(pre),
+				&info_from_weight(WEIGHT_5),
+				&default_post_info(),
+				len,
+				&Ok(())
+			));
+
+			// neither the fee swap nor the refund swap paid the network fee.
+			assert_eq!(Balances::free_balance(receiver), 60 * balance_factor);
+			assert_eq!(FeeUnbalancedAmount::get(), fee_in_native);
+		});
+}
+
 #[test]
 fn converted_fee_is_never_zero_if_input_fee_is_not() {
 	let base_weight = 1;
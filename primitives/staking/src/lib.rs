@@ -68,6 +68,24 @@ pub struct Stake<Balance> {
 	pub active: Balance,
 }
 
+impl<Balance: Saturating + Copy + PartialEq> Stake<Balance> {
+	/// The part of the stake that is in the process of unbonding.
+	pub fn unbonding(&self) -> Balance {
+		self.total.saturating_sub(self.active)
+	}
+
+	/// Whether nothing is unbonding, i.e. all of the stake is active.
+	pub fn is_fully_bonded(&self) -> bool {
+		self.total == self.active
+	}
+
+	/// The stake after unbonding `amount` of it. At most the active stake can be unbonded, the
+	/// total is unchanged until the unbonded funds are withdrawn.
+	pub fn with_unbond(&self, amount: Balance) -> Self {
+		Self { total: self.total, active: self.active.saturating_sub(amount) }
+	}
+}
+
 /// A generic staking event listener.
 ///
 /// Note that the interface is designed in a way that the events are fired post-action, so any
@@ -81,6 +99,12 @@ pub trait OnStakingUpdate<AccountId, Balance> {
 	/// unbonding.
 	fn on_stake_update(_who: &AccountId, _prev_stake: Option<Stake<Balance>>) {}
 
+	/// Fired when someone bonds `amount` of new funds on top of their existing bond.
+	///
+	/// This is fired in addition to, not instead of, `on_stake_update`, so consumers only
+	/// interested in new funds don't need to reconstruct the difference from the previous stake.
+	fn on_bond_extra(_who: &AccountId, _amount: Balance) {}
+
 	/// Fired when someone sets their intention to nominate.
 	///
 	/// This should never be fired for existing nominators.
@@ -115,6 +139,12 @@ pub trait OnStakingUpdate<AccountId, Balance> {
 	/// Fired when someone is fully unstaked.
 	fn on_unstake(_who: &AccountId) {}
 
+	/// Fired when a staker is rewarded.
+	///
+	/// This is fired post-payout, with `amount` being what was actually credited for the stash
+	/// `who`, regardless of the reward destination.
+	fn on_reward(_who: &AccountId, _amount: Balance) {}
+
 	/// Fired when a staker is slashed.
 	///
 	/// * `stash` - The stash of the staker whom the slash was applied to.
@@ -160,6 +190,12 @@ pub trait StakingInterface {
 	/// The minimum amount required to bond in order to set validation intentions.
 	fn minimum_validator_bond() -> Self::Balance;
 
+	/// The smallest active stake that made it into the voters of the last election.
+	///
+	/// This is only updated by elections, so it may be stale in between. It gives an idea of how
+	/// much needs to be bonded to be competitive.
+	fn minimum_active_stake() -> Self::Balance;
+
 	/// Return a stash account that is controlled by a `controller`.
 	///
 	/// ## Note
@@ -176,6 +212,22 @@ pub trait StakingInterface {
 	/// This should be the latest planned era that the staking system knows about.
 	fn current_era() -> EraIndex;
 
+	/// The active era index.
+	///
+	/// This is the era rewards are paid and slashes are applied for. It lags the
+	/// [`Self::current_era`] by one from the moment the next era is planned until it starts, and
+	/// equals it otherwise.
+	fn active_era() -> EraIndex;
+
+	/// The sum of the active stake of all stakers.
+	///
+	/// This does not include funds that are in the process of unbonding, i.e. it is the sum of
+	/// [`Stake::active`] of every staker. Useful for computing the staking ratio.
+	///
+	/// Implementations must make this cheap, i.e. a single storage read, so that it can be called
+	/// from within a block. It must not iterate over all stakers.
+	fn total_staked() -> Self::Balance;
+
 	/// Returns the [`Stake`] of `who`.
 	fn stake(who: &Self::AccountId) -> Result<Stake<Self::Balance>, DispatchError>;
 
@@ -194,6 +246,35 @@ pub trait StakingInterface {
 		Self::stake(who).map(|s| s.active != s.total)
 	}
 
+	/// The funds of a staker that are scheduled to unlock, as a list of the era at which they
+	/// unlock and the amount. `Err` if not a staker.
+	///
+	/// The default implementation reports all unbonding funds as a single chunk unlocking at
+	/// [`Self::current_era`] + [`Self::bonding_duration`], the latest era they can unlock at.
+	/// Implementations that track the individual chunks should override it.
+	fn unlocking(who: &Self::AccountId) -> Result<Vec<(EraIndex, Self::Balance)>, DispatchError> {
+		let stake = Self::stake(who)?;
+		let unbonding = stake.total.saturating_sub(stake.active);
+		if unbonding == Self::Balance::default() {
+			return Ok(Vec::new())
+		}
+		let era = Self::current_era().saturating_add(Self::bonding_duration());
+		Ok(sp_std::vec![(era, unbonding)])
+	}
+
+	/// The number of eras until the next unlocking chunk of `who` becomes withdrawable, `Err` if
+	/// not a staker.
+	///
+	/// Returns `None` if nothing is unbonding, and `Some(0)` if some funds can already be
+	/// withdrawn via [`Self::withdraw_unbonded`].
+	fn eras_to_next_withdraw(who: &Self::AccountId) -> Result<Option<EraIndex>, DispatchError> {
+		let current_era = Self::current_era();
+		Ok(Self::unlocking(who)?
+			.into_iter()
+			.map(|(era, _)| era.saturating_sub(current_era))
+			.min())
+	}
+
 	/// Returns whether a staker is FULLY unbonding, `Err` if not a staker at all.
 	fn fully_unbond(who: &Self::AccountId) -> DispatchResult {
 		Self::unbond(who, Self::stake(who)?.active)
@@ -203,12 +284,28 @@ pub trait StakingInterface {
 	fn bond(who: &Self::AccountId, value: Self::Balance, payee: &Self::AccountId)
 		-> DispatchResult;
 
+	/// The account the rewards of `who` are paid to, `Err` if not a staker.
+	///
+	/// This is `who` itself if the rewards are paid into the stash, or the external account
+	/// given as `payee` to [`Self::bond`] otherwise. Also `Err` if `who` receives no rewards.
+	fn payee(who: &Self::AccountId) -> Result<Self::AccountId, DispatchError>;
+
 	/// Have `who` nominate `validators`.
 	fn nominate(who: &Self::AccountId, validators: Vec<Self::AccountId>) -> DispatchResult;
 
 	/// Chill `who`.
 	fn chill(who: &Self::AccountId) -> DispatchResult;
 
+	/// Chill `who` on behalf of anyone, not `who` itself.
+	///
+	/// This is permissionless, so implementations must only allow it for a `who` that no longer
+	/// meets the bond required of its role: a nominator with less active stake than
+	/// [`Self::minimum_nominator_bond`], or a validator with less than
+	/// [`Self::minimum_validator_bond`]. Any other staker can not be chilled this way.
+	/// Implementations may restrict it further, e.g. to when the number of stakers of that role
+	/// gets close to its maximum, like the `chill_other` extrinsic of pallet-staking does.
+	fn chill_other(who: &Self::AccountId) -> DispatchResult;
+
 	/// Bond some extra amount in `who`'s free balance against the active bonded balance of
 	/// the account. The amount extra actually bonded will never be more than `who`'s free
 	/// balance.
@@ -245,9 +342,32 @@ pub trait StakingInterface {
 	/// Checks whether an account `staker` has been exposed in an era.
 	fn is_exposed_in_era(who: &Self::AccountId, era: &EraIndex) -> bool;
 
+	/// Returns the subset of `eras` in which `who` has been exposed.
+	///
+	/// The default implementation checks each era via [`Self::is_exposed_in_era`].
+	fn exposed_eras(who: &Self::AccountId, eras: &[EraIndex]) -> Vec<EraIndex> {
+		eras.iter().filter(|era| Self::is_exposed_in_era(who, era)).copied().collect()
+	}
+
 	/// Return the status of the given staker, `None` if not staked at all.
 	fn status(who: &Self::AccountId) -> Result<StakerStatus<Self::AccountId>, DispatchError>;
 
+	/// Returns up to `limit` of the validator candidates, starting at the opaque cursor
+	/// `start_key`, or at the first candidate if `None`.
+	///
+	/// Also returns the cursor to pass for the next page, `None` once there are no candidates
+	/// left. For storage-backed implementations this is a raw storage key, so that each page
+	/// costs only what it returns and paging stays stable across blocks even if candidates are
+	/// added in between.
+	///
+	/// These are the accounts that declared their intention to validate, not the active set:
+	/// they only become active, if elected, at the next election, and chilled validators are
+	/// only removed from the active set at the end of their era.
+	fn validators_paged(
+		start_key: Option<Vec<u8>>,
+		limit: u32,
+	) -> (Vec<Self::AccountId>, Option<Vec<u8>>);
+
 	/// Checks whether or not this is a validator account.
 	fn is_validator(who: &Self::AccountId) -> bool {
 		Self::status(who).map(|s| matches!(s, StakerStatus::Validator)).unwrap_or(false)
@@ -273,3 +393,36 @@ pub trait StakingInterface {
 }
 
 sp_core::generate_feature_enabled_macro!(runtime_benchmarks_enabled, feature = "runtime-benchmarks", $);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn unbonding_is_the_inactive_stake() {
+		assert_eq!(Stake { total: 100u32, active: 100 }.unbonding(), 0);
+		assert_eq!(Stake { total: 100u32, active: 60 }.unbonding(), 40);
+		assert_eq!(Stake { total: 100u32, active: 0 }.unbonding(), 100);
+	}
+
+	#[test]
+	fn is_fully_bonded_works() {
+		assert!(Stake { total: 100u32, active: 100 }.is_fully_bonded());
+		assert!(Stake { total: 0u32, active: 0 }.is_fully_bonded());
+		assert!(!Stake { total: 100u32, active: 99 }.is_fully_bonded());
+	}
+
+	#[test]
+	fn with_unbond_moves_active_stake_to_unbonding() {
+		let stake = Stake { total: 100u32, active: 80 };
+
+		let unbonded = stake.with_unbond(30);
+		assert_eq!(unbonded, Stake { total: 100, active: 50 });
+		assert_eq!(unbonded.unbonding(), 50);
+		assert_eq!(stake.with_unbond(0), stake);
+
+		// no more than the active stake can be unbonded.
+		assert_eq!(stake.with_unbond(200), Stake { total: 100, active: 0 });
+		assert_eq!(stake.with_unbond(200).unbonding(), 100);
+	}
+}
@@ -20,10 +20,14 @@
 
 use codec::{Decode, Encode};
 use sp_core::Get;
-use sp_runtime::{transaction_validity::TransactionValidityError, DispatchError, Perbill};
-use sp_std::vec::Vec;
+use sp_runtime::{
+	traits::{AtLeast32BitUnsigned, Saturating},
+	transaction_validity::TransactionValidityError,
+	DispatchError, Perbill,
+};
+use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
 
-use crate::SessionIndex;
+use crate::{EraIndex, OnStakingUpdate, SessionIndex, StakingInterface};
 
 /// The kind of an offence, is a byte string representing some kind identifier
 /// e.g. `b"im-online:offlin"`, `b"babe:equivocatio"`
@@ -274,3 +278,55 @@ impl<Reporter, Evidence> OffenceReportSystem<Reporter, Evidence> for () {
 		Ok(())
 	}
 }
+
+/// Compute the slashes of an offence committed in `slash_era` and notify `H` of them.
+///
+/// `offenders` and `slash_fraction` are paired by index, as in [`OnOffenceHandler::on_offence`].
+/// `slash_era` is the era of the session the offence was reported for. The caller resolves it,
+/// like the staking system does in `on_offence`, and should not apply offences that are older
+/// than the bonded eras.
+///
+/// Each offender's active stake is slashed by its fraction, and so is every unlocking chunk that
+/// was still exposed at `slash_era`, i.e. that unlocks at or after `slash_era + bonding_duration`.
+/// Chunks unbonded before the offence are left untouched. `H::on_slash` then receives the
+/// post-slash active balance and the post-slash value of each affected chunk.
+///
+/// Offenders that are no longer staking are skipped. This only computes and reports the slash;
+/// moving the slashed funds is left to the staking system.
+///
+/// Returns the total amount slashed from each offender that was processed.
+pub fn apply_offence_slash<S, H, Reporter, FullIdentification>(
+	offenders: &[OffenceDetails<Reporter, (S::AccountId, FullIdentification)>],
+	slash_fraction: &[Perbill],
+	slash_era: EraIndex,
+) -> Vec<(S::AccountId, S::Balance)>
+where
+	S: StakingInterface,
+	S::Balance: AtLeast32BitUnsigned,
+	H: OnStakingUpdate<S::AccountId, S::Balance>,
+{
+	let exposed_from = slash_era.saturating_add(S::bonding_duration());
+
+	offenders
+		.iter()
+		.zip(slash_fraction)
+		.filter_map(|(details, fraction)| {
+			let who = &details.offender.0;
+			let stake = S::stake(who).ok()?;
+			let unlocking = S::unlocking(who).ok()?;
+
+			let mut slashed = *fraction * stake.active;
+			let active = stake.active.saturating_sub(slashed);
+
+			let mut slashed_chunks = BTreeMap::new();
+			for (era, value) in unlocking.into_iter().filter(|(era, _)| *era >= exposed_from) {
+				let chunk_slash = *fraction * value;
+				slashed = slashed.saturating_add(chunk_slash);
+				slashed_chunks.insert(era, value.saturating_sub(chunk_slash));
+			}
+
+			H::on_slash(who, active, &slashed_chunks);
+			Some((who.clone(), slashed))
+		})
+		.collect()
+}